@@ -0,0 +1,296 @@
+use actix_web::{get, post, web, HttpResponse, Responder};
+use bitcoin::Amount;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use validator::Validate;
+
+use crate::{
+    api::btc::{
+        handler::{empty_result_response, rpc_call_url, rpc_error_response, ErrorResponse, Utxo},
+        model::{
+            DeriveAddresses, DescriptorBalance, DescriptorInfo, DescriptorInfoResult,
+            ImportDescriptors, ListDescriptors, ListUnspent, ListUnspentResult,
+        },
+        wallet::{invalid_url_response, wallet_error_response, wallet_url},
+    },
+    config::BitcoinRpcConfig,
+    request::RequestClient,
+};
+
+// addresses derived from a ranged descriptor when no range end is given
+const DEFAULT_RANGE_END: u32 = 999;
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct DescriptorImport {
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub desc: Option<String>,
+
+    // [start, end] of a ranged descriptor
+    pub range: Option<[u32; 2]>,
+
+    pub internal: Option<bool>,
+    pub active: Option<bool>,
+    pub label: Option<String>,
+
+    // unix time to rescan the chain from, 0 for a full rescan, no rescan when missing
+    pub rescan_from: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ImportDescriptorsRequest {
+    #[validate]
+    #[validate(
+        required,
+        length(min = 1, max = 100, message = "must be between 1 and 100")
+    )]
+    pub descriptors: Option<Vec<DescriptorImport>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct DescriptorQuery {
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub desc: Option<String>,
+
+    // last index derived from a ranged descriptor
+    #[validate(range(max = 9999, message = "must be at most 9999"))]
+    pub range_end: Option<u32>,
+
+    pub min_conf: Option<u32>,
+}
+
+fn without_checksum(desc: &str) -> &str {
+    desc.split('#').next().unwrap_or(desc)
+}
+
+// checks the descriptor on the node, watch-only wallets can't hold private keys
+async fn descriptor_info(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    desc: &str,
+) -> Result<DescriptorInfoResult, HttpResponse> {
+    let payload = json!({ "jsonrpc": "2.0",  "method": "getdescriptorinfo", "params": [desc]});
+
+    match rpc_call_url::<DescriptorInfo>(
+        rq_client,
+        btc_rpc_cfg,
+        &btc_rpc_cfg.bitcoin_rpc_url_one,
+        &payload,
+    )
+    .await?
+    {
+        DescriptorInfo {
+            result: Some(info), ..
+        } if info.hasprivatekeys => Err(HttpResponse::BadRequest().json(ErrorResponse {
+            message: format!("descriptor {} contains private keys", info.descriptor),
+        })),
+        DescriptorInfo {
+            result: Some(info), ..
+        } => Ok(info),
+        DescriptorInfo {
+            error: Some(err), ..
+        } => Err(rpc_error_response(err)),
+        _ => Err(empty_result_response()),
+    }
+}
+
+#[post("/wallets/{name}/descriptors")]
+pub(super) async fn import_descriptors(
+    path: web::Path<String>,
+    json: web::Json<ImportDescriptorsRequest>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    let url = match wallet_url(&btc_rpc_cfg, &path.into_inner()) {
+        Some(url) => url,
+        None => return invalid_url_response(),
+    };
+
+    let mut requests: Vec<Value> = Vec::new();
+    for import in json.descriptors.as_ref().unwrap() {
+        let desc = import.desc.as_ref().unwrap();
+        let info = match descriptor_info(&rq_client, &btc_rpc_cfg, desc).await {
+            Ok(info) => info,
+            Err(resp) => return resp,
+        };
+
+        let timestamp = match import.rescan_from {
+            Some(timestamp) => json!(timestamp),
+            None => json!("now"),
+        };
+
+        let mut request = json!({
+            "desc": format!("{}#{}", without_checksum(desc), info.checksum),
+            "timestamp": timestamp,
+            "active": import.active.unwrap_or(false),
+            "internal": import.internal.unwrap_or(false),
+        });
+        if info.isrange {
+            request["range"] = json!(import.range.unwrap_or([0, DEFAULT_RANGE_END]));
+        }
+        if let Some(label) = &import.label {
+            request["label"] = json!(label);
+        }
+
+        requests.push(request);
+    }
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "importdescriptors", "params": [requests]});
+
+    match rpc_call_url::<ImportDescriptors>(&rq_client, &btc_rpc_cfg, &url, &payload).await {
+        Ok(ImportDescriptors {
+            result: Some(results),
+            ..
+        }) => HttpResponse::Ok().json(results),
+        Ok(ImportDescriptors {
+            error: Some(err), ..
+        }) => wallet_error_response(err),
+        Ok(_) => empty_result_response(),
+        Err(resp) => resp,
+    }
+}
+
+#[get("/wallets/{name}/descriptors")]
+pub(super) async fn list_descriptors(
+    path: web::Path<String>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let url = match wallet_url(&btc_rpc_cfg, &path.into_inner()) {
+        Some(url) => url,
+        None => return invalid_url_response(),
+    };
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "listdescriptors", "params": []});
+
+    match rpc_call_url::<ListDescriptors>(&rq_client, &btc_rpc_cfg, &url, &payload).await {
+        Ok(ListDescriptors {
+            result: Some(list), ..
+        }) => HttpResponse::Ok().json(list.descriptors),
+        Ok(ListDescriptors {
+            error: Some(err), ..
+        }) => wallet_error_response(err),
+        Ok(_) => empty_result_response(),
+        Err(resp) => resp,
+    }
+}
+
+// utxos of the wallet paying any address derived from the descriptor
+async fn descriptor_unspents(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    wallet: &str,
+    query: &DescriptorQuery,
+) -> Result<(String, usize, Vec<ListUnspentResult>), HttpResponse> {
+    let url = wallet_url(btc_rpc_cfg, wallet).ok_or_else(invalid_url_response)?;
+
+    let desc = query.desc.as_ref().unwrap();
+    let info = descriptor_info(rq_client, btc_rpc_cfg, desc).await?;
+    let desc = format!("{}#{}", without_checksum(desc), info.checksum);
+
+    let payload = if info.isrange {
+        let range_end = query.range_end.unwrap_or(DEFAULT_RANGE_END);
+        json!({ "jsonrpc": "2.0",  "method": "deriveaddresses", "params": [desc, [0, range_end]]})
+    } else {
+        json!({ "jsonrpc": "2.0",  "method": "deriveaddresses", "params": [desc]})
+    };
+
+    let addresses = match rpc_call_url::<DeriveAddresses>(
+        rq_client,
+        btc_rpc_cfg,
+        &btc_rpc_cfg.bitcoin_rpc_url_one,
+        &payload,
+    )
+    .await?
+    {
+        DeriveAddresses {
+            result: Some(addresses),
+            ..
+        } => addresses,
+        DeriveAddresses {
+            error: Some(err), ..
+        } => return Err(rpc_error_response(err)),
+        _ => return Err(empty_result_response()),
+    };
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "listunspent", "params": [
+        query.min_conf.unwrap_or(1),
+        9999999,
+        addresses,
+    ]});
+
+    match rpc_call_url::<ListUnspent>(rq_client, btc_rpc_cfg, &url, &payload).await? {
+        ListUnspent {
+            result: Some(unspents),
+            ..
+        } => Ok((desc, addresses.len(), unspents)),
+        ListUnspent {
+            error: Some(err), ..
+        } => Err(wallet_error_response(err)),
+        _ => Err(empty_result_response()),
+    }
+}
+
+#[get("/wallets/{name}/descriptors/utxos")]
+pub(super) async fn descriptor_utxos(
+    path: web::Path<String>,
+    query: web::Query<DescriptorQuery>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    if let Err(err) = query.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    match descriptor_unspents(&rq_client, &btc_rpc_cfg, &path.into_inner(), &query).await {
+        Ok((_, _, unspents)) => HttpResponse::Ok().json(
+            unspents
+                .into_iter()
+                .map(|unspent| Utxo {
+                    tx_id: Some(unspent.txid),
+                    vout: Some(unspent.vout),
+                    amount: Some(unspent.amount),
+                    pk_script: Some(unspent.script_pub_key),
+                    witness_script: unspent.witness_script,
+                    leaf_script: None,
+                    control_block: None,
+                })
+                .collect::<Vec<_>>(),
+        ),
+        Err(resp) => resp,
+    }
+}
+
+#[get("/wallets/{name}/descriptors/balance")]
+pub(super) async fn descriptor_balance(
+    path: web::Path<String>,
+    query: web::Query<DescriptorQuery>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    if let Err(err) = query.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    match descriptor_unspents(&rq_client, &btc_rpc_cfg, &path.into_inner(), &query).await {
+        Ok((descriptor, address_count, unspents)) => {
+            // summed in sats, float BTC amounts don't add up exactly
+            let balance = unspents
+                .iter()
+                .filter_map(|unspent| Amount::from_btc(unspent.amount).ok())
+                .map(Amount::to_sat)
+                .sum();
+
+            HttpResponse::Ok().json(DescriptorBalance {
+                descriptor,
+                balance: Amount::from_sat(balance).to_btc(),
+                utxo_count: unspents.len(),
+                address_count,
+            })
+        }
+        Err(resp) => resp,
+    }
+}
@@ -1,32 +1,108 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, str::FromStr, time::Duration};
 
-use actix_web::{get, http, post, web, HttpResponse, Responder};
+use actix_web::{get, http, post, rt, web, web::Bytes, HttpResponse, Responder};
 use base64::encode;
+use bitcoin::{
+    consensus, hashes::hex::FromHex, psbt::Psbt, secp256k1::Secp256k1, Address, Amount, BlockHash,
+    Network, Txid,
+};
+use futures::future::join_all;
 use log::error;
-use regex::Regex;
-use serde::{Deserialize, Serialize};
-use serde_json::json;
-use validator::Validate;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use validator::{Validate, ValidationError};
 
 use crate::{
-    api::btc::{
-        model::{BlockchainInfo, FeeRate, SendTx, SignTx},
-        service::create_transaction,
+    api::{
+        btc::{
+            descriptor,
+            hd::{self, HdKey, HdNetwork, ScriptType},
+            index, keys, message,
+            model::{
+                AddressBalance, BlockHeader, BlockInfo, BlockPage, BlockResult, BlockVerbosity,
+                BlockchainInfo, BlockchainInfoResult, Broadcast, BroadcastNodeResult,
+                BroadcastStatus, FeeEstimate, FeeEstimateMode, FeeRate, FeeSource, GetBlockHash,
+                MempoolEntry, MempoolEntryDetails, MempoolInfo, MempoolTxIds, RPCError, RawBlock,
+                RawFormat, RawMempool, RawTransaction, RawTransactionHex, RawTransactionResult,
+                ScanTxOutSet, ScanTxOutSetResult, SendTx, SignTx, SignTxResult, TestMempoolAccept,
+                TxOut, TxStatus, UtxoStatus,
+            },
+            multisig,
+            node::chain_status,
+            rest::{self, RestError},
+            rpc, script,
+            service::{chain_network, create_transaction, fee_histogram},
+            taproot, wallet, watch, ws,
+        },
+        chain::{handler::FeeQuery, model::ChainStatus},
     },
     config::BitcoinRpcConfig,
-    request::RequestClient,
+    events::tip::TipTracker,
+    request::{Headers, RequestClient},
 };
 
+// mounted before the generic chain routes, the shared paths keep their bitcoin contracts
+// and also take the generic bodies
 pub fn init(cfg: &mut web::ServiceConfig) {
     cfg.service(status);
     cfg.service(create_tx);
     cfg.service(sign_tx);
     cfg.service(send_tx);
+    cfg.service(test_tx);
+    cfg.service(tx_status);
+    cfg.service(block_by_height);
+    cfg.service(block_header);
+    cfg.service(block_raw);
+    cfg.service(tx_raw);
+    cfg.service(utxo);
+    cfg.service(block_by_hash);
+    cfg.service(address_utxos);
+    cfg.service(address_balance);
+    cfg.service(mempool_info);
+    cfg.service(mempool_histogram);
+    cfg.service(mempool_entry);
+    cfg.service(fees);
+    cfg.service(reorgs);
+    cfg.service(ws::ws);
+    cfg.service(watch::create_watch);
+    cfg.service(watch::list_watches);
+    cfg.service(watch::get_watch);
+    cfg.service(watch::delete_watch);
+    cfg.service(watch::watch_deliveries);
+    cfg.service(index::index_status);
+    cfg.service(index::index_address);
+    cfg.service(index::indexed_addresses);
+    cfg.service(index::remove_indexed_address);
+    cfg.service(index::indexed_utxos);
+    cfg.service(index::indexed_balance);
+    cfg.service(index::indexed_history);
+    cfg.service(wallet::list_wallets);
+    cfg.service(wallet::create_wallet);
+    cfg.service(wallet::load_wallet);
+    cfg.service(wallet::unload_wallet);
+    cfg.service(wallet::wallet_utxos);
+    cfg.service(wallet::wallet_balances);
+    cfg.service(wallet::wallet_new_address);
+    cfg.service(wallet::wallet_transactions);
+    cfg.service(descriptor::import_descriptors);
+    cfg.service(descriptor::list_descriptors);
+    cfg.service(descriptor::descriptor_utxos);
+    cfg.service(descriptor::descriptor_balance);
+    cfg.service(hd::derive);
+    cfg.service(keys::generate_mnemonic);
+    cfg.service(keys::derive_key);
+    cfg.service(message::sign);
+    cfg.service(message::verify);
+    cfg.service(script::address_details);
+    cfg.service(script::script_decode);
+    cfg.service(multisig::create_multisig);
+    cfg.service(taproot::taproot_sign);
+    cfg.service(rpc::rpc_passthrough);
 }
 
 #[derive(Serialize)]
-struct ErrorResponse {
-    message: String,
+pub(crate) struct ErrorResponse {
+    pub(crate) message: String,
 }
 
 #[derive(Serialize)]
@@ -34,16 +110,86 @@ struct OkResponse {
     result: String,
 }
 
-#[get("/status")]
-async fn status(
-    rq_client: web::Data<RequestClient>,
-    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
-) -> impl Responder {
+#[derive(Serialize)]
+struct CreateTxResponse {
+    result: String,
+    // base64, carries the spent outputs and witness scripts for cosigners
+    psbt: String,
+    // fields of the generic create-tx response, hex is the same as result
+    hex: String,
+    fee_rate: f64,
+}
+
+#[derive(Serialize)]
+struct StatusResponse {
+    #[serde(flatten)]
+    info: BlockchainInfo,
+    // fields of the generic status response
+    #[serde(flatten)]
+    status: ChainStatus,
+}
+
+#[derive(Serialize)]
+struct FeeResponse {
+    #[serde(flatten)]
+    estimate: FeeEstimate,
+    // field of the generic fees response, same as btc_per_kvb
+    fee_rate: f64,
+}
+
+// confirmation target create-tx pays for
+const CREATE_TX_FEE_TARGET: u16 = 4;
+
+// bitcoin core error code for unknown tx, block or address
+const RPC_INVALID_ADDRESS_OR_KEY: isize = -5;
+// bitcoin core error code for out of range params, e.g. block height above the tip
+const RPC_INVALID_PARAMETER: isize = -8;
+// bitcoin core error code for missing or spent inputs, what a mined tx gets once its
+// outputs are spent as well
+const RPC_VERIFY_ERROR: isize = -25;
+// bitcoin core error code for a tx with unspent outputs in the chain
+const RPC_VERIFY_ALREADY_IN_CHAIN: isize = -27;
+
+// a node that takes longer is reported as timed out, the others are not held back
+const BROADCAST_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub(crate) fn rpc_error_response(err: RPCError) -> HttpResponse {
+    if err.code == RPC_INVALID_ADDRESS_OR_KEY {
+        HttpResponse::NotFound().json(ErrorResponse {
+            message: err.message,
+        })
+    } else {
+        HttpResponse::BadRequest().json(ErrorResponse {
+            message: err.message,
+        })
+    }
+}
+
+pub(crate) fn empty_result_response() -> HttpResponse {
+    HttpResponse::BadRequest().json(ErrorResponse {
+        message: "empty rpc result".to_string(),
+    })
+}
+
+fn rest_error_response(err: RestError) -> HttpResponse {
+    match err {
+        RestError::NotFound(message) => HttpResponse::NotFound().json(ErrorResponse { message }),
+        RestError::BadRequest(message) => {
+            HttpResponse::BadRequest().json(ErrorResponse { message })
+        }
+        RestError::Decode => HttpResponse::BadRequest().json(ErrorResponse {
+            message: "failed to decode response".to_string(),
+        }),
+        RestError::Request => HttpResponse::RequestTimeout().json(ErrorResponse {
+            message: "failed to do request, something wrong with rest node".to_string(),
+        }),
+    }
+}
+
+pub(crate) fn rpc_headers(btc_rpc_cfg: &BitcoinRpcConfig) -> Headers {
     let mut headers = HashMap::new();
-    headers.insert(
-        "Content-Type".to_string(),
-        "application/x-www-form-urlencoded".to_string(),
-    );
+    headers.insert("Content-Type".to_string(), "application/json".to_string());
+    headers.insert("Accept".to_string(), "application/json".to_string());
 
     headers.insert(
         "Authorization".to_string(),
@@ -56,51 +202,120 @@ async fn status(
         ),
     );
 
-    let payload = json!({ "jsonrpc": "2.0",  "method": "getblockchaininfo"});
+    headers
+}
 
-    match rq_client
-        .post(&btc_rpc_cfg.bitcoin_rpc_url_one, Some(&headers), &payload)
-        .await
-    {
+// does the rpc request and decodes the body, on failure returns ready error response
+pub(crate) async fn rpc_call<T: DeserializeOwned>(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    payload: &Value,
+) -> Result<T, HttpResponse> {
+    rpc_call_url(
+        rq_client,
+        btc_rpc_cfg,
+        &btc_rpc_cfg.bitcoin_rpc_url_one,
+        payload,
+    )
+    .await
+}
+
+pub(crate) async fn rpc_call_url<T: DeserializeOwned>(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    url: &str,
+    payload: &Value,
+) -> Result<T, HttpResponse> {
+    let headers = rpc_headers(btc_rpc_cfg);
+
+    match rq_client.post(url, Some(&headers), payload).await {
         Ok(response) => {
             if response.status() == http::StatusCode::UNAUTHORIZED {
-                HttpResponse::Unauthorized().json(ErrorResponse {
+                Err(HttpResponse::Unauthorized().json(ErrorResponse {
                     message: "Unauthorized".to_string(),
-                })
+                }))
             } else {
-                match response.json::<BlockchainInfo>().await {
-                    Ok(info) => {
-                        if let Some(err) = info.error {
-                            HttpResponse::BadRequest().json(ErrorResponse {
-                                message: err.message,
-                            })
-                        } else {
-                            HttpResponse::Ok().json(info)
-                        }
-                    }
-                    Err(err) => {
-                        error!("failed to decode BlockchainInfo, {}", err);
-                        HttpResponse::BadRequest().json(ErrorResponse {
-                            message: "failed to decode response".to_string(),
-                        })
-                    }
-                }
+                response.json::<T>().await.map_err(|err| {
+                    error!("failed to decode {} response, {}", payload["method"], err);
+                    HttpResponse::BadRequest().json(ErrorResponse {
+                        message: "failed to decode response".to_string(),
+                    })
+                })
             }
         }
         Err(err) => {
             error!("request error: {}", err);
-            HttpResponse::RequestTimeout().json(ErrorResponse {
+            Err(HttpResponse::RequestTimeout().json(ErrorResponse {
                 message: "failed to do request, something wrong with rpc node".to_string(),
-            })
+            }))
         }
     }
 }
 
-// ^(bc1|[13])[a-zA-HJ-NP-Z0-9]{25,39}$ - mainnet
-// ^(tb1|[2nm]|bcrt)[a-zA-HJ-NP-Z0-9]{25,40}$ - testnet
-lazy_static::lazy_static! {
-    static ref RE_BITCOIN_ADDRESS: Regex =
-        Regex::new(r"^(tb1|[2nm]|bcrt)[a-zA-HJ-NP-Z0-9]{25,40}$").unwrap();
+#[get("/status")]
+async fn status(
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    match blockchain_info(&rq_client, &btc_rpc_cfg).await {
+        Ok(info) => HttpResponse::Ok().json(StatusResponse {
+            status: chain_status(&info),
+            info: BlockchainInfo {
+                result: Some(info),
+                error: None,
+            },
+        }),
+        Err(resp) => resp,
+    }
+}
+
+// network of the chain the node follows
+pub(super) async fn node_network(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+) -> Result<Network, HttpResponse> {
+    let info = blockchain_info(rq_client, btc_rpc_cfg).await?;
+
+    chain_network(&info.chain).ok_or_else(|| {
+        HttpResponse::InternalServerError().json(ErrorResponse {
+            message: format!("unknown node chain {}", info.chain),
+        })
+    })
+}
+
+pub(super) async fn blockchain_info(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+) -> Result<BlockchainInfoResult, HttpResponse> {
+    if let Some(rest_url) = btc_rpc_cfg.rest_url(&btc_rpc_cfg.bitcoin_rpc_url_one) {
+        return rest::chain_info(rq_client, rest_url)
+            .await
+            .map_err(rest_error_response);
+    }
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "getblockchaininfo"});
+
+    match rpc_call::<BlockchainInfo>(rq_client, btc_rpc_cfg, &payload).await? {
+        BlockchainInfo {
+            result: Some(info), ..
+        } => Ok(info),
+        BlockchainInfo {
+            error: Some(err), ..
+        } => Err(rpc_error_response(err)),
+        _ => Err(empty_result_response()),
+    }
+}
+
+// any network, create_transaction checks it against the chain of the node
+pub(super) fn validate_bitcoin_address(address: &str) -> Result<(), ValidationError> {
+    match Address::from_str(address) {
+        Ok(_) => Ok(()),
+        Err(_) => {
+            let mut err = ValidationError::new("address");
+            err.message = Some("invalid Bitcoin address".into());
+            Err(err)
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -116,14 +331,23 @@ pub struct Utxo {
 
     #[validate(required, length(min = 1, message = "cannot be empty"))]
     pub pk_script: Option<String>,
+
+    // hex, p2wsh and p2sh-p2wsh outputs only
+    #[validate(length(min = 1, message = "cannot be empty"))]
+    pub witness_script: Option<String>,
+
+    // hex, p2tr script path spends only, given together
+    #[validate(length(min = 1, message = "cannot be empty"))]
+    pub leaf_script: Option<String>,
+    #[validate(length(min = 66, message = "must be at least 33 bytes hex"))]
+    pub control_block: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct ToAddresses {
-    #[validate(
-        required,
-        regex(path = "RE_BITCOIN_ADDRESS", message = "invalid Bitcoin address")
-    )]
+    // "address" in the generic create-tx body
+    #[validate(required, custom = "validate_bitcoin_address")]
+    #[serde(alias = "address")]
     pub to_address: Option<String>,
 
     #[validate(required, range(min = 0.00000001, message = "cannot be empty"))]
@@ -140,8 +364,37 @@ pub struct CreateTxRequest {
     #[validate(required, length(min = 1, message = "cannot be empty"))]
     pub to: Option<Vec<ToAddresses>>,
 
-    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    // required unless change_derivation is set
+    #[validate(length(min = 1, message = "cannot be empty"))]
     pub change_address: Option<String>,
+
+    #[validate]
+    pub change_derivation: Option<ChangeDerivation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ChangeDerivation {
+    // xpub or change descriptor, plain keys derive from the change chain /1/*
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub key: Option<String>,
+
+    #[validate(required)]
+    pub index: Option<u32>,
+
+    pub script_type: Option<ScriptType>,
+    pub network: Option<HdNetwork>,
+}
+
+impl ChangeDerivation {
+    fn address(&self) -> Result<String, String> {
+        let key = HdKey::parse(self.key.as_ref().unwrap(), self.script_type, true)?;
+        let derived = key.derive(
+            &Secp256k1::verification_only(),
+            self.index.unwrap(),
+            self.network.map(Network::from),
+        )?;
+        Ok(derived.address)
+    }
 }
 
 #[post("/create-tx")]
@@ -150,88 +403,82 @@ async fn create_tx(
     rq_client: web::Data<RequestClient>,
     btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
 ) -> impl Responder {
-    match json.validate() {
-        Ok(_) => {
-            let mut headers = HashMap::new();
-            headers.insert("Content-Type".to_string(), "application/json".to_string());
-            headers.insert("Accept".to_string(), "application/json".to_string());
-
-            headers.insert(
-                "Authorization".to_string(),
-                format!(
-                    "Basic {}",
-                    encode(format!(
-                        "{}:{}",
-                        btc_rpc_cfg.bitcoin_rpc_user, btc_rpc_cfg.bitcoin_rpc_password
-                    ))
-                ),
-            );
-
-            let payload = json!({ "jsonrpc": "2.0",  "method": "estimatesmartfee", "params": [4]});
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
 
-            match rq_client
-                .post(&btc_rpc_cfg.bitcoin_rpc_url_one, Some(&headers), &payload)
-                .await
-            {
-                Ok(response) => {
-                    if response.status() == http::StatusCode::UNAUTHORIZED {
-                        HttpResponse::Unauthorized().json(ErrorResponse {
-                            message: "Unauthorized".to_string(),
-                        })
-                    } else {
-                        match response.json::<FeeRate>().await {
-                            Ok(fee_rate) => {
-                                if let Some(err) = fee_rate.error {
-                                    HttpResponse::BadRequest().json(ErrorResponse {
-                                        message: err.message,
-                                    })
-                                } else {
-                                    match create_transaction(
-                                        json.utxos.as_ref().unwrap(),
-                                        json.to.as_ref().unwrap(),
-                                        json.change_address.as_ref().unwrap(),
-                                        fee_rate.result.unwrap().feerate,
-                                    ) {
-                                        Ok(hex_tx) => {
-                                            return HttpResponse::Ok()
-                                                .json(OkResponse { result: hex_tx })
-                                        }
-                                        Err(err) => {
-                                            return HttpResponse::BadRequest().json(ErrorResponse {
-                                                message: err.to_string(),
-                                            })
-                                        }
-                                    };
-                                }
-                            }
-                            Err(err) => {
-                                error!("failed to decode BlockchainInfo, {}", err);
-                                HttpResponse::BadRequest().json(ErrorResponse {
-                                    message: "failed to decode response".to_string(),
-                                })
-                            }
-                        }
-                    }
-                }
-                Err(err) => {
-                    error!("request error: {}", err);
-                    HttpResponse::RequestTimeout().json(ErrorResponse {
-                        message: "failed to do request, something wrong with rpc node".to_string(),
-                    })
-                }
-            }
+    let change_address = match (&json.change_address, &json.change_derivation) {
+        (Some(change_address), _) => change_address.clone(),
+        (None, Some(derivation)) => match derivation.address() {
+            Ok(change_address) => change_address,
+            Err(message) => return HttpResponse::BadRequest().json(ErrorResponse { message }),
+        },
+        (None, None) => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                message: "change_address or change_derivation is required".to_string(),
+            })
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+    };
+
+    match build_psbt(
+        &rq_client,
+        &btc_rpc_cfg,
+        json.utxos.as_ref().unwrap(),
+        json.to.as_ref().unwrap(),
+        &change_address,
+    )
+    .await
+    {
+        Ok((psbt, fee_rate)) => HttpResponse::Ok().json(CreateTxResponse {
+            result: consensus::encode::serialize_hex(&psbt.unsigned_tx),
+            psbt: encode(consensus::encode::serialize(&psbt)),
+            hex: consensus::encode::serialize_hex(&psbt.unsigned_tx),
+            fee_rate,
+        }),
+        Err(resp) => resp,
     }
 }
 
+// unsigned psbt paying the create-tx fee target on the network of the node, with its fee rate
+pub(super) async fn build_psbt(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    utxos: &[Utxo],
+    to: &[ToAddresses],
+    change_address: &str,
+) -> Result<(Psbt, f64), HttpResponse> {
+    let network = node_network(rq_client, btc_rpc_cfg).await?;
+
+    let fee_rate = estimate_fees(
+        rq_client,
+        btc_rpc_cfg,
+        &[CREATE_TX_FEE_TARGET],
+        &[FeeEstimateMode::Conservative],
+    )
+    .await?
+    .remove(0)
+    .btc_per_kvb;
+
+    let psbt = create_transaction(utxos, to, change_address, fee_rate, network).map_err(|err| {
+        HttpResponse::BadRequest().json(ErrorResponse {
+            message: err.to_string(),
+        })
+    })?;
+
+    Ok((psbt, fee_rate))
+}
+
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct SignTxRequest {
     #[validate(required, length(min = 1, message = "cannot be empty"))]
     pub raw_tx: Option<String>,
 
-    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    // one of private_key and private_keys, the generic body uses private_keys
+    #[validate(length(min = 1, message = "cannot be empty"))]
     pub private_key: Option<String>,
+
+    #[validate(length(min = 1, message = "cannot be empty"))]
+    pub private_keys: Option<Vec<String>>,
 }
 
 #[post("/sign-tx")]
@@ -240,63 +487,57 @@ async fn sign_tx(
     rq_client: web::Data<RequestClient>,
     btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
 ) -> impl Responder {
-    match json.validate() {
-        Ok(_) => {
-            let mut headers = HashMap::new();
-            headers.insert("Content-Type".to_string(), "application/json".to_string());
-            headers.insert("Accept".to_string(), "application/json".to_string());
-
-            headers.insert(
-                "Authorization".to_string(),
-                format!(
-                    "Basic {}",
-                    encode(format!(
-                        "{}:{}",
-                        btc_rpc_cfg.bitcoin_rpc_user, btc_rpc_cfg.bitcoin_rpc_password
-                    ))
-                ),
-            );
-
-            let payload = json!({ "jsonrpc": "2.0",  "method": "signrawtransactionwithkey", "params": [json.raw_tx.as_ref().unwrap(), [json.private_key.as_ref().unwrap()]]});
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
 
-            match rq_client
-                .post(&btc_rpc_cfg.bitcoin_rpc_url_one, Some(&headers), &payload)
-                .await
-            {
-                Ok(response) => {
-                    if response.status() == http::StatusCode::UNAUTHORIZED {
-                        HttpResponse::Unauthorized().json(ErrorResponse {
-                            message: "Unauthorized".to_string(),
-                        })
-                    } else {
-                        match response.json::<SignTx>().await {
-                            Ok(signed_tx) => {
-                                if let Some(err) = signed_tx.error {
-                                    HttpResponse::BadRequest().json(ErrorResponse {
-                                        message: err.message,
-                                    })
-                                } else {
-                                    HttpResponse::Ok().json(signed_tx)
-                                }
-                            }
-                            Err(err) => {
-                                error!("failed to sign tx, {}", err);
-                                HttpResponse::BadRequest().json(ErrorResponse {
-                                    message: "failed to decode response".to_string(),
-                                })
-                            }
-                        }
-                    }
-                }
-                Err(err) => {
-                    error!("request error: {}", err);
-                    HttpResponse::RequestTimeout().json(ErrorResponse {
-                        message: "failed to do request, something wrong with rpc node".to_string(),
-                    })
-                }
-            }
+    let private_keys = match (&json.private_key, &json.private_keys) {
+        (Some(private_key), None) => vec![private_key.clone()],
+        (None, Some(private_keys)) => private_keys.clone(),
+        _ => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                message: "either private_key or private_keys is required".to_string(),
+            })
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+    };
+
+    match sign_raw_transaction(
+        &rq_client,
+        &btc_rpc_cfg,
+        json.raw_tx.as_ref().unwrap(),
+        &private_keys,
+    )
+    .await
+    {
+        // the generic body gets the generic response
+        Ok(signed_tx) if json.private_keys.is_some() => HttpResponse::Ok().json(signed_tx),
+        Ok(signed_tx) => HttpResponse::Ok().json(SignTx {
+            result: Some(signed_tx),
+            error: None,
+        }),
+        Err(resp) => resp,
+    }
+}
+
+pub(crate) async fn sign_raw_transaction(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    raw_tx: &str,
+    private_keys: &[String],
+) -> Result<SignTxResult, HttpResponse> {
+    let payload = json!({ "jsonrpc": "2.0",  "method": "signrawtransactionwithkey", "params": [raw_tx, private_keys]});
+
+    match rpc_call::<SignTx>(rq_client, btc_rpc_cfg, &payload).await? {
+        SignTx {
+            error: Some(err), ..
+        } => Err(HttpResponse::BadRequest().json(ErrorResponse {
+            message: err.message,
+        })),
+        SignTx {
+            result: Some(signed_tx),
+            ..
+        } => Ok(signed_tx),
+        _ => Err(empty_result_response()),
     }
 }
 
@@ -304,6 +545,9 @@ async fn sign_tx(
 pub struct SendTxRequest {
     #[validate(required, length(min = 1, message = "cannot be empty"))]
     pub signed_tx: Option<String>,
+
+    // only check mempool acceptance via testmempoolaccept, do not broadcast
+    pub dry_run: Option<bool>,
 }
 
 #[post("/send-tx")]
@@ -312,62 +556,977 @@ async fn send_tx(
     rq_client: web::Data<RequestClient>,
     btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
 ) -> impl Responder {
-    match json.validate() {
-        Ok(_) => {
-            let mut headers = HashMap::new();
-            headers.insert("Content-Type".to_string(), "application/json".to_string());
-            headers.insert("Accept".to_string(), "application/json".to_string());
-
-            headers.insert(
-                "Authorization".to_string(),
-                format!(
-                    "Basic {}",
-                    encode(format!(
-                        "{}:{}",
-                        btc_rpc_cfg.bitcoin_rpc_user, btc_rpc_cfg.bitcoin_rpc_password
-                    ))
-                ),
-            );
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
 
-            let payload = json!({ "jsonrpc": "2.0",  "method": "sendrawtransaction", "params": [json.signed_tx.as_ref().unwrap()]});
+    let signed_tx = json.signed_tx.as_ref().unwrap();
 
-            match rq_client
-                .post(&btc_rpc_cfg.bitcoin_rpc_url_one, Some(&headers), &payload)
-                .await
+    if json.dry_run.unwrap_or(false) {
+        return test_mempool_accept(&rq_client, &btc_rpc_cfg, &[signed_tx.to_string()]).await;
+    }
+
+    broadcast_response(broadcast_tx(&rq_client, &btc_rpc_cfg, signed_tx).await)
+}
+
+// 400 when a node rejected the tx, 504 when every node timed out, 502 when none was
+// reached or answered
+pub(crate) fn broadcast_response(broadcast: Broadcast) -> HttpResponse {
+    if broadcast.success {
+        HttpResponse::Ok().json(broadcast)
+    } else if broadcast
+        .nodes
+        .iter()
+        .any(|n| n.status == BroadcastStatus::Rejected)
+    {
+        HttpResponse::BadRequest().json(broadcast)
+    } else if !broadcast.nodes.is_empty()
+        && broadcast
+            .nodes
+            .iter()
+            .all(|n| n.status == BroadcastStatus::TimedOut)
+    {
+        HttpResponse::GatewayTimeout().json(broadcast)
+    } else {
+        HttpResponse::BadGateway().json(broadcast)
+    }
+}
+
+// sends the tx to every configured node at once, success if at least one node has it
+pub(crate) async fn broadcast_tx(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    signed_tx: &str,
+) -> Broadcast {
+    let payload = json!({ "jsonrpc": "2.0",  "method": "sendrawtransaction", "params": [signed_tx, btc_rpc_cfg.bitcoin_max_fee_rate]});
+
+    broadcast_payload(rq_client, btc_rpc_cfg, &payload).await
+}
+
+// sends the sendrawtransaction payload to every configured node
+pub(crate) async fn broadcast_payload(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    payload: &Value,
+) -> Broadcast {
+    let headers = rpc_headers(btc_rpc_cfg);
+
+    let sends = btc_rpc_cfg.bitcoin_rpc_urls.iter().map(|url| {
+        let headers = &headers;
+        async move {
+            let (node_status, txid, reason) = match rt::time::timeout(
+                BROADCAST_TIMEOUT,
+                rq_client.post(url, Some(headers), payload),
+            )
+            .await
             {
-                Ok(response) => {
-                    if response.status() == http::StatusCode::UNAUTHORIZED {
-                        HttpResponse::Unauthorized().json(ErrorResponse {
-                            message: "Unauthorized".to_string(),
-                        })
-                    } else {
-                        match response.json::<SendTx>().await {
-                            Ok(sended_tx) => {
-                                if let Some(err) = sended_tx.error {
-                                    HttpResponse::BadRequest().json(ErrorResponse {
-                                        message: err.message,
-                                    })
-                                } else {
-                                    HttpResponse::Ok().json(sended_tx)
-                                }
-                            }
-                            Err(err) => {
-                                error!("failed to sign tx, {}", err);
-                                HttpResponse::BadRequest().json(ErrorResponse {
-                                    message: "failed to decode response".to_string(),
-                                })
+                Ok(Ok(response)) => {
+                    let http_status = response.status();
+                    match response.json::<SendTx>().await {
+                        Ok(SendTx {
+                            error: Some(err), ..
+                        }) => match err.code {
+                            RPC_VERIFY_ALREADY_IN_CHAIN | RPC_VERIFY_ERROR => {
+                                (BroadcastStatus::AlreadyInChain, None, Some(err.message))
                             }
-                        }
+                            _ => (BroadcastStatus::Rejected, None, Some(err.message)),
+                        },
+                        Ok(SendTx {
+                            result: Some(txid), ..
+                        }) => (BroadcastStatus::Accepted, Some(txid), None),
+                        _ => (
+                            BroadcastStatus::Failed,
+                            None,
+                            Some(format!("node responded with {}", http_status)),
+                        ),
                     }
                 }
-                Err(err) => {
-                    error!("request error: {}", err);
-                    HttpResponse::RequestTimeout().json(ErrorResponse {
-                        message: "failed to do request, something wrong with rpc node".to_string(),
-                    })
+                Ok(Err(err)) => {
+                    error!("broadcast to {} failed: {}", url, err);
+                    (
+                        BroadcastStatus::Unreachable,
+                        None,
+                        Some("failed to reach the node".to_string()),
+                    )
                 }
+                Err(_) => (
+                    BroadcastStatus::TimedOut,
+                    None,
+                    Some(format!(
+                        "node did not answer within {}s",
+                        BROADCAST_TIMEOUT.as_secs()
+                    )),
+                ),
+            };
+
+            (
+                txid,
+                BroadcastNodeResult {
+                    node: url.to_string(),
+                    status: node_status,
+                    reason,
+                },
+            )
+        }
+    });
+
+    let mut txid = None;
+    let mut nodes = Vec::new();
+    for (node_txid, node) in join_all(sends).await {
+        if txid.is_none() {
+            txid = node_txid;
+        }
+        nodes.push(node);
+    }
+
+    Broadcast {
+        success: nodes.iter().any(|n| {
+            matches!(
+                n.status,
+                BroadcastStatus::Accepted
+                    | BroadcastStatus::AlreadyInMempool
+                    | BroadcastStatus::AlreadyInChain
+            )
+        }),
+        txid,
+        nodes,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TestTxRequest {
+    // several txs are checked as a package, max package count is 25
+    #[validate(
+        required,
+        length(min = 1, max = 25, message = "must contain 1 to 25 txs")
+    )]
+    pub signed_txs: Option<Vec<String>>,
+}
+
+#[post("/test-tx")]
+async fn test_tx(
+    json: web::Json<TestTxRequest>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    match json.validate() {
+        Ok(_) => {
+            test_mempool_accept(&rq_client, &btc_rpc_cfg, json.signed_txs.as_ref().unwrap()).await
+        }
+        Err(err) => HttpResponse::BadRequest().json(err),
+    }
+}
+
+async fn test_mempool_accept(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    signed_txs: &[String],
+) -> HttpResponse {
+    let payload = json!({ "jsonrpc": "2.0",  "method": "testmempoolaccept", "params": [signed_txs, btc_rpc_cfg.bitcoin_max_fee_rate]});
+
+    match rpc_call::<TestMempoolAccept>(rq_client, btc_rpc_cfg, &payload).await {
+        Ok(accept) => {
+            if let Some(err) = accept.error {
+                HttpResponse::BadRequest().json(ErrorResponse {
+                    message: err.message,
+                })
+            } else {
+                HttpResponse::Ok().json(accept)
             }
         }
+        Err(resp) => resp,
+    }
+}
+
+#[get("/tx/{txid}")]
+async fn tx_status(
+    path: web::Path<String>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let txid = path.into_inner();
+    if Txid::from_hex(&txid).is_err() {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            message: "invalid txid".to_string(),
+        });
+    }
+
+    match transaction_status(&rq_client, &btc_rpc_cfg, txid).await {
+        Ok(tx_status) => HttpResponse::Ok().json(tx_status),
+        Err(resp) => resp,
+    }
+}
+
+pub(crate) async fn transaction_status(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    txid: String,
+) -> Result<TxStatus, HttpResponse> {
+    let raw_tx = get_raw_transaction(rq_client, btc_rpc_cfg, &txid).await?;
+
+    let confirmations = raw_tx.confirmations.unwrap_or(0);
+
+    if confirmations == 0 {
+        let payload = json!({ "jsonrpc": "2.0",  "method": "getmempoolentry", "params": [txid]});
+
+        return match rpc_call::<MempoolEntry>(rq_client, btc_rpc_cfg, &payload).await? {
+            MempoolEntry {
+                result: Some(entry),
+                ..
+            } => Ok(TxStatus {
+                txid,
+                in_mempool: true,
+                confirmations,
+                block_hash: None,
+                block_height: None,
+                fee: Some(entry.fees.base),
+                replaceable: Some(entry.bip125_replaceable),
+            }),
+            // tx left the mempool between the two calls
+            MempoolEntry {
+                error: Some(err), ..
+            } if err.code == RPC_INVALID_ADDRESS_OR_KEY => Ok(TxStatus {
+                txid,
+                in_mempool: false,
+                confirmations,
+                block_hash: None,
+                block_height: None,
+                fee: None,
+                replaceable: None,
+            }),
+            MempoolEntry {
+                error: Some(err), ..
+            } => Err(rpc_error_response(err)),
+            _ => Err(empty_result_response()),
+        };
+    }
+
+    let block_hash = raw_tx.blockhash.clone().unwrap_or_default();
+    let payload = json!({ "jsonrpc": "2.0",  "method": "getblockheader", "params": [block_hash]});
+
+    let block_height = match rpc_call::<BlockHeader>(rq_client, btc_rpc_cfg, &payload).await? {
+        BlockHeader {
+            result: Some(header),
+            ..
+        } => header.height,
+        BlockHeader {
+            error: Some(err), ..
+        } => return Err(rpc_error_response(err)),
+        _ => return Err(empty_result_response()),
+    };
+
+    Ok(TxStatus {
+        txid,
+        in_mempool: false,
+        confirmations,
+        block_hash: Some(block_hash),
+        block_height: Some(block_height),
+        fee: confirmed_tx_fee(rq_client, btc_rpc_cfg, &raw_tx).await,
+        replaceable: None,
+    })
+}
+
+pub(super) async fn get_raw_transaction(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    txid: &str,
+) -> Result<RawTransactionResult, HttpResponse> {
+    let payload =
+        json!({ "jsonrpc": "2.0",  "method": "getrawtransaction", "params": [txid, true]});
+
+    match rpc_call::<RawTransaction>(rq_client, btc_rpc_cfg, &payload).await? {
+        RawTransaction {
+            result: Some(raw_tx),
+            ..
+        } => Ok(raw_tx),
+        RawTransaction {
+            error: Some(err), ..
+        } => Err(rpc_error_response(err)),
+        _ => Err(empty_result_response()),
+    }
+}
+
+// fee of a mined tx is not reported by the node, so sum up the spent outputs
+async fn confirmed_tx_fee(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    raw_tx: &RawTransactionResult,
+) -> Option<f64> {
+    if raw_tx.vin.iter().any(|vin| vin.coinbase.is_some()) {
+        return None;
+    }
+
+    let prev_txs = raw_tx.vin.iter().map(|vin| async move {
+        let prev_tx = get_raw_transaction(
+            rq_client,
+            btc_rpc_cfg,
+            vin.txid.as_deref().unwrap_or_default(),
+        )
+        .await
+        .ok()?;
+        let prev_out = prev_tx.vout.iter().find(|out| Some(out.n) == vin.vout)?;
+
+        Amount::from_btc(prev_out.value).ok().map(|a| a.to_sat())
+    });
+
+    let mut in_amount = 0;
+    for amount in join_all(prev_txs).await {
+        in_amount += amount?;
+    }
+
+    let mut out_amount = 0;
+    for out in &raw_tx.vout {
+        out_amount += Amount::from_btc(out.value).ok()?.to_sat();
+    }
+
+    Some(Amount::from_sat(in_amount.checked_sub(out_amount)?).to_btc())
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct BlockQuery {
+    pub verbosity: Option<BlockVerbosity>,
+
+    #[validate(range(min = 1, message = "starts from 1"))]
+    pub page: Option<usize>,
+
+    #[validate(range(min = 1, max = 1000, message = "must be between 1 and 1000"))]
+    pub limit: Option<usize>,
+}
+
+#[get("/block/{hash}")]
+async fn block_by_hash(
+    path: web::Path<String>,
+    query: web::Query<BlockQuery>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let hash = path.into_inner();
+    if BlockHash::from_hex(&hash).is_err() {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            message: "invalid block hash".to_string(),
+        });
+    }
+
+    match query.validate() {
+        Ok(_) => get_block(&rq_client, &btc_rpc_cfg, &hash, &query).await,
         Err(err) => HttpResponse::BadRequest().json(err),
     }
 }
+
+#[get("/block-height/{height}")]
+async fn block_by_height(
+    path: web::Path<usize>,
+    query: web::Query<BlockQuery>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    if let Err(err) = query.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    let height = path.into_inner();
+
+    if let Some(rest_url) = btc_rpc_cfg.rest_url(&btc_rpc_cfg.bitcoin_rpc_url_one) {
+        return match rest::block_hash_by_height(&rq_client, rest_url, height).await {
+            Ok(hash) => get_block(&rq_client, &btc_rpc_cfg, &hash, &query).await,
+            Err(err) => rest_error_response(err),
+        };
+    }
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "getblockhash", "params": [height]});
+
+    match rpc_call::<GetBlockHash>(&rq_client, &btc_rpc_cfg, &payload).await {
+        Ok(GetBlockHash {
+            result: Some(hash), ..
+        }) => get_block(&rq_client, &btc_rpc_cfg, &hash, &query).await,
+        Ok(GetBlockHash {
+            error: Some(err), ..
+        }) if err.code == RPC_INVALID_PARAMETER => HttpResponse::NotFound().json(ErrorResponse {
+            message: err.message,
+        }),
+        Ok(GetBlockHash {
+            error: Some(err), ..
+        }) => rpc_error_response(err),
+        Ok(_) => empty_result_response(),
+        Err(resp) => resp,
+    }
+}
+
+#[get("/block/{hash}/header")]
+async fn block_header(
+    path: web::Path<String>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let hash = path.into_inner();
+    if BlockHash::from_hex(&hash).is_err() {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            message: "invalid block hash".to_string(),
+        });
+    }
+
+    if let Some(rest_url) = btc_rpc_cfg.rest_url(&btc_rpc_cfg.bitcoin_rpc_url_one) {
+        return match rest::block_header(&rq_client, rest_url, &hash).await {
+            Ok(header) => HttpResponse::Ok().json(header),
+            Err(err) => rest_error_response(err),
+        };
+    }
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "getblockheader", "params": [hash]});
+
+    match rpc_call::<BlockHeader>(&rq_client, &btc_rpc_cfg, &payload).await {
+        Ok(BlockHeader {
+            result: Some(header),
+            ..
+        }) => HttpResponse::Ok().json(header),
+        Ok(BlockHeader {
+            error: Some(err), ..
+        }) => rpc_error_response(err),
+        Ok(_) => empty_result_response(),
+        Err(resp) => resp,
+    }
+}
+
+async fn get_block(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    hash: &str,
+    query: &BlockQuery,
+) -> HttpResponse {
+    let verbosity = query.verbosity.unwrap_or(BlockVerbosity::TxIds);
+
+    if verbosity == BlockVerbosity::Hex {
+        return match fetch_block_raw(rq_client, btc_rpc_cfg, hash, RawFormat::Hex).await {
+            Ok(raw) => raw_response(raw, RawFormat::Hex),
+            Err(resp) => resp,
+        };
+    }
+
+    match fetch_block(rq_client, btc_rpc_cfg, hash, verbosity).await {
+        Ok(mut block) => {
+            let page = query.page.unwrap_or(1);
+            let limit = query.limit.unwrap_or(100);
+            let total_tx = block.tx.len();
+
+            block.tx = block
+                .tx
+                .into_iter()
+                .skip((page - 1).saturating_mul(limit))
+                .take(limit)
+                .collect();
+
+            HttpResponse::Ok().json(BlockPage {
+                block,
+                page,
+                limit,
+                total_tx,
+            })
+        }
+        Err(resp) => resp,
+    }
+}
+
+async fn fetch_block(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    hash: &str,
+    verbosity: BlockVerbosity,
+) -> Result<BlockResult, HttpResponse> {
+    if let Some(rest_url) = btc_rpc_cfg.rest_url(&btc_rpc_cfg.bitcoin_rpc_url_one) {
+        return rest::block(
+            rq_client,
+            rest_url,
+            hash,
+            verbosity == BlockVerbosity::Transactions,
+        )
+        .await
+        .map_err(rest_error_response);
+    }
+
+    let payload =
+        json!({ "jsonrpc": "2.0",  "method": "getblock", "params": [hash, verbosity.level()]});
+
+    match rpc_call::<BlockInfo>(rq_client, btc_rpc_cfg, &payload).await? {
+        BlockInfo {
+            result: Some(block),
+            ..
+        } => Ok(block),
+        BlockInfo {
+            error: Some(err), ..
+        } => Err(rpc_error_response(err)),
+        _ => Err(empty_result_response()),
+    }
+}
+
+async fn fetch_block_raw(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    hash: &str,
+    format: RawFormat,
+) -> Result<Bytes, HttpResponse> {
+    if let Some(rest_url) = btc_rpc_cfg.rest_url(&btc_rpc_cfg.bitcoin_rpc_url_one) {
+        return rest::block_raw(rq_client, rest_url, hash, format)
+            .await
+            .map_err(rest_error_response);
+    }
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "getblock", "params": [hash, 0]});
+
+    match rpc_call::<RawBlock>(rq_client, btc_rpc_cfg, &payload).await? {
+        RawBlock {
+            result: Some(hex), ..
+        } => hex_to_raw(hex, format).map_err(hex_error_response),
+        RawBlock {
+            error: Some(err), ..
+        } => Err(rpc_error_response(err)),
+        _ => Err(empty_result_response()),
+    }
+}
+
+#[get("/address/{address}/utxos")]
+async fn address_utxos(
+    path: web::Path<String>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    match scan_address(&rq_client, &btc_rpc_cfg, &path.into_inner()).await {
+        Ok(scan) => {
+            let utxos: Vec<Utxo> = scan
+                .unspents
+                .into_iter()
+                .map(|unspent| Utxo {
+                    tx_id: Some(unspent.txid),
+                    vout: Some(unspent.vout),
+                    amount: Some(unspent.amount),
+                    pk_script: Some(unspent.script_pub_key),
+                    witness_script: None,
+                    leaf_script: None,
+                    control_block: None,
+                })
+                .collect();
+
+            HttpResponse::Ok().json(utxos)
+        }
+        Err(resp) => resp,
+    }
+}
+
+#[get("/address/{address}/balance")]
+async fn address_balance(
+    path: web::Path<String>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let address = path.into_inner();
+
+    match scan_address(&rq_client, &btc_rpc_cfg, &address).await {
+        Ok(scan) => HttpResponse::Ok().json(AddressBalance {
+            address,
+            balance: scan.total_amount,
+            utxo_count: scan.unspents.len(),
+            height: scan.height,
+        }),
+        Err(resp) => resp,
+    }
+}
+
+// scans the node utxo set, works without a wallet but takes a while on mainnet
+async fn scan_address(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    address: &str,
+) -> Result<ScanTxOutSetResult, HttpResponse> {
+    if Address::from_str(address).is_err() {
+        return Err(HttpResponse::BadRequest().json(ErrorResponse {
+            message: "invalid Bitcoin address".to_string(),
+        }));
+    }
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "scantxoutset", "params": ["start", [format!("addr({})", address)]]});
+
+    match rpc_call::<ScanTxOutSet>(rq_client, btc_rpc_cfg, &payload).await? {
+        ScanTxOutSet {
+            result: Some(scan), ..
+        } => Ok(scan),
+        ScanTxOutSet {
+            error: Some(err), ..
+        } => Err(rpc_error_response(err)),
+        _ => Err(empty_result_response()),
+    }
+}
+
+#[get("/mempool")]
+async fn mempool_info(
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let payload = json!({ "jsonrpc": "2.0",  "method": "getmempoolinfo"});
+
+    match rpc_call::<MempoolInfo>(&rq_client, &btc_rpc_cfg, &payload).await {
+        Ok(MempoolInfo {
+            result: Some(info), ..
+        }) => HttpResponse::Ok().json(info),
+        Ok(MempoolInfo {
+            error: Some(err), ..
+        }) => rpc_error_response(err),
+        Ok(_) => empty_result_response(),
+        Err(resp) => resp,
+    }
+}
+
+#[get("/mempool/histogram")]
+async fn mempool_histogram(
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let payload = json!({ "jsonrpc": "2.0",  "method": "getrawmempool", "params": [true]});
+
+    match rpc_call::<RawMempool>(&rq_client, &btc_rpc_cfg, &payload).await {
+        Ok(RawMempool {
+            result: Some(mempool),
+            ..
+        }) => HttpResponse::Ok().json(fee_histogram(mempool.values())),
+        Ok(RawMempool {
+            error: Some(err), ..
+        }) => rpc_error_response(err),
+        Ok(_) => empty_result_response(),
+        Err(resp) => resp,
+    }
+}
+
+#[get("/mempool/{txid}")]
+async fn mempool_entry(
+    path: web::Path<String>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let txid = path.into_inner();
+    if Txid::from_hex(&txid).is_err() {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            message: "invalid txid".to_string(),
+        });
+    }
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "getmempoolentry", "params": [txid]});
+
+    let entry = match rpc_call::<MempoolEntry>(&rq_client, &btc_rpc_cfg, &payload).await {
+        Ok(MempoolEntry {
+            result: Some(entry),
+            ..
+        }) => entry,
+        Ok(MempoolEntry {
+            error: Some(err), ..
+        }) => return rpc_error_response(err),
+        Ok(_) => return empty_result_response(),
+        Err(resp) => return resp,
+    };
+
+    let (ancestors, descendants) = futures::join!(
+        get_mempool_txids(&rq_client, &btc_rpc_cfg, "getmempoolancestors", &txid),
+        get_mempool_txids(&rq_client, &btc_rpc_cfg, "getmempooldescendants", &txid)
+    );
+
+    let ancestors = match ancestors {
+        Ok(txids) => txids,
+        Err(resp) => return resp,
+    };
+    let descendants = match descendants {
+        Ok(txids) => txids,
+        Err(resp) => return resp,
+    };
+
+    HttpResponse::Ok().json(MempoolEntryDetails {
+        entry,
+        ancestors,
+        descendants,
+    })
+}
+
+async fn get_mempool_txids(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    method: &str,
+    txid: &str,
+) -> Result<Vec<String>, HttpResponse> {
+    let payload = json!({ "jsonrpc": "2.0",  "method": method, "params": [txid]});
+
+    match rpc_call::<MempoolTxIds>(rq_client, btc_rpc_cfg, &payload).await? {
+        MempoolTxIds {
+            result: Some(txids),
+            ..
+        } => Ok(txids),
+        MempoolTxIds {
+            error: Some(err), ..
+        } => Err(rpc_error_response(err)),
+        _ => Err(empty_result_response()),
+    }
+}
+
+// every configured target in both modes, or the generic single target estimate
+#[get("/fees")]
+async fn fees(
+    query: web::Query<FeeQuery>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    if let Err(err) = query.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    if let Some(target) = query.target {
+        return match estimate_fees(
+            &rq_client,
+            &btc_rpc_cfg,
+            &[target],
+            &[FeeEstimateMode::Conservative],
+        )
+        .await
+        {
+            Ok(mut estimates) => {
+                let estimate = estimates.remove(0);
+                HttpResponse::Ok().json(FeeResponse {
+                    fee_rate: estimate.btc_per_kvb,
+                    estimate,
+                })
+            }
+            Err(resp) => resp,
+        };
+    }
+
+    match estimate_fees(
+        &rq_client,
+        &btc_rpc_cfg,
+        &btc_rpc_cfg.bitcoin_fee_targets,
+        &[FeeEstimateMode::Economical, FeeEstimateMode::Conservative],
+    )
+    .await
+    {
+        Ok(estimates) => HttpResponse::Ok().json(estimates),
+        Err(resp) => resp,
+    }
+}
+
+// estimatesmartfee for every target and mode, falls back to the mempool min fee
+// when the node has no estimate yet
+pub(crate) async fn estimate_fees(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    targets: &[u16],
+    modes: &[FeeEstimateMode],
+) -> Result<Vec<FeeEstimate>, HttpResponse> {
+    let requests = targets
+        .iter()
+        .flat_map(|target| modes.iter().map(move |mode| (*target, *mode)))
+        .map(|(target, mode)| async move {
+            let payload =
+                json!({ "jsonrpc": "2.0",  "method": "estimatesmartfee", "params": [target, mode]});
+            let fee_rate = rpc_call::<FeeRate>(rq_client, btc_rpc_cfg, &payload).await;
+
+            (target, mode, fee_rate)
+        });
+
+    let mut estimates = Vec::new();
+    let mut mempool_min_fee = None;
+
+    for (target, mode, fee_rate) in join_all(requests).await {
+        let fee_rate = match fee_rate? {
+            FeeRate {
+                error: Some(err), ..
+            } => return Err(rpc_error_response(err)),
+            FeeRate {
+                result: Some(fee_rate),
+                ..
+            } => fee_rate,
+            _ => return Err(empty_result_response()),
+        };
+
+        let (blocks, btc_per_kvb, source) = match fee_rate.feerate {
+            Some(feerate) => (Some(fee_rate.blocks), feerate, FeeSource::EstimateSmartFee),
+            None => {
+                if mempool_min_fee.is_none() {
+                    mempool_min_fee = Some(get_mempool_min_fee(rq_client, btc_rpc_cfg).await?);
+                }
+
+                (None, mempool_min_fee.unwrap(), FeeSource::MempoolMinFee)
+            }
+        };
+
+        estimates.push(FeeEstimate {
+            target,
+            mode,
+            blocks,
+            btc_per_kvb,
+            sat_per_vb: btc_per_kvb * 1.0e5,
+            source,
+        });
+    }
+
+    Ok(estimates)
+}
+
+async fn get_mempool_min_fee(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+) -> Result<f64, HttpResponse> {
+    let payload = json!({ "jsonrpc": "2.0",  "method": "getmempoolinfo"});
+
+    match rpc_call::<MempoolInfo>(rq_client, btc_rpc_cfg, &payload).await? {
+        MempoolInfo {
+            result: Some(info), ..
+        } => Ok(info.mempoolminfee),
+        MempoolInfo {
+            error: Some(err), ..
+        } => Err(rpc_error_response(err)),
+        _ => Err(empty_result_response()),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RawQuery {
+    pub format: Option<RawFormat>,
+}
+
+#[get("/block/{hash}/raw")]
+async fn block_raw(
+    path: web::Path<String>,
+    query: web::Query<RawQuery>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let hash = path.into_inner();
+    if BlockHash::from_hex(&hash).is_err() {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            message: "invalid block hash".to_string(),
+        });
+    }
+
+    let format = query.format.unwrap_or(RawFormat::Hex);
+
+    match fetch_block_raw(&rq_client, &btc_rpc_cfg, &hash, format).await {
+        Ok(raw) => raw_response(raw, format),
+        Err(resp) => resp,
+    }
+}
+
+#[get("/tx/{txid}/raw")]
+async fn tx_raw(
+    path: web::Path<String>,
+    query: web::Query<RawQuery>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let txid = path.into_inner();
+    if Txid::from_hex(&txid).is_err() {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            message: "invalid txid".to_string(),
+        });
+    }
+
+    let format = query.format.unwrap_or(RawFormat::Hex);
+
+    if let Some(rest_url) = btc_rpc_cfg.rest_url(&btc_rpc_cfg.bitcoin_rpc_url_one) {
+        return match rest::tx_raw(&rq_client, rest_url, &txid, format).await {
+            Ok(raw) => raw_response(raw, format),
+            Err(err) => rest_error_response(err),
+        };
+    }
+
+    let payload =
+        json!({ "jsonrpc": "2.0",  "method": "getrawtransaction", "params": [txid, false]});
+
+    match rpc_call::<RawTransactionHex>(&rq_client, &btc_rpc_cfg, &payload).await {
+        Ok(RawTransactionHex {
+            result: Some(hex), ..
+        }) => match hex_to_raw(hex, format) {
+            Ok(raw) => raw_response(raw, format),
+            Err(err) => hex_error_response(err),
+        },
+        Ok(RawTransactionHex {
+            error: Some(err), ..
+        }) => rpc_error_response(err),
+        Ok(_) => empty_result_response(),
+        Err(resp) => resp,
+    }
+}
+
+#[get("/utxo/{txid}/{vout}")]
+async fn utxo(
+    path: web::Path<(String, u32)>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let (txid, vout) = path.into_inner();
+    if Txid::from_hex(&txid).is_err() {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            message: "invalid txid".to_string(),
+        });
+    }
+
+    if let Some(rest_url) = btc_rpc_cfg.rest_url(&btc_rpc_cfg.bitcoin_rpc_url_one) {
+        return match rest::utxos(&rq_client, rest_url, &txid, vout).await {
+            Ok(utxos) => {
+                let utxo = utxos.utxos.into_iter().next();
+
+                HttpResponse::Ok().json(UtxoStatus {
+                    txid,
+                    vout,
+                    unspent: utxo.is_some(),
+                    // mempool outputs are reported with height 2147483647
+                    confirmations: utxo
+                        .as_ref()
+                        .map(|u| (utxos.chain_height + 1).saturating_sub(u.height)),
+                    value: utxo.as_ref().map(|u| u.value),
+                    pk_script: utxo.map(|u| u.script_pub_key.hex),
+                })
+            }
+            Err(err) => rest_error_response(err),
+        };
+    }
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "gettxout", "params": [txid, vout, true]});
+
+    match rpc_call::<TxOut>(&rq_client, &btc_rpc_cfg, &payload).await {
+        Ok(TxOut {
+            error: Some(err), ..
+        }) => rpc_error_response(err),
+        Ok(TxOut { result, .. }) => HttpResponse::Ok().json(UtxoStatus {
+            txid,
+            vout,
+            unspent: result.is_some(),
+            confirmations: result.as_ref().map(|out| out.confirmations),
+            value: result.as_ref().map(|out| out.value),
+            pk_script: result.map(|out| out.script_pub_key.hex),
+        }),
+        Err(resp) => resp,
+    }
+}
+
+fn hex_to_raw(hex: String, format: RawFormat) -> Result<Bytes, hex::FromHexError> {
+    match format {
+        RawFormat::Hex => Ok(Bytes::from(hex)),
+        RawFormat::Bin => hex::decode(hex).map(Bytes::from),
+    }
+}
+
+fn hex_error_response(err: hex::FromHexError) -> HttpResponse {
+    error!("failed to decode hex, {}", err);
+    HttpResponse::BadRequest().json(ErrorResponse {
+        message: "failed to decode response".to_string(),
+    })
+}
+
+fn raw_response(raw: Bytes, format: RawFormat) -> HttpResponse {
+    match format {
+        RawFormat::Hex => HttpResponse::Ok().json(OkResponse {
+            result: String::from_utf8_lossy(&raw).into_owned(),
+        }),
+        RawFormat::Bin => HttpResponse::Ok()
+            .content_type("application/octet-stream")
+            .body(raw),
+    }
+}
+
+#[get("/reorgs")]
+async fn reorgs(tip_tracker: web::Data<TipTracker>) -> impl Responder {
+    HttpResponse::Ok().json(tip_tracker.history())
+}
@@ -0,0 +1,373 @@
+use std::str::FromStr;
+
+use actix_web::{post, web, HttpResponse, Responder};
+use bitcoin::{
+    hashes::hex::{FromHex, ToHex},
+    secp256k1::{self, Secp256k1, Verification, XOnlyPublicKey},
+    util::{
+        base58,
+        bip32::{ChildNumber, ExtendedPubKey, Fingerprint},
+    },
+    Address, Network, PublicKey,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::api::btc::{handler::ErrorResponse, model::DerivedAddress};
+
+// base58 version bytes of extended public keys
+const XPUB: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+const YPUB: [u8; 4] = [0x04, 0x9d, 0x7c, 0xb2];
+const ZPUB: [u8; 4] = [0x04, 0xb2, 0x47, 0x46];
+const TPUB: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
+const UPUB: [u8; 4] = [0x04, 0x4a, 0x52, 0x62];
+const VPUB: [u8; 4] = [0x04, 0x5f, 0x1c, 0xf6];
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScriptType {
+    // BIP44
+    P2pkh,
+    // BIP49
+    P2shP2wpkh,
+    // BIP84
+    P2wpkh,
+    // BIP86
+    P2tr,
+}
+
+impl ScriptType {
+    fn purpose(&self) -> u32 {
+        match self {
+            ScriptType::P2pkh => 44,
+            ScriptType::P2shP2wpkh => 49,
+            ScriptType::P2wpkh => 84,
+            ScriptType::P2tr => 86,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HdNetwork {
+    Bitcoin,
+    Testnet,
+    Signet,
+    Regtest,
+}
+
+impl From<HdNetwork> for Network {
+    fn from(network: HdNetwork) -> Network {
+        match network {
+            HdNetwork::Bitcoin => Network::Bitcoin,
+            HdNetwork::Testnet => Network::Testnet,
+            HdNetwork::Signet => Network::Signet,
+            HdNetwork::Regtest => Network::Regtest,
+        }
+    }
+}
+
+// single key address, p2tr is the BIP86 key path only output
+pub fn address<C: Verification>(
+    secp: &Secp256k1<C>,
+    public_key: secp256k1::PublicKey,
+    script_type: ScriptType,
+    network: Network,
+) -> Result<Address, String> {
+    let key = PublicKey::new(public_key);
+
+    match script_type {
+        ScriptType::P2pkh => Ok(Address::p2pkh(&key, network)),
+        ScriptType::P2shP2wpkh => Address::p2shwpkh(&key, network).map_err(|err| err.to_string()),
+        ScriptType::P2wpkh => Address::p2wpkh(&key, network).map_err(|err| err.to_string()),
+        ScriptType::P2tr => Ok(Address::p2tr(
+            secp,
+            XOnlyPublicKey::from(public_key),
+            None,
+            network,
+        )),
+    }
+}
+
+// ranged public key, either a plain extended key or a single key descriptor
+#[derive(Debug)]
+pub struct HdKey {
+    xpub: ExtendedPubKey,
+    script_type: ScriptType,
+    // key origin of a descriptor
+    origin: Option<(Fingerprint, Vec<ChildNumber>)>,
+    // steps between the key and the ranged index
+    chain: Vec<ChildNumber>,
+}
+
+fn parse_steps(steps: &str) -> Result<Vec<ChildNumber>, String> {
+    steps
+        .split('/')
+        .filter(|step| !step.is_empty())
+        .map(|step| {
+            ChildNumber::from_str(step).map_err(|_| format!("invalid derivation step {}", step))
+        })
+        .collect()
+}
+
+// decodes xpub/ypub/zpub and their testnet versions, returns the key with the script type implied by its version
+fn parse_extended_key(key: &str) -> Result<(ExtendedPubKey, Option<ScriptType>), String> {
+    let mut data = base58::from_check(key).map_err(|_| "invalid extended public key")?;
+    if data.len() != 78 {
+        return Err("invalid extended public key".to_string());
+    }
+
+    let (version, script_type) = match [data[0], data[1], data[2], data[3]] {
+        XPUB => (XPUB, None),
+        YPUB => (XPUB, Some(ScriptType::P2shP2wpkh)),
+        ZPUB => (XPUB, Some(ScriptType::P2wpkh)),
+        TPUB => (TPUB, None),
+        UPUB => (TPUB, Some(ScriptType::P2shP2wpkh)),
+        VPUB => (TPUB, Some(ScriptType::P2wpkh)),
+        _ => return Err("unsupported extended key version".to_string()),
+    };
+    data[..4].copy_from_slice(&version);
+
+    let xpub = ExtendedPubKey::decode(&data).map_err(|err| err.to_string())?;
+    Ok((xpub, script_type))
+}
+
+impl HdKey {
+    // plain keys use the external chain /0/* or the change chain /1/*,
+    // script_type overrides the type implied by the key version
+    pub fn parse(
+        key: &str,
+        script_type: Option<ScriptType>,
+        change: bool,
+    ) -> Result<HdKey, String> {
+        let key = key.trim();
+        if key.contains('(') {
+            return HdKey::parse_descriptor(key);
+        }
+
+        let (xpub, implied) = parse_extended_key(key)?;
+        Ok(HdKey {
+            xpub,
+            script_type: script_type.or(implied).unwrap_or(ScriptType::P2pkh),
+            origin: None,
+            chain: vec![ChildNumber::Normal {
+                index: change as u32,
+            }],
+        })
+    }
+
+    // pkh(), sh(wpkh()), wpkh() and tr() with a single ranged key, e.g. wpkh([d34db33f/84h/1h/0h]tpub.../0/*)
+    fn parse_descriptor(desc: &str) -> Result<HdKey, String> {
+        let desc = desc.split('#').next().unwrap_or(desc);
+
+        let (script_type, inner) = [
+            ("sh(wpkh(", "))", ScriptType::P2shP2wpkh),
+            ("pkh(", ")", ScriptType::P2pkh),
+            ("wpkh(", ")", ScriptType::P2wpkh),
+            ("tr(", ")", ScriptType::P2tr),
+        ]
+        .iter()
+        .find_map(|(prefix, suffix, script_type)| {
+            desc.strip_prefix(prefix)
+                .and_then(|rest| rest.strip_suffix(suffix))
+                .map(|inner| (*script_type, inner))
+        })
+        .ok_or("unsupported descriptor, expected pkh, sh(wpkh), wpkh or tr")?;
+
+        let (origin, key) = match inner.strip_prefix('[') {
+            Some(rest) => {
+                let (origin, key) = rest.split_once(']').ok_or("invalid key origin")?;
+                let (fingerprint, path) = origin.split_once('/').unwrap_or((origin, ""));
+                let fingerprint =
+                    Fingerprint::from_hex(fingerprint).map_err(|_| "invalid key fingerprint")?;
+                (Some((fingerprint, parse_steps(path)?)), key)
+            }
+            None => (None, inner),
+        };
+
+        let (key, steps) = key.split_once('/').ok_or("descriptor must be ranged")?;
+        let steps = steps
+            .strip_suffix("/*")
+            .or_else(|| steps.strip_suffix('*'))
+            .ok_or("descriptor must end with unhardened /*")?;
+
+        let chain = parse_steps(steps)?;
+        if chain.iter().any(ChildNumber::is_hardened) {
+            return Err("hardened derivation needs the private key".to_string());
+        }
+
+        let (xpub, _) = parse_extended_key(key)?;
+        Ok(HdKey {
+            xpub,
+            script_type,
+            origin,
+            chain,
+        })
+    }
+
+    pub fn derive<C: Verification>(
+        &self,
+        secp: &Secp256k1<C>,
+        index: u32,
+        network: Option<Network>,
+    ) -> Result<DerivedAddress, String> {
+        let index_step = ChildNumber::from_normal_idx(index).map_err(|err| err.to_string())?;
+        let mut steps = self.chain.clone();
+        steps.push(index_step);
+
+        let child = self
+            .xpub
+            .derive_pub(secp, &steps)
+            .map_err(|err| err.to_string())?;
+        let address = address(
+            secp,
+            child.public_key,
+            self.script_type,
+            network.unwrap_or(self.xpub.network),
+        )?;
+
+        Ok(DerivedAddress {
+            index,
+            script_pubkey: address.script_pubkey().to_hex(),
+            address: address.to_string(),
+            path: self.path(&steps),
+        })
+    }
+
+    // full path when the key origin is known or the key is at account level,
+    // otherwise the path relative to the key
+    fn path(&self, steps: &[ChildNumber]) -> String {
+        let prefix = match &self.origin {
+            Some((_, origin)) => Some(origin.clone()),
+            None if self.xpub.depth == 3 && self.xpub.child_number.is_hardened() => {
+                let coin = match self.xpub.network {
+                    Network::Bitcoin => 0,
+                    _ => 1,
+                };
+                Some(vec![
+                    ChildNumber::Hardened {
+                        index: self.script_type.purpose(),
+                    },
+                    ChildNumber::Hardened { index: coin },
+                    self.xpub.child_number,
+                ])
+            }
+            None => None,
+        };
+
+        let steps = steps.iter().map(ChildNumber::to_string);
+        match prefix {
+            Some(prefix) => std::iter::once("m".to_string())
+                .chain(prefix.iter().map(ChildNumber::to_string))
+                .chain(steps)
+                .collect::<Vec<_>>()
+                .join("/"),
+            None => steps.collect::<Vec<_>>().join("/"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct DeriveRequest {
+    // xpub/ypub/zpub (or tpub/upub/vpub) or a ranged descriptor
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub key: Option<String>,
+
+    pub script_type: Option<ScriptType>,
+
+    // plain keys only, derive from the change chain
+    pub change: Option<bool>,
+
+    #[validate(length(min = 1, max = 1000, message = "must be between 1 and 1000"))]
+    pub indices: Option<Vec<u32>>,
+
+    // used when indices are missing
+    pub start: Option<u32>,
+
+    #[validate(range(min = 1, max = 1000, message = "must be between 1 and 1000"))]
+    pub count: Option<u32>,
+
+    // defaults to the network of the key, tpub keys need it for regtest addresses
+    pub network: Option<HdNetwork>,
+}
+
+#[post("/derive")]
+pub(super) async fn derive(json: web::Json<DeriveRequest>) -> impl Responder {
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    let key = match HdKey::parse(
+        json.key.as_ref().unwrap(),
+        json.script_type,
+        json.change.unwrap_or(false),
+    ) {
+        Ok(key) => key,
+        Err(message) => return HttpResponse::BadRequest().json(ErrorResponse { message }),
+    };
+
+    let indices = match &json.indices {
+        Some(indices) => indices.clone(),
+        None => {
+            let start = json.start.unwrap_or(0);
+            (start..start.saturating_add(json.count.unwrap_or(20))).collect()
+        }
+    };
+
+    let secp = Secp256k1::verification_only();
+    let network = json.network.map(Network::from);
+
+    match indices
+        .into_iter()
+        .map(|index| key.derive(&secp, index, network))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(addresses) => HttpResponse::Ok().json(addresses),
+        Err(message) => HttpResponse::BadRequest().json(ErrorResponse { message }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BIP84 test vector, abandon abandon ... about
+    const ZPUB_84: &str = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+
+    #[test]
+    fn derive_zpub() {
+        let secp = Secp256k1::verification_only();
+        let key = HdKey::parse(ZPUB_84, None, false).unwrap();
+
+        let first = key.derive(&secp, 0, None).unwrap();
+        assert_eq!(first.address, "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+        assert_eq!(first.path, "m/84'/0'/0'/0/0");
+
+        let change = HdKey::parse(ZPUB_84, None, true).unwrap();
+        assert_eq!(
+            change.derive(&secp, 0, None).unwrap().address,
+            "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el"
+        );
+    }
+
+    #[test]
+    fn derive_descriptor() {
+        let secp = Secp256k1::verification_only();
+        let xpub = "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ";
+        let key = HdKey::parse(
+            &format!("tr([73c5da0a/86'/0'/0']{}/0/*)", xpub),
+            None,
+            false,
+        )
+        .unwrap();
+
+        let first = key.derive(&secp, 0, None).unwrap();
+        assert_eq!(
+            first.address,
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+        assert_eq!(first.path, "m/86'/0'/0'/0/0");
+
+        assert!(HdKey::parse(&format!("wpkh({}/0h/*)", xpub), None, false).is_err());
+    }
+}
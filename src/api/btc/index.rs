@@ -0,0 +1,248 @@
+use std::str::FromStr;
+
+use actix_web::{delete, get, post, web, HttpResponse, Responder};
+use bitcoin::{hashes::hex::ToHex, Address, Amount};
+use log::error;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+    api::btc::{
+        handler::{ErrorResponse, Utxo},
+        model::{AddressBalance, IndexHistoryEntry, IndexStatus},
+    },
+    config::BitcoinRpcConfig,
+    indexer::{IndexStore, IndexedScript},
+    webhooks::now,
+};
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct IndexAddressRequest {
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub address: Option<String>,
+}
+
+fn store_error_response(err: sled::Error) -> HttpResponse {
+    error!("index store error: {}", err);
+    HttpResponse::InternalServerError().json(ErrorResponse {
+        message: "failed to access index storage".to_string(),
+    })
+}
+
+fn invalid_address_response() -> HttpResponse {
+    HttpResponse::BadRequest().json(ErrorResponse {
+        message: "invalid Bitcoin address".to_string(),
+    })
+}
+
+fn not_indexed_response() -> HttpResponse {
+    HttpResponse::NotFound().json(ErrorResponse {
+        message: "address is not indexed".to_string(),
+    })
+}
+
+// script of a registered address, otherwise a ready error response
+async fn indexed_script(store: &IndexStore, address: &str) -> Result<String, HttpResponse> {
+    let script_pubkey = match Address::from_str(address) {
+        Ok(address) => address.script_pubkey().to_hex(),
+        Err(_) => return Err(invalid_address_response()),
+    };
+
+    match store.script(&script_pubkey) {
+        Ok(Some(_)) => Ok(script_pubkey),
+        Ok(None) => Err(not_indexed_response()),
+        Err(err) => Err(store_error_response(err)),
+    }
+}
+
+async fn tip_height(store: &IndexStore) -> Result<Option<u64>, HttpResponse> {
+    store
+        .tip()
+        .map(|tip| tip.map(|tip| tip.height))
+        .map_err(store_error_response)
+}
+
+#[get("/index")]
+pub(super) async fn index_status(
+    store: web::Data<IndexStore>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let tip = match store.tip() {
+        Ok(tip) => tip,
+        Err(err) => return store_error_response(err),
+    };
+
+    let backfilling = match store.backfills() {
+        Ok(backfills) => backfills.len(),
+        Err(err) => return store_error_response(err),
+    };
+
+    match store.scripts() {
+        Ok(scripts) => HttpResponse::Ok().json(IndexStatus {
+            enabled: btc_rpc_cfg.bitcoin_indexer_enabled,
+            height: tip.as_ref().map(|tip| tip.height),
+            hash: tip.map(|tip| tip.hash),
+            addresses: scripts.len(),
+            backfilling,
+        }),
+        Err(err) => store_error_response(err),
+    }
+}
+
+#[post("/index/addresses")]
+pub(super) async fn index_address(
+    json: web::Json<IndexAddressRequest>,
+    store: web::Data<IndexStore>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    let address = json.address.as_ref().unwrap();
+    let script_pubkey = match Address::from_str(address) {
+        Ok(address) => address.script_pubkey().to_hex(),
+        Err(_) => return invalid_address_response(),
+    };
+
+    match store.script(&script_pubkey) {
+        Ok(Some(script)) => return HttpResponse::Ok().json(script),
+        Ok(None) => {}
+        Err(err) => return store_error_response(err),
+    }
+
+    let registered_height = match tip_height(&store).await {
+        Ok(height) => height,
+        Err(resp) => return resp,
+    };
+
+    let script = IndexedScript {
+        address: address.to_string(),
+        script_pubkey,
+        registered_height,
+        created_at: now(),
+    };
+
+    // blocks indexed before the registration are scanned for it by the indexer
+    let backfill_from = registered_height.map(|_| btc_rpc_cfg.bitcoin_indexer_start_height);
+
+    match store.add_script(&script, backfill_from) {
+        Ok(_) => HttpResponse::Created().json(script),
+        Err(err) => store_error_response(err),
+    }
+}
+
+#[get("/index/addresses")]
+pub(super) async fn indexed_addresses(store: web::Data<IndexStore>) -> impl Responder {
+    match store.scripts() {
+        Ok(scripts) => HttpResponse::Ok().json(scripts),
+        Err(err) => store_error_response(err),
+    }
+}
+
+#[delete("/index/addresses/{address}")]
+pub(super) async fn remove_indexed_address(
+    path: web::Path<String>,
+    store: web::Data<IndexStore>,
+) -> impl Responder {
+    let script_pubkey = match indexed_script(&store, &path.into_inner()).await {
+        Ok(script_pubkey) => script_pubkey,
+        Err(resp) => return resp,
+    };
+
+    match store.remove_script(&script_pubkey) {
+        Ok(Some(script)) => HttpResponse::Ok().json(script),
+        Ok(None) => not_indexed_response(),
+        Err(err) => store_error_response(err),
+    }
+}
+
+#[get("/index/address/{address}/utxos")]
+pub(super) async fn indexed_utxos(
+    path: web::Path<String>,
+    store: web::Data<IndexStore>,
+) -> impl Responder {
+    let script_pubkey = match indexed_script(&store, &path.into_inner()).await {
+        Ok(script_pubkey) => script_pubkey,
+        Err(resp) => return resp,
+    };
+
+    match store.utxos(&script_pubkey) {
+        Ok(utxos) => HttpResponse::Ok().json(
+            utxos
+                .into_iter()
+                .map(|utxo| Utxo {
+                    tx_id: Some(utxo.txid),
+                    vout: Some(utxo.vout),
+                    amount: Some(Amount::from_sat(utxo.value).to_btc()),
+                    pk_script: Some(utxo.script_pubkey),
+                    witness_script: None,
+                    leaf_script: None,
+                    control_block: None,
+                })
+                .collect::<Vec<_>>(),
+        ),
+        Err(err) => store_error_response(err),
+    }
+}
+
+#[get("/index/address/{address}/balance")]
+pub(super) async fn indexed_balance(
+    path: web::Path<String>,
+    store: web::Data<IndexStore>,
+) -> impl Responder {
+    let address = path.into_inner();
+    let script_pubkey = match indexed_script(&store, &address).await {
+        Ok(script_pubkey) => script_pubkey,
+        Err(resp) => return resp,
+    };
+
+    let height = match tip_height(&store).await {
+        Ok(height) => height.unwrap_or_default(),
+        Err(resp) => return resp,
+    };
+
+    match store.utxos(&script_pubkey) {
+        Ok(utxos) => HttpResponse::Ok().json(AddressBalance {
+            address,
+            balance: Amount::from_sat(utxos.iter().map(|utxo| utxo.value).sum()).to_btc(),
+            utxo_count: utxos.len(),
+            height: height as usize,
+        }),
+        Err(err) => store_error_response(err),
+    }
+}
+
+#[get("/index/address/{address}/history")]
+pub(super) async fn indexed_history(
+    path: web::Path<String>,
+    store: web::Data<IndexStore>,
+) -> impl Responder {
+    let script_pubkey = match indexed_script(&store, &path.into_inner()).await {
+        Ok(script_pubkey) => script_pubkey,
+        Err(resp) => return resp,
+    };
+
+    let height = match tip_height(&store).await {
+        Ok(height) => height.unwrap_or_default(),
+        Err(resp) => return resp,
+    };
+
+    match store.history(&script_pubkey) {
+        Ok(history) => HttpResponse::Ok().json(
+            history
+                .into_iter()
+                .rev()
+                .map(|entry| IndexHistoryEntry {
+                    confirmations: (height + 1).saturating_sub(entry.height),
+                    txid: entry.txid,
+                    height: entry.height,
+                    block_hash: entry.block_hash,
+                    received: Amount::from_sat(entry.received).to_btc(),
+                    sent: Amount::from_sat(entry.sent).to_btc(),
+                })
+                .collect::<Vec<_>>(),
+        ),
+        Err(err) => store_error_response(err),
+    }
+}
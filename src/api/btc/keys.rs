@@ -0,0 +1,161 @@
+use std::str::FromStr;
+
+use actix_web::{post, web, HttpResponse, Responder};
+use bip39::Mnemonic;
+use bitcoin::{
+    secp256k1::Secp256k1,
+    util::bip32::{DerivationPath, ExtendedPrivKey, ExtendedPubKey},
+    Network,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+    api::btc::{
+        handler::ErrorResponse,
+        hd::{address, HdNetwork, ScriptType},
+        model::{DerivedKey, GeneratedMnemonic, KeyAddresses},
+    },
+    config::BitcoinRpcConfig,
+};
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct MnemonicRequest {
+    #[validate(range(min = 12, max = 24, message = "must be 12, 15, 18, 21 or 24"))]
+    pub word_count: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct DeriveKeyRequest {
+    // either a mnemonic or an xprv
+    pub mnemonic: Option<String>,
+    pub passphrase: Option<String>,
+    pub xprv: Option<String>,
+
+    // defaults to m/84'/1'/0'/0/0
+    pub path: Option<String>,
+
+    // defaults to the network of the xprv, testnet for mnemonics
+    pub network: Option<HdNetwork>,
+}
+
+fn disabled_response() -> HttpResponse {
+    HttpResponse::Forbidden().json(ErrorResponse {
+        message: "key tools are disabled".to_string(),
+    })
+}
+
+fn bad_request(message: String) -> HttpResponse {
+    HttpResponse::BadRequest().json(ErrorResponse { message })
+}
+
+#[post("/keys/mnemonic")]
+pub(super) async fn generate_mnemonic(
+    json: web::Json<MnemonicRequest>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    if !btc_rpc_cfg.bitcoin_key_tools_enabled {
+        return disabled_response();
+    }
+
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    match Mnemonic::generate(json.word_count.unwrap_or(12)) {
+        Ok(mnemonic) => HttpResponse::Ok().json(GeneratedMnemonic {
+            mnemonic: mnemonic.to_string(),
+        }),
+        Err(err) => bad_request(err.to_string()),
+    }
+}
+
+#[post("/keys/derive")]
+pub(super) async fn derive_key(
+    json: web::Json<DeriveKeyRequest>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    if !btc_rpc_cfg.bitcoin_key_tools_enabled {
+        return disabled_response();
+    }
+
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    match derive(&json) {
+        Ok(key) => HttpResponse::Ok().json(key),
+        Err(message) => bad_request(message),
+    }
+}
+
+fn derive(json: &DeriveKeyRequest) -> Result<DerivedKey, String> {
+    let master = match (&json.mnemonic, &json.xprv) {
+        (Some(mnemonic), None) => {
+            let mnemonic = Mnemonic::parse(mnemonic.as_str()).map_err(|err| err.to_string())?;
+            let seed = mnemonic.to_seed(json.passphrase.as_deref().unwrap_or_default());
+            let network = json.network.map(Network::from).unwrap_or(Network::Testnet);
+            ExtendedPrivKey::new_master(network, &seed).map_err(|err| err.to_string())?
+        }
+        (None, Some(xprv)) => ExtendedPrivKey::from_str(xprv).map_err(|err| err.to_string())?,
+        _ => return Err("either mnemonic or xprv is required".to_string()),
+    };
+
+    let path = DerivationPath::from_str(json.path.as_deref().unwrap_or("m/84'/1'/0'/0/0"))
+        .map_err(|_| "invalid derivation path".to_string())?;
+
+    let secp = Secp256k1::new();
+    let xprv = master
+        .derive_priv(&secp, &path)
+        .map_err(|err| err.to_string())?;
+    let xpub = ExtendedPubKey::from_priv(&secp, &xprv);
+
+    let network = json.network.map(Network::from).unwrap_or(master.network);
+    let key_address = |script_type| {
+        address(&secp, xpub.public_key, script_type, network).map(|address| address.to_string())
+    };
+
+    Ok(DerivedKey {
+        master_fingerprint: master.fingerprint(&secp).to_string(),
+        path: path.to_string(),
+        xprv: xprv.to_string(),
+        xpub: xpub.to_string(),
+        wif: xprv.to_priv().to_wif(),
+        public_key: xpub.public_key.to_string(),
+        addresses: KeyAddresses {
+            p2pkh: key_address(ScriptType::P2pkh)?,
+            p2sh_p2wpkh: key_address(ScriptType::P2shP2wpkh)?,
+            p2wpkh: key_address(ScriptType::P2wpkh)?,
+            p2tr: key_address(ScriptType::P2tr)?,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn derive_bip84_key() {
+        let key = derive(&DeriveKeyRequest {
+            mnemonic: Some(MNEMONIC.to_string()),
+            passphrase: None,
+            xprv: None,
+            path: Some("m/84'/0'/0'/0/0".to_string()),
+            network: Some(HdNetwork::Bitcoin),
+        })
+        .unwrap();
+
+        assert_eq!(key.master_fingerprint, "73c5da0a");
+        assert_eq!(
+            key.wif,
+            "KyZpNDKnfs94vbrwhJneDi77V6jF64PWPF8x5cdJb8ifgg2DUc9d"
+        );
+        assert_eq!(
+            key.addresses.p2wpkh,
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+    }
+}
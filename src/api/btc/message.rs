@@ -0,0 +1,474 @@
+use std::str::FromStr;
+
+use actix_web::{post, web, HttpResponse, Responder};
+use bitcoin::{
+    blockdata::{opcodes, script::Builder},
+    consensus::{deserialize, serialize},
+    hashes::{sha256, Hash, HashEngine},
+    secp256k1::{KeyPair, Message, Secp256k1, Signing, Verification, XOnlyPublicKey},
+    util::{
+        misc::{signed_msg_hash, MessageSignature},
+        schnorr::{SchnorrSig, TapTweak},
+        sighash::{Prevouts, SighashCache},
+    },
+    Address, AddressType, EcdsaSig, EcdsaSighashType, OutPoint, PackedLockTime, PrivateKey,
+    PublicKey, SchnorrSighashType, Script, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::api::btc::{
+    handler::ErrorResponse,
+    model::{MessageFormat, MessageSignatureResult, MessageVerification},
+};
+
+const BIP322_TAG: &[u8] = b"BIP0322-signed-message";
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SignMessageRequest {
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub address: Option<String>,
+
+    #[validate(required)]
+    pub message: Option<String>,
+
+    // WIF
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub private_key: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct VerifyMessageRequest {
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub address: Option<String>,
+
+    #[validate(required)]
+    pub message: Option<String>,
+
+    // base64
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub signature: Option<String>,
+}
+
+// P2PKH addresses use the legacy format, segwit v0 and taproot ones BIP322 simple,
+// P2SH ones are taken as P2SH-P2WPKH
+fn message_format(address: &Address) -> Result<MessageFormat, String> {
+    match address.address_type() {
+        Some(AddressType::P2pkh) => Ok(MessageFormat::Legacy),
+        Some(AddressType::P2wpkh) | Some(AddressType::P2sh) | Some(AddressType::P2tr) => {
+            Ok(MessageFormat::Bip322)
+        }
+        _ => Err("only P2PKH, P2WPKH, P2SH-P2WPKH and P2TR addresses are supported".to_string()),
+    }
+}
+
+// redeem script of the P2SH-P2WPKH address of the key, pushed by the to_sign input
+fn p2sh_p2wpkh_redeem_script(public_key: &PublicKey) -> Result<Script, String> {
+    let pubkey_hash = public_key
+        .wpubkey_hash()
+        .ok_or("uncompressed keys can't be used with segwit")?;
+    Ok(Script::new_v0_p2wpkh(&pubkey_hash))
+}
+
+fn bip322_message_hash(message: &str) -> sha256::Hash {
+    let tag = sha256::Hash::hash(BIP322_TAG);
+    let mut engine = sha256::Hash::engine();
+    engine.input(&tag);
+    engine.input(&tag);
+    engine.input(message.as_bytes());
+    sha256::Hash::from_engine(engine)
+}
+
+// virtual tx paying the address, its only output is spent by to_sign
+fn bip322_to_spend(script_pubkey: &Script, message: &str) -> Transaction {
+    Transaction {
+        version: 0,
+        lock_time: PackedLockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(Txid::all_zeros(), 0xffffffff),
+            script_sig: Builder::new()
+                .push_opcode(opcodes::OP_FALSE)
+                .push_slice(&bip322_message_hash(message))
+                .into_script(),
+            sequence: Sequence::ZERO,
+            witness: Witness::default(),
+        }],
+        output: vec![TxOut {
+            value: 0,
+            script_pubkey: script_pubkey.clone(),
+        }],
+    }
+}
+
+// script_sig is empty except for P2SH-P2WPKH where it pushes the redeem script
+fn bip322_to_sign(to_spend: &Transaction, script_sig: Script, witness: Witness) -> Transaction {
+    Transaction {
+        version: 0,
+        lock_time: PackedLockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(to_spend.txid(), 0),
+            script_sig,
+            sequence: Sequence::ZERO,
+            witness,
+        }],
+        output: vec![TxOut {
+            value: 0,
+            script_pubkey: Builder::new()
+                .push_opcode(opcodes::all::OP_RETURN)
+                .into_script(),
+        }],
+    }
+}
+
+fn p2wpkh_sighash(to_sign: &Transaction, public_key: &PublicKey) -> Result<Message, String> {
+    let pubkey_hash = public_key
+        .wpubkey_hash()
+        .ok_or("uncompressed keys can't be used with segwit")?;
+    let script_code = Script::new_p2pkh(&bitcoin::PubkeyHash::from_hash(pubkey_hash.as_hash()));
+
+    let sighash = SighashCache::new(to_sign)
+        .segwit_signature_hash(0, &script_code, 0, EcdsaSighashType::All)
+        .map_err(|err| err.to_string())?;
+    Message::from_slice(&sighash).map_err(|err| err.to_string())
+}
+
+fn p2tr_sighash(
+    to_sign: &Transaction,
+    to_spend: &Transaction,
+    hash_ty: SchnorrSighashType,
+) -> Result<Message, String> {
+    let sighash = SighashCache::new(to_sign)
+        .taproot_key_spend_signature_hash(0, &Prevouts::All(&to_spend.output), hash_ty)
+        .map_err(|err| err.to_string())?;
+    Message::from_slice(&sighash).map_err(|err| err.to_string())
+}
+
+pub fn sign_message<C: Signing + Verification>(
+    secp: &Secp256k1<C>,
+    address: &Address,
+    message: &str,
+    private_key: &PrivateKey,
+) -> Result<(MessageFormat, String), String> {
+    let format = message_format(address)?;
+    let public_key = private_key.public_key(secp);
+
+    if format == MessageFormat::Legacy {
+        if Address::p2pkh(&public_key, address.network) != *address {
+            return Err("private key does not belong to the address".to_string());
+        }
+
+        let hash = signed_msg_hash(message);
+        let msg = Message::from_slice(&hash).map_err(|err| err.to_string())?;
+        let signature = MessageSignature::new(
+            secp.sign_ecdsa_recoverable(&msg, &private_key.inner),
+            private_key.compressed,
+        );
+        return Ok((format, base64::encode(signature.serialize())));
+    }
+
+    let to_spend = bip322_to_spend(&address.script_pubkey(), message);
+    let mut to_sign = bip322_to_sign(&to_spend, Script::new(), Witness::default());
+
+    let witness = if address.address_type() != Some(AddressType::P2tr) {
+        let owned = if address.address_type() == Some(AddressType::P2sh) {
+            to_sign.input[0].script_sig = Builder::new()
+                .push_slice(p2sh_p2wpkh_redeem_script(&public_key)?.as_bytes())
+                .into_script();
+            Address::p2shwpkh(&public_key, address.network).ok()
+        } else {
+            Address::p2wpkh(&public_key, address.network).ok()
+        };
+        if owned.as_ref() != Some(address) {
+            return Err("private key does not belong to the address".to_string());
+        }
+
+        let msg = p2wpkh_sighash(&to_sign, &public_key)?;
+        let sig = EcdsaSig::sighash_all(secp.sign_ecdsa_low_r(&msg, &private_key.inner));
+        Witness::from_vec(vec![sig.to_vec(), public_key.to_bytes()])
+    } else {
+        let keypair = KeyPair::from_secret_key(secp, &private_key.inner);
+        let (internal_key, _) = XOnlyPublicKey::from_keypair(&keypair);
+        if Address::p2tr(secp, internal_key, None, address.network) != *address {
+            return Err("private key does not belong to the address".to_string());
+        }
+
+        let msg = p2tr_sighash(&to_sign, &to_spend, SchnorrSighashType::Default)?;
+        let tweaked = keypair.tap_tweak(secp, None).to_inner();
+        let sig = SchnorrSig {
+            sig: secp.sign_schnorr_with_aux_rand(&msg, &tweaked, &rand::random()),
+            hash_ty: SchnorrSighashType::Default,
+        };
+        Witness::from_vec(vec![sig.to_vec()])
+    };
+
+    to_sign.input[0].witness = witness;
+    Ok((format, base64::encode(serialize(&to_sign.input[0].witness))))
+}
+
+pub fn verify_message<C: Verification>(
+    secp: &Secp256k1<C>,
+    address: &Address,
+    message: &str,
+    signature: &str,
+) -> Result<(MessageFormat, bool), String> {
+    let format = message_format(address)?;
+    let signature = base64::decode(signature).map_err(|_| "signature is not base64")?;
+
+    if format == MessageFormat::Legacy {
+        let valid = MessageSignature::from_slice(&signature)
+            .and_then(|sig| sig.is_signed_by_address(secp, address, signed_msg_hash(message)))
+            .unwrap_or(false);
+        return Ok((format, valid));
+    }
+
+    let witness: Witness = match deserialize(&signature) {
+        Ok(witness) => witness,
+        Err(_) => return Ok((format, false)),
+    };
+    let script_pubkey = address.script_pubkey();
+    let to_spend = bip322_to_spend(&script_pubkey, message);
+    let mut to_sign = bip322_to_sign(&to_spend, Script::new(), witness.clone());
+
+    let valid = if address.address_type() != Some(AddressType::P2tr) {
+        let items: Vec<&[u8]> = witness.iter().collect();
+        match items[..] {
+            [sig, public_key] => {
+                match (EcdsaSig::from_slice(sig), PublicKey::from_slice(public_key)) {
+                    (Ok(sig), Ok(public_key)) if sig.hash_ty == EcdsaSighashType::All => {
+                        // the witness key has to pay to the address
+                        let owned = if address.address_type() == Some(AddressType::P2sh) {
+                            match p2sh_p2wpkh_redeem_script(&public_key) {
+                                Ok(redeem_script) => {
+                                    to_sign.input[0].script_sig = Builder::new()
+                                        .push_slice(redeem_script.as_bytes())
+                                        .into_script();
+                                    Script::new_p2sh(&redeem_script.script_hash()) == script_pubkey
+                                }
+                                Err(_) => false,
+                            }
+                        } else {
+                            public_key
+                                .wpubkey_hash()
+                                .is_some_and(|hash| hash[..] == script_pubkey[2..])
+                        };
+
+                        if owned {
+                            let msg = p2wpkh_sighash(&to_sign, &public_key)?;
+                            secp.verify_ecdsa(&msg, &sig.sig, &public_key.inner).is_ok()
+                        } else {
+                            false
+                        }
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    } else {
+        let program = &script_pubkey[2..];
+        let items: Vec<&[u8]> = witness.iter().collect();
+        match (&items[..], XOnlyPublicKey::from_slice(program)) {
+            ([sig], Ok(output_key)) => match SchnorrSig::from_slice(sig) {
+                Ok(sig) => {
+                    let msg = p2tr_sighash(&to_sign, &to_spend, sig.hash_ty)?;
+                    secp.verify_schnorr(&sig.sig, &msg, &output_key).is_ok()
+                }
+                Err(_) => false,
+            },
+            _ => false,
+        }
+    };
+
+    Ok((format, valid))
+}
+
+#[post("/message/sign")]
+pub(super) async fn sign(json: web::Json<SignMessageRequest>) -> impl Responder {
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    let address = match Address::from_str(json.address.as_ref().unwrap()) {
+        Ok(address) => address,
+        Err(_) => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                message: "invalid Bitcoin address".to_string(),
+            })
+        }
+    };
+    let private_key = match PrivateKey::from_wif(json.private_key.as_ref().unwrap()) {
+        Ok(private_key) => private_key,
+        Err(_) => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                message: "invalid private key".to_string(),
+            })
+        }
+    };
+
+    let secp = Secp256k1::new();
+    match sign_message(
+        &secp,
+        &address,
+        json.message.as_ref().unwrap(),
+        &private_key,
+    ) {
+        Ok((format, signature)) => HttpResponse::Ok().json(MessageSignatureResult {
+            address: address.to_string(),
+            format,
+            signature,
+        }),
+        Err(message) => HttpResponse::BadRequest().json(ErrorResponse { message }),
+    }
+}
+
+#[post("/message/verify")]
+pub(super) async fn verify(json: web::Json<VerifyMessageRequest>) -> impl Responder {
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    let address = match Address::from_str(json.address.as_ref().unwrap()) {
+        Ok(address) => address,
+        Err(_) => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                message: "invalid Bitcoin address".to_string(),
+            })
+        }
+    };
+
+    let secp = Secp256k1::verification_only();
+    match verify_message(
+        &secp,
+        &address,
+        json.message.as_ref().unwrap(),
+        json.signature.as_ref().unwrap(),
+    ) {
+        Ok((format, valid)) => HttpResponse::Ok().json(MessageVerification {
+            address: address.to_string(),
+            format,
+            valid,
+        }),
+        Err(message) => HttpResponse::BadRequest().json(ErrorResponse { message }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BIP322 test vectors
+    const P2WPKH: &str = "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l";
+    const P2WPKH_KEY: &str = "L3VFeEujGtevx9w18HD1fhRbCH67Az2dpCymeRE1SoPK6XQtaN2k";
+    const P2TR: &str = "bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3";
+
+    #[test]
+    fn message_hash() {
+        assert_eq!(
+            bip322_message_hash("").to_string(),
+            "c90c269c4f8fcbe6880f72a721ddfbf1914268a794cbb21cfafee13770ae19f1"
+        );
+        assert_eq!(
+            bip322_message_hash("Hello World").to_string(),
+            "f0eb03b1a75ac6d9847f55c624a99169b5dccba2a31f5b23bea77ba270de0a7a"
+        );
+    }
+
+    #[test]
+    fn bip322_vectors() {
+        let secp = Secp256k1::new();
+        let address = Address::from_str(P2WPKH).unwrap();
+
+        let signature = "AkcwRAIgZRfIY3p7/DoVTty6YZbWS71bc5Vct9p9Fia83eRmw2QCICK/ENGfwLtptFluMGs2KsqoNSk89pO7F29zJLUx9a/sASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI=";
+        assert_eq!(
+            verify_message(&secp, &address, "Hello World", signature).unwrap(),
+            (MessageFormat::Bip322, true)
+        );
+        assert!(
+            !verify_message(&secp, &address, "Hello", signature)
+                .unwrap()
+                .1
+        );
+
+        let key = PrivateKey::from_wif(P2WPKH_KEY).unwrap();
+        let (_, signature) = sign_message(&secp, &address, "Hello World", &key).unwrap();
+        assert!(
+            verify_message(&secp, &address, "Hello World", &signature)
+                .unwrap()
+                .1
+        );
+
+        let taproot = Address::from_str(P2TR).unwrap();
+        let signature = "AUHd69PrJQEv+oKTfZ8l+WROBHuy9HKrbFCJu7U1iK2iiEy1vMU5EfMtjc+VSHM7aU0SDbak5IUZRVno2P5mjSafAQ==";
+        assert!(
+            verify_message(&secp, &taproot, "Hello World", signature)
+                .unwrap()
+                .1
+        );
+
+        let (_, signature) = sign_message(&secp, &taproot, "Hello World", &key).unwrap();
+        assert!(
+            verify_message(&secp, &taproot, "Hello World", &signature)
+                .unwrap()
+                .1
+        );
+    }
+
+    #[test]
+    fn p2sh_p2wpkh_roundtrip() {
+        let secp = Secp256k1::new();
+        let key = PrivateKey::from_wif(P2WPKH_KEY).unwrap();
+        let public_key = key.public_key(&secp);
+        let address = Address::p2shwpkh(&public_key, bitcoin::Network::Bitcoin).unwrap();
+
+        let (format, signature) = sign_message(&secp, &address, "Hello World", &key).unwrap();
+        assert_eq!(format, MessageFormat::Bip322);
+        assert!(
+            verify_message(&secp, &address, "Hello World", &signature)
+                .unwrap()
+                .1
+        );
+        assert!(
+            !verify_message(&secp, &address, "Hello", &signature)
+                .unwrap()
+                .1
+        );
+
+        // the p2wpkh signature of the same key commits to another to_spend
+        let native = Address::p2wpkh(&public_key, bitcoin::Network::Bitcoin).unwrap();
+        let (_, native_signature) = sign_message(&secp, &native, "Hello World", &key).unwrap();
+        assert!(
+            !verify_message(&secp, &address, "Hello World", &native_signature)
+                .unwrap()
+                .1
+        );
+
+        // p2sh of another script
+        let other = Address::p2sh(&Script::new_op_return(&[]), bitcoin::Network::Bitcoin).unwrap();
+        assert!(sign_message(&secp, &other, "Hello World", &key).is_err());
+        assert!(
+            !verify_message(&secp, &other, "Hello World", &signature)
+                .unwrap()
+                .1
+        );
+    }
+
+    #[test]
+    fn legacy_roundtrip() {
+        let secp = Secp256k1::new();
+        let key = PrivateKey::from_wif(P2WPKH_KEY).unwrap();
+        let address = Address::p2pkh(&key.public_key(&secp), bitcoin::Network::Bitcoin);
+
+        let (format, signature) = sign_message(&secp, &address, "withdraw", &key).unwrap();
+        assert_eq!(format, MessageFormat::Legacy);
+        assert!(
+            verify_message(&secp, &address, "withdraw", &signature)
+                .unwrap()
+                .1
+        );
+        assert!(
+            !verify_message(&secp, &address, "other", &signature)
+                .unwrap()
+                .1
+        );
+    }
+}
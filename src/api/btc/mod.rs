@@ -1,3 +1,19 @@
+mod descriptor;
 pub mod handler;
-mod model;
-mod service;
+mod hd;
+mod index;
+mod keys;
+mod message;
+pub(crate) mod model;
+mod multisig;
+mod node;
+mod rest;
+mod rpc;
+mod script;
+pub(crate) mod service;
+mod taproot;
+mod wallet;
+mod watch;
+mod ws;
+
+pub use node::BitcoinNode;
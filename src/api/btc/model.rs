@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 // #[derive(Deserialize)]
@@ -28,39 +30,113 @@ pub struct RPCError {
 }
 
 #[derive(Deserialize, Serialize)]
-struct BlockchainInfoResult {
-    chain: String,
-    blocks: usize,
-    headers: usize,
-    bestblockhash: String,
+pub struct BlockchainInfoResult {
+    pub chain: String,
+    pub blocks: usize,
+    pub headers: usize,
+    pub bestblockhash: String,
     // difficulty: usize,
-    time: usize,
-    mediantime: usize,
-    verificationprogress: f64,
-    initialblockdownload: bool,
-    chainwork: String,
-    size_on_disk: usize,
-    pruned: bool,
-    warnings: String,
+    pub time: usize,
+    pub mediantime: usize,
+    pub verificationprogress: f64,
+    pub initialblockdownload: bool,
+    pub chainwork: String,
+    pub size_on_disk: usize,
+    pub pruned: bool,
+    pub warnings: String,
 }
 #[derive(Deserialize, Serialize)]
 pub struct BlockchainInfo {
-    result: Option<BlockchainInfoResult>,
+    pub result: Option<BlockchainInfoResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MempoolInfoResult {
+    pub loaded: bool,
+    pub size: usize,
+    pub bytes: usize,
+    pub usage: usize,
+    pub total_fee: Option<f64>,
+    pub maxmempool: usize,
+    pub mempoolminfee: f64,
+    pub minrelaytxfee: f64,
+    pub incrementalrelayfee: Option<f64>,
+    pub unbroadcastcount: Option<usize>,
+    pub fullrbf: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MempoolInfo {
+    pub result: Option<MempoolInfoResult>,
     pub error: Option<RPCError>,
 }
 
 #[derive(Deserialize, Serialize)]
-pub struct CreateTx {
-    result: Option<String>,
+pub struct RawMempool {
+    pub result: Option<HashMap<String, MempoolEntryResult>>,
     pub error: Option<RPCError>,
 }
 
+#[derive(Deserialize, Serialize)]
+pub struct MempoolTxIds {
+    pub result: Option<Vec<String>>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MempoolEntryDetails {
+    #[serde(flatten)]
+    pub entry: MempoolEntryResult,
+    pub ancestors: Vec<String>,
+    pub descendants: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct FeeHistogramBucket {
+    // sat/vB, inclusive
+    pub from_fee_rate: f64,
+    // sat/vB, exclusive, None for the last bucket
+    pub to_fee_rate: Option<f64>,
+    pub count: usize,
+    pub vsize: usize,
+    // BTC
+    pub total_fees: f64,
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct FeeRateResult {
-    pub feerate: f64,
+    // missing when the node has not enough data, e.g. on regtest
+    pub feerate: Option<f64>,
+    pub errors: Option<Vec<String>>,
     pub blocks: usize,
 }
 
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeEstimateMode {
+    Economical,
+    Conservative,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeSource {
+    EstimateSmartFee,
+    MempoolMinFee,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct FeeEstimate {
+    pub target: u16,
+    pub mode: FeeEstimateMode,
+    // block target the node actually used, None on fallback
+    pub blocks: Option<usize>,
+    pub btc_per_kvb: f64,
+    pub sat_per_vb: f64,
+    pub source: FeeSource,
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct FeeRate {
     pub result: Option<FeeRateResult>,
@@ -95,3 +171,721 @@ pub struct SendTx {
     pub result: Option<String>,
     pub error: Option<RPCError>,
 }
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BroadcastStatus {
+    Accepted,
+    AlreadyInMempool,
+    AlreadyInChain,
+    Rejected,
+    // the node answered without a rpc result
+    Failed,
+    Unreachable,
+    TimedOut,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct BroadcastNodeResult {
+    pub node: String,
+    pub status: BroadcastStatus,
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Broadcast {
+    pub success: bool,
+    pub txid: Option<String>,
+    pub nodes: Vec<BroadcastNodeResult>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TestMempoolAcceptFees {
+    pub base: f64,
+    #[serde(rename = "effective-feerate")]
+    pub effective_feerate: Option<f64>,
+    #[serde(rename = "effective-includes")]
+    pub effective_includes: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TestMempoolAcceptResult {
+    pub txid: String,
+    pub wtxid: Option<String>,
+    #[serde(rename = "package-error")]
+    pub package_error: Option<String>,
+    pub allowed: Option<bool>,
+    pub vsize: Option<usize>,
+    pub fees: Option<TestMempoolAcceptFees>,
+    #[serde(rename = "reject-reason")]
+    pub reject_reason: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TestMempoolAccept {
+    pub result: Option<Vec<TestMempoolAcceptResult>>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTxScriptPubKey {
+    pub asm: String,
+    pub hex: String,
+    #[serde(rename = "type")]
+    pub script_type: String,
+    pub address: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTxVout {
+    pub value: f64,
+    pub n: u32,
+    pub script_pub_key: RawTxScriptPubKey,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RawTxVin {
+    pub txid: Option<String>,
+    pub vout: Option<u32>,
+    pub coinbase: Option<String>,
+    pub sequence: u32,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RawTransactionResult {
+    pub txid: String,
+    pub hash: String,
+    pub size: usize,
+    pub vsize: usize,
+    pub weight: usize,
+    pub version: i32,
+    pub locktime: u32,
+    pub vin: Vec<RawTxVin>,
+    pub vout: Vec<RawTxVout>,
+    pub hex: String,
+    pub blockhash: Option<String>,
+    pub confirmations: Option<usize>,
+    pub time: Option<usize>,
+    pub blocktime: Option<usize>,
+    // only present in getblock verbosity 2
+    pub fee: Option<f64>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RawTransaction {
+    pub result: Option<RawTransactionResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RawTransactionHex {
+    pub result: Option<String>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MempoolEntryFees {
+    pub base: f64,
+    pub modified: f64,
+    pub ancestor: f64,
+    pub descendant: f64,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MempoolEntryResult {
+    pub vsize: usize,
+    pub weight: usize,
+    pub time: usize,
+    pub height: usize,
+    pub descendantcount: usize,
+    pub descendantsize: usize,
+    pub ancestorcount: usize,
+    pub ancestorsize: usize,
+    pub wtxid: String,
+    pub fees: MempoolEntryFees,
+    pub depends: Vec<String>,
+    pub spentby: Vec<String>,
+    #[serde(rename = "bip125-replaceable")]
+    pub bip125_replaceable: bool,
+    pub unbroadcast: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MempoolEntry {
+    pub result: Option<MempoolEntryResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockHeaderResult {
+    pub hash: String,
+    pub confirmations: isize,
+    pub height: usize,
+    pub version: i32,
+    pub version_hex: String,
+    pub merkleroot: String,
+    pub time: usize,
+    pub mediantime: usize,
+    pub nonce: u32,
+    pub bits: String,
+    pub difficulty: f64,
+    pub chainwork: String,
+    #[serde(rename = "nTx")]
+    pub n_tx: usize,
+    pub previousblockhash: Option<String>,
+    pub nextblockhash: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct BlockHeader {
+    pub result: Option<BlockHeaderResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockVerbosity {
+    // serialized block hex
+    Hex,
+    // block with txids
+    TxIds,
+    // block with decoded txs
+    Transactions,
+}
+
+impl BlockVerbosity {
+    pub fn level(self) -> u8 {
+        match self {
+            BlockVerbosity::Hex => 0,
+            BlockVerbosity::TxIds => 1,
+            BlockVerbosity::Transactions => 2,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum BlockTx {
+    TxId(String),
+    Transaction(Box<RawTransactionResult>),
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct BlockResult {
+    #[serde(flatten)]
+    pub header: BlockHeaderResult,
+    pub size: usize,
+    pub strippedsize: usize,
+    pub weight: usize,
+    pub tx: Vec<BlockTx>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct BlockInfo {
+    pub result: Option<BlockResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RawBlock {
+    pub result: Option<String>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct GetBlockHash {
+    pub result: Option<String>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct BlockPage {
+    #[serde(flatten)]
+    pub block: BlockResult,
+    pub page: usize,
+    pub limit: usize,
+    pub total_tx: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TxStatus {
+    pub txid: String,
+    pub in_mempool: bool,
+    pub confirmations: usize,
+    pub block_hash: Option<String>,
+    pub block_height: Option<usize>,
+    // BTC, None for coinbase
+    pub fee: Option<f64>,
+    // bip125 signaling, only known while in mempool
+    pub replaceable: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanTxOutSetUnspent {
+    pub txid: String,
+    pub vout: u32,
+    pub script_pub_key: String,
+    pub desc: String,
+    pub amount: f64,
+    pub coinbase: Option<bool>,
+    pub height: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ScanTxOutSetResult {
+    pub success: bool,
+    pub txouts: usize,
+    pub height: usize,
+    pub bestblock: String,
+    pub unspents: Vec<ScanTxOutSetUnspent>,
+    pub total_amount: f64,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ScanTxOutSet {
+    pub result: Option<ScanTxOutSetResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct AddressBalance {
+    pub address: String,
+    // BTC
+    pub balance: f64,
+    pub utxo_count: usize,
+    pub height: usize,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum RawFormat {
+    Hex,
+    Bin,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TxOutResult {
+    pub bestblock: String,
+    pub confirmations: usize,
+    pub value: f64,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: RawTxScriptPubKey,
+    pub coinbase: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TxOut {
+    // null when the output is spent or unknown
+    pub result: Option<TxOutResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestUtxo {
+    pub height: usize,
+    pub value: f64,
+    pub script_pub_key: RawTxScriptPubKey,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestUtxos {
+    pub chain_height: usize,
+    pub chaintip_hash: String,
+    pub bitmap: String,
+    pub utxos: Vec<RestUtxo>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RestBlockHash {
+    pub blockhash: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct UtxoStatus {
+    pub txid: String,
+    pub vout: u32,
+    pub unspent: bool,
+    pub confirmations: Option<usize>,
+    // BTC
+    pub value: Option<f64>,
+    pub pk_script: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "topic", rename_all = "snake_case")]
+pub enum WsTopic {
+    Blocks,
+    Tx { txid: String },
+    Address { address: String },
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum WsRequest {
+    Subscribe(WsTopic),
+    Unsubscribe(WsTopic),
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WsEvent {
+    Subscribed(WsTopic),
+    Unsubscribed(WsTopic),
+    Block {
+        hash: String,
+        height: Option<u64>,
+        tx_count: usize,
+    },
+    BlockDisconnected {
+        hash: String,
+    },
+    Tx {
+        txid: String,
+        confirmations: usize,
+    },
+    AddressTx {
+        address: String,
+        txid: String,
+        vout: u32,
+        // BTC
+        amount: f64,
+        confirmations: usize,
+    },
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WatchResponse {
+    pub id: String,
+    pub address: String,
+    pub script_pubkey: String,
+    pub callback_url: String,
+    pub confirmations: u32,
+    // only returned on creation
+    pub secret: Option<String>,
+    pub created_at: u64,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct IndexStatus {
+    pub enabled: bool,
+    pub height: Option<u64>,
+    pub hash: Option<String>,
+    pub addresses: usize,
+    // addresses registered after the tip whose older blocks are still scanned
+    pub backfilling: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct IndexHistoryEntry {
+    pub txid: String,
+    pub height: u64,
+    pub block_hash: String,
+    pub confirmations: u64,
+    // BTC
+    pub received: f64,
+    pub sent: f64,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WalletNames {
+    pub result: Option<Vec<String>>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WalletDirEntry {
+    pub name: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WalletDirResult {
+    pub wallets: Vec<WalletDirEntry>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WalletDir {
+    pub result: Option<WalletDirResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Wallets {
+    pub loaded: Vec<String>,
+    // wallets in the node wallet directory, loaded or not
+    pub available: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WalletLoadResult {
+    #[serde(default)]
+    pub name: String,
+    // older nodes report a single warning, newer ones a list
+    pub warning: Option<String>,
+    pub warnings: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WalletLoad {
+    pub result: Option<WalletLoadResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ListUnspentResult {
+    pub txid: String,
+    pub vout: u32,
+    pub address: Option<String>,
+    pub label: Option<String>,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: String,
+    // BTC
+    pub amount: f64,
+    pub confirmations: u32,
+    pub spendable: bool,
+    pub solvable: bool,
+    pub desc: Option<String>,
+    // set for p2wsh and p2sh-p2wsh outputs the wallet can solve
+    #[serde(rename = "witnessScript")]
+    pub witness_script: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ListUnspent {
+    pub result: Option<Vec<ListUnspentResult>>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WalletBalance {
+    pub trusted: f64,
+    pub untrusted_pending: f64,
+    pub immature: f64,
+    pub used: Option<f64>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WalletBalancesResult {
+    pub mine: WalletBalance,
+    pub watchonly: Option<WalletBalance>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WalletBalances {
+    pub result: Option<WalletBalancesResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct NewAddress {
+    pub result: Option<String>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WalletTransactionResult {
+    pub address: Option<String>,
+    pub category: String,
+    pub amount: f64,
+    pub fee: Option<f64>,
+    pub label: Option<String>,
+    pub vout: Option<u32>,
+    pub confirmations: i64,
+    pub blockhash: Option<String>,
+    pub blockheight: Option<u64>,
+    pub txid: String,
+    pub time: u64,
+    pub timereceived: u64,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WalletTransactions {
+    pub result: Option<Vec<WalletTransactionResult>>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DescriptorInfoResult {
+    pub descriptor: String,
+    pub checksum: String,
+    pub isrange: bool,
+    pub issolvable: bool,
+    pub hasprivatekeys: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DescriptorInfo {
+    pub result: Option<DescriptorInfoResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ImportDescriptorResult {
+    pub success: bool,
+    pub warnings: Option<Vec<String>>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ImportDescriptors {
+    pub result: Option<Vec<ImportDescriptorResult>>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WalletDescriptor {
+    pub desc: String,
+    pub timestamp: u64,
+    pub active: bool,
+    pub internal: Option<bool>,
+    pub range: Option<[u32; 2]>,
+    pub next: Option<u32>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ListDescriptorsResult {
+    pub wallet_name: String,
+    pub descriptors: Vec<WalletDescriptor>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ListDescriptors {
+    pub result: Option<ListDescriptorsResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DeriveAddresses {
+    pub result: Option<Vec<String>>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DescriptorBalance {
+    pub descriptor: String,
+    // BTC
+    pub balance: f64,
+    pub utxo_count: usize,
+    // derived addresses which were checked
+    pub address_count: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DerivedAddress {
+    pub index: u32,
+    pub address: String,
+    pub script_pubkey: String,
+    pub path: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct GeneratedMnemonic {
+    pub mnemonic: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct KeyAddresses {
+    pub p2pkh: String,
+    pub p2sh_p2wpkh: String,
+    pub p2wpkh: String,
+    pub p2tr: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DerivedKey {
+    pub master_fingerprint: String,
+    pub path: String,
+    pub xprv: String,
+    pub xpub: String,
+    pub wif: String,
+    pub public_key: String,
+    pub addresses: KeyAddresses,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageFormat {
+    // signmessage compatible, P2PKH only
+    Legacy,
+    // BIP322 simple, P2WPKH, P2SH-P2WPKH and P2TR
+    Bip322,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MessageSignatureResult {
+    pub address: String,
+    pub format: MessageFormat,
+    // base64
+    pub signature: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MessageVerification {
+    pub address: String,
+    pub format: MessageFormat,
+    pub valid: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct AddressInfo {
+    pub address: String,
+    // testnet and signet share the address encoding, both are reported as testnet
+    pub network: String,
+    pub address_type: Option<String>,
+    pub witness_version: Option<u8>,
+    pub witness_program: Option<String>,
+    pub script_pubkey: String,
+    pub script_asm: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ScriptInfo {
+    pub hex: String,
+    pub asm: String,
+    pub script_type: String,
+    // multisig only
+    pub required_signatures: Option<u8>,
+    // address of an output script
+    pub address: Option<String>,
+    // the script wrapped in p2sh and p2wsh
+    pub p2sh: Option<String>,
+    pub p2wsh: Option<String>,
+    pub is_standard: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum MultisigType {
+    P2wsh,
+    P2shP2wsh,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MultisigAddress {
+    pub address: String,
+    pub script_type: MultisigType,
+    pub required: u8,
+    // BIP67 order, as they appear in the witness script
+    pub public_keys: Vec<String>,
+    pub witness_script: String,
+    // p2sh-p2wsh only
+    pub redeem_script: Option<String>,
+    pub script_pubkey: String,
+    // without checksum, importable as watch-only
+    pub descriptor: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TaprootSigned {
+    pub hex: String,
+    // every input carries a script_sig or witness
+    pub complete: bool,
+}
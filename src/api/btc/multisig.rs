@@ -0,0 +1,203 @@
+use actix_web::{post, web, HttpResponse, Responder};
+use bitcoin::{
+    blockdata::{opcodes, script::Builder},
+    hashes::hex::ToHex,
+    Address, Network, PublicKey, Script,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::api::btc::{
+    handler::ErrorResponse,
+    hd::HdNetwork,
+    model::{MultisigAddress, MultisigType},
+};
+
+// keys encodable with OP_1..OP_16
+const MAX_MULTISIG_KEYS: usize = 16;
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateMultisigRequest {
+    #[validate(
+        required,
+        range(min = 1, max = 16, message = "must be between 1 and 16")
+    )]
+    pub required: Option<u8>,
+
+    #[validate(
+        required,
+        length(min = 1, max = 16, message = "must be between 1 and 16 keys")
+    )]
+    pub public_keys: Option<Vec<String>>,
+
+    // p2wsh by default
+    pub script_type: Option<MultisigType>,
+    pub network: Option<HdNetwork>,
+}
+
+// BIP67 sorted m of n witness script
+pub fn sorted_multisig(required: u8, keys: &[PublicKey]) -> Result<Script, String> {
+    if keys.is_empty() || keys.len() > MAX_MULTISIG_KEYS {
+        return Err(format!(
+            "between 1 and {} keys are required",
+            MAX_MULTISIG_KEYS
+        ));
+    }
+    if required == 0 || required as usize > keys.len() {
+        return Err(format!(
+            "required signatures must be between 1 and {}",
+            keys.len()
+        ));
+    }
+    if keys.iter().any(|key| !key.compressed) {
+        return Err("segwit multisig requires compressed public keys".to_string());
+    }
+
+    let mut keys = keys.to_vec();
+    keys.sort_by_key(|key| key.to_bytes());
+    if keys.windows(2).any(|pair| pair[0] == pair[1]) {
+        return Err("duplicate public key".to_string());
+    }
+
+    let builder = keys
+        .iter()
+        .fold(Builder::new().push_int(required as i64), |builder, key| {
+            builder.push_key(key)
+        });
+
+    Ok(builder
+        .push_int(keys.len() as i64)
+        .push_opcode(opcodes::all::OP_CHECKMULTISIG)
+        .into_script())
+}
+
+pub fn multisig_address(
+    required: u8,
+    keys: &[PublicKey],
+    script_type: MultisigType,
+    network: Network,
+) -> Result<MultisigAddress, String> {
+    let witness_script = sorted_multisig(required, keys)?;
+
+    let sortedmulti = format!(
+        "sortedmulti({},{})",
+        required,
+        keys.iter()
+            .map(|key| key.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+
+    let (address, redeem_script, descriptor) = match script_type {
+        MultisigType::P2wsh => (
+            Address::p2wsh(&witness_script, network),
+            None,
+            format!("wsh({})", sortedmulti),
+        ),
+        MultisigType::P2shP2wsh => (
+            Address::p2shwsh(&witness_script, network),
+            Some(witness_script.to_v0_p2wsh()),
+            format!("sh(wsh({}))", sortedmulti),
+        ),
+    };
+
+    let mut public_keys = keys.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+    public_keys.sort();
+
+    Ok(MultisigAddress {
+        address: address.to_string(),
+        script_type,
+        required,
+        public_keys,
+        witness_script: witness_script.to_hex(),
+        redeem_script: redeem_script.map(|script| script.to_hex()),
+        script_pubkey: address.script_pubkey().to_hex(),
+        descriptor,
+    })
+}
+
+#[post("/multisig")]
+pub(super) async fn create_multisig(json: web::Json<CreateMultisigRequest>) -> impl Responder {
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    let keys = match json
+        .public_keys
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|key| key.parse::<PublicKey>())
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(keys) => keys,
+        Err(_) => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                message: "invalid public key".to_string(),
+            })
+        }
+    };
+
+    match multisig_address(
+        json.required.unwrap(),
+        &keys,
+        json.script_type.unwrap_or(MultisigType::P2wsh),
+        json.network.map(Network::from).unwrap_or(Network::Bitcoin),
+    ) {
+        Ok(multisig) => HttpResponse::Ok().json(multisig),
+        Err(message) => HttpResponse::BadRequest().json(ErrorResponse { message }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(keys: &[&str]) -> Vec<PublicKey> {
+        keys.iter().map(|key| key.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn bip67_sorted_script() {
+        // BIP67 test vector 1
+        let keys = keys(&[
+            "02ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f8",
+            "02fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f",
+        ]);
+        let script = sorted_multisig(2, &keys).unwrap();
+
+        assert_eq!(script.to_hex(), "522102fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f2102ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f852ae");
+        assert_eq!(
+            Address::p2sh(&script, Network::Bitcoin)
+                .unwrap()
+                .to_string(),
+            "39bgKC7RFbpoCRbtD5KEdkYKtNyhpsNa3Z"
+        );
+
+        let multisig =
+            multisig_address(2, &keys, MultisigType::P2shP2wsh, Network::Bitcoin).unwrap();
+        assert!(multisig.address.starts_with('3'));
+        assert_eq!(
+            multisig.redeem_script.unwrap(),
+            script.to_v0_p2wsh().to_hex()
+        );
+        assert!(multisig
+            .descriptor
+            .starts_with("sh(wsh(sortedmulti(2,02ff12"));
+    }
+
+    #[test]
+    fn invalid_multisig() {
+        let keys = keys(&[
+            "02ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f8",
+            "02ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f8",
+        ]);
+
+        assert!(sorted_multisig(3, &keys).is_err());
+        assert!(sorted_multisig(0, &keys).is_err());
+        assert_eq!(
+            sorted_multisig(1, &keys).unwrap_err(),
+            "duplicate public key"
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use actix_web::{web, HttpResponse};
+use async_trait::async_trait;
+use base64::encode;
+use bitcoin::{consensus, hashes::hex::FromHex, Txid};
+
+use crate::{
+    api::{
+        btc::{
+            handler::{
+                self, blockchain_info, broadcast_tx, build_psbt, estimate_fees,
+                sign_raw_transaction, transaction_status, ErrorResponse, ToAddresses,
+            },
+            model::{BlockchainInfoResult, Broadcast, FeeEstimateMode, SignTxResult, TxStatus},
+        },
+        chain::{
+            self,
+            handler::BuildTxRequest,
+            model::{ChainFee, ChainStatus, UnsignedTx},
+            ChainNode,
+        },
+    },
+    config::BitcoinRpcConfig,
+    request::RequestClient,
+};
+
+pub struct BitcoinNode {
+    rq_client: RequestClient,
+    btc_rpc_cfg: BitcoinRpcConfig,
+}
+
+// generic status of a bitcoin core node
+pub(super) fn chain_status(info: &BlockchainInfoResult) -> ChainStatus {
+    ChainStatus {
+        chain: "bitcoin".to_string(),
+        network: info.chain.clone(),
+        blocks: info.blocks,
+        headers: info.headers,
+        best_block_hash: info.bestblockhash.clone(),
+        verification_progress: info.verificationprogress,
+        initial_block_download: info.initialblockdownload,
+        mweb: None,
+    }
+}
+
+impl BitcoinNode {
+    pub fn new(rq_client: RequestClient, btc_rpc_cfg: BitcoinRpcConfig) -> BitcoinNode {
+        BitcoinNode {
+            rq_client,
+            btc_rpc_cfg,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl ChainNode for BitcoinNode {
+    fn name(&self) -> &'static str {
+        "bitcoin"
+    }
+
+    // the bitcoin routes first, they win over the generic ones on shared paths
+    fn configure(&self, cfg: &mut web::ServiceConfig) {
+        cfg.app_data(web::Data::new(self.btc_rpc_cfg.clone()));
+        handler::init(cfg);
+        chain::handler::init(cfg);
+    }
+
+    async fn status(&self) -> Result<ChainStatus, HttpResponse> {
+        let info = blockchain_info(&self.rq_client, &self.btc_rpc_cfg).await?;
+
+        Ok(chain_status(&info))
+    }
+
+    async fn estimate_fee(&self, target: u16) -> Result<ChainFee, HttpResponse> {
+        let mut estimates = estimate_fees(
+            &self.rq_client,
+            &self.btc_rpc_cfg,
+            &[target],
+            &[FeeEstimateMode::Conservative],
+        )
+        .await?;
+
+        Ok(ChainFee {
+            target,
+            fee_rate: estimates.remove(0).btc_per_kvb,
+        })
+    }
+
+    async fn build_tx(&self, request: &BuildTxRequest) -> Result<UnsignedTx, HttpResponse> {
+        let to = request
+            .to
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|output| ToAddresses {
+                to_address: output.address.clone(),
+                amount: output.amount,
+            })
+            .collect::<Vec<_>>();
+
+        let (psbt, fee_rate) = build_psbt(
+            &self.rq_client,
+            &self.btc_rpc_cfg,
+            request.utxos.as_ref().unwrap(),
+            &to,
+            request.change_address.as_ref().unwrap(),
+        )
+        .await?;
+
+        Ok(UnsignedTx {
+            hex: consensus::encode::serialize_hex(&psbt.unsigned_tx),
+            psbt: Some(encode(consensus::encode::serialize(&psbt))),
+            fee_rate,
+        })
+    }
+
+    async fn sign_tx(
+        &self,
+        raw_tx: &str,
+        private_keys: &[String],
+    ) -> Result<SignTxResult, HttpResponse> {
+        sign_raw_transaction(&self.rq_client, &self.btc_rpc_cfg, raw_tx, private_keys).await
+    }
+
+    async fn broadcast(&self, signed_tx: &str) -> Result<Broadcast, HttpResponse> {
+        Ok(broadcast_tx(&self.rq_client, &self.btc_rpc_cfg, signed_tx).await)
+    }
+
+    async fn tx_status(&self, txid: &str) -> Result<TxStatus, HttpResponse> {
+        if Txid::from_hex(txid).is_err() {
+            return Err(HttpResponse::BadRequest().json(ErrorResponse {
+                message: "invalid txid".to_string(),
+            }));
+        }
+
+        transaction_status(&self.rq_client, &self.btc_rpc_cfg, txid.to_string()).await
+    }
+}
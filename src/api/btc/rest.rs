@@ -0,0 +1,170 @@
+use actix_web::web::Bytes;
+use log::error;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    api::btc::model::{
+        BlockHeaderResult, BlockResult, BlockchainInfoResult, RawFormat, RestBlockHash, RestUtxos,
+    },
+    request::RequestClient,
+};
+
+// bitcoin core rest interface (rest=1), read only and without rpc credentials
+
+pub enum RestError {
+    NotFound(String),
+    BadRequest(String),
+    Decode,
+    Request,
+}
+
+async fn get(rq_client: &RequestClient, url: &str) -> Result<reqwest::Response, RestError> {
+    let response = match rq_client.get(url, None).await {
+        Ok(response) => response,
+        Err(err) => {
+            error!("request error: {}", err);
+            return Err(RestError::Request);
+        }
+    };
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    // rest errors are plain text
+    let message = response.text().await.unwrap_or_default().trim().to_string();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        Err(RestError::NotFound(message))
+    } else {
+        Err(RestError::BadRequest(message))
+    }
+}
+
+async fn get_json<T: DeserializeOwned>(
+    rq_client: &RequestClient,
+    rest_url: &str,
+    path: &str,
+) -> Result<T, RestError> {
+    let url = format!("{}/rest/{}.json", rest_url.trim_end_matches('/'), path);
+
+    get(rq_client, &url)
+        .await?
+        .json::<T>()
+        .await
+        .map_err(|err| {
+            error!("failed to decode rest {} response, {}", path, err);
+            RestError::Decode
+        })
+}
+
+async fn get_raw(
+    rq_client: &RequestClient,
+    rest_url: &str,
+    path: &str,
+    format: RawFormat,
+) -> Result<Bytes, RestError> {
+    let extension = match format {
+        RawFormat::Hex => "hex",
+        RawFormat::Bin => "bin",
+    };
+    let url = format!(
+        "{}/rest/{}.{}",
+        rest_url.trim_end_matches('/'),
+        path,
+        extension
+    );
+
+    let body = get(rq_client, &url).await?.bytes().await.map_err(|err| {
+        error!("failed to read rest {} response, {}", path, err);
+        RestError::Decode
+    })?;
+
+    // hex responses end with a newline
+    match format {
+        RawFormat::Hex => Ok(Bytes::copy_from_slice(body.trim_ascii_end())),
+        RawFormat::Bin => Ok(body),
+    }
+}
+
+pub async fn chain_info(
+    rq_client: &RequestClient,
+    rest_url: &str,
+) -> Result<BlockchainInfoResult, RestError> {
+    get_json(rq_client, rest_url, "chaininfo").await
+}
+
+pub async fn block(
+    rq_client: &RequestClient,
+    rest_url: &str,
+    hash: &str,
+    with_txs: bool,
+) -> Result<BlockResult, RestError> {
+    if with_txs {
+        get_json(rq_client, rest_url, &format!("block/{}", hash)).await
+    } else {
+        get_json(rq_client, rest_url, &format!("block/notxdetails/{}", hash)).await
+    }
+}
+
+pub async fn block_raw(
+    rq_client: &RequestClient,
+    rest_url: &str,
+    hash: &str,
+    format: RawFormat,
+) -> Result<Bytes, RestError> {
+    get_raw(rq_client, rest_url, &format!("block/{}", hash), format).await
+}
+
+pub async fn block_header(
+    rq_client: &RequestClient,
+    rest_url: &str,
+    hash: &str,
+) -> Result<BlockHeaderResult, RestError> {
+    let mut headers: Vec<BlockHeaderResult> =
+        get_json(rq_client, rest_url, &format!("headers/1/{}", hash)).await?;
+
+    // unknown hashes give an empty list instead of 404
+    match headers.pop() {
+        Some(header) => Ok(header),
+        None => Err(RestError::NotFound(format!("{} not found", hash))),
+    }
+}
+
+pub async fn block_hash_by_height(
+    rq_client: &RequestClient,
+    rest_url: &str,
+    height: usize,
+) -> Result<String, RestError> {
+    let hash: RestBlockHash = get_json(
+        rq_client,
+        rest_url,
+        &format!("blockhashbyheight/{}", height),
+    )
+    .await?;
+
+    Ok(hash.blockhash)
+}
+
+pub async fn tx_raw(
+    rq_client: &RequestClient,
+    rest_url: &str,
+    txid: &str,
+    format: RawFormat,
+) -> Result<Bytes, RestError> {
+    get_raw(rq_client, rest_url, &format!("tx/{}", txid), format).await
+}
+
+pub async fn utxos(
+    rq_client: &RequestClient,
+    rest_url: &str,
+    txid: &str,
+    vout: u32,
+) -> Result<RestUtxos, RestError> {
+    get_json(
+        rq_client,
+        rest_url,
+        &format!("getutxos/checkmempool/{}-{}", txid, vout),
+    )
+    .await
+}
@@ -0,0 +1,255 @@
+use actix_web::{http, post, web, HttpRequest, HttpResponse, Responder};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use validator::Validate;
+
+use crate::{
+    api::btc::handler::{rpc_headers, ErrorResponse},
+    config::{BitcoinRpcConfig, RpcApiKey},
+    request::RequestClient,
+};
+
+const API_KEY_HEADER: &str = "X-API-Key";
+const AUDIT_TARGET: &str = "rpc_audit";
+const ANY_METHOD: &str = "*";
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct RpcRequest {
+    #[validate(
+        required,
+        length(min = 1, max = 64, message = "must be between 1 and 64")
+    )]
+    pub method: Option<String>,
+
+    // positional array or named object, empty by default
+    pub params: Option<Value>,
+}
+
+fn listed(list: &[String], method: &str) -> bool {
+    list.iter().any(|m| m == ANY_METHOD || m == method)
+}
+
+// denylist first, then the global allowlist, then the key's own methods
+pub fn check_method(
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    api_key: &RpcApiKey,
+    method: &str,
+) -> Result<(), &'static str> {
+    if btc_rpc_cfg.bitcoin_rpc_denylist.iter().any(|m| m == method) {
+        Err("method is denied")
+    } else if !listed(&btc_rpc_cfg.bitcoin_rpc_allowlist, method) {
+        Err("method is not allowed")
+    } else if !listed(&api_key.methods, method) {
+        Err("method is not permitted for this api key")
+    } else {
+        Ok(())
+    }
+}
+
+// compares every byte so the time doesn't depend on where the keys differ
+fn keys_match(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+fn find_api_key<'a>(btc_rpc_cfg: &'a BitcoinRpcConfig, key: &str) -> Option<&'a RpcApiKey> {
+    btc_rpc_cfg
+        .bitcoin_rpc_api_keys
+        .iter()
+        .find(|api_key| keys_match(&api_key.key, key))
+}
+
+// tries the nodes in order, moving on only when one can't be reached
+async fn forward(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    payload: &Value,
+) -> Result<(String, Value), HttpResponse> {
+    let headers = rpc_headers(btc_rpc_cfg);
+
+    for url in &btc_rpc_cfg.bitcoin_rpc_urls {
+        match rq_client.post(url, Some(&headers), payload).await {
+            Ok(response) if response.status() == http::StatusCode::UNAUTHORIZED => {
+                return Err(HttpResponse::Unauthorized().json(ErrorResponse {
+                    message: "Unauthorized".to_string(),
+                }))
+            }
+            Ok(response) => {
+                return match response.json::<Value>().await {
+                    Ok(body) => Ok((url.clone(), body)),
+                    Err(err) => {
+                        error!("failed to decode {} response, {}", payload["method"], err);
+                        Err(HttpResponse::BadRequest().json(ErrorResponse {
+                            message: "failed to decode response".to_string(),
+                        }))
+                    }
+                }
+            }
+            Err(err) => warn!("request error: {}, trying next node", err),
+        }
+    }
+
+    Err(HttpResponse::RequestTimeout().json(ErrorResponse {
+        message: "failed to do request, something wrong with rpc node".to_string(),
+    }))
+}
+
+#[post("/rpc")]
+pub(super) async fn rpc_passthrough(
+    req: HttpRequest,
+    json: web::Json<RpcRequest>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    if btc_rpc_cfg.bitcoin_rpc_api_keys.is_empty() {
+        return HttpResponse::Forbidden().json(ErrorResponse {
+            message: "rpc passthrough is disabled".to_string(),
+        });
+    }
+
+    // the socket peer, forwarding headers are set by the caller and can't be trusted
+    let client = req
+        .peer_addr()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let api_key = match req
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|key| key.to_str().ok())
+        .and_then(|key| find_api_key(&btc_rpc_cfg, key))
+    {
+        Some(api_key) => api_key,
+        None => {
+            warn!(target: AUDIT_TARGET, "client={} rejected: invalid api key", client);
+            return HttpResponse::Unauthorized().json(ErrorResponse {
+                message: "invalid api key".to_string(),
+            });
+        }
+    };
+
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    let method = json.method.as_ref().unwrap().to_lowercase();
+    let params = match &json.params {
+        None => json!([]),
+        Some(params) if params.is_array() || params.is_object() => params.clone(),
+        Some(_) => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                message: "params must be an array or an object".to_string(),
+            })
+        }
+    };
+
+    if let Err(message) = check_method(&btc_rpc_cfg, api_key, &method) {
+        warn!(
+            target: AUDIT_TARGET,
+            "client={} key={} method={} rejected: {}", client, api_key.name, method, message
+        );
+        return HttpResponse::Forbidden().json(ErrorResponse {
+            message: message.to_string(),
+        });
+    }
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": method, "params": params});
+
+    match forward(&rq_client, &btc_rpc_cfg, &payload).await {
+        Ok((node, body)) => {
+            let failed = !body["error"].is_null();
+            info!(
+                target: AUDIT_TARGET,
+                "client={} key={} method={} node={} {}",
+                client,
+                api_key.name,
+                method,
+                node,
+                if failed { "rpc error" } else { "ok" }
+            );
+
+            if failed {
+                HttpResponse::BadRequest().json(body)
+            } else {
+                HttpResponse::Ok().json(body)
+            }
+        }
+        Err(resp) => {
+            info!(
+                target: AUDIT_TARGET,
+                "client={} key={} method={} failed: {}",
+                client,
+                api_key.name,
+                method,
+                resp.status()
+            );
+            resp
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_key(methods: &[&str]) -> RpcApiKey {
+        RpcApiKey {
+            name: "ops".to_string(),
+            key: "secret".to_string(),
+            methods: methods.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn method_permissions() {
+        let cfg = BitcoinRpcConfig {
+            bitcoin_rpc_allowlist: vec!["getblockcount".to_string(), "stop".to_string()],
+            bitcoin_rpc_denylist: vec!["stop".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check_method(&cfg, &api_key(&["*"]), "getblockcount"),
+            Ok(())
+        );
+        assert_eq!(
+            check_method(&cfg, &api_key(&["*"]), "stop"),
+            Err("method is denied")
+        );
+        assert_eq!(
+            check_method(&cfg, &api_key(&["*"]), "getblock"),
+            Err("method is not allowed")
+        );
+        assert_eq!(
+            check_method(&cfg, &api_key(&["getblock"]), "getblockcount"),
+            Err("method is not permitted for this api key")
+        );
+
+        let open = BitcoinRpcConfig {
+            bitcoin_rpc_allowlist: vec!["*".to_string()],
+            bitcoin_rpc_denylist: vec!["stop".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(check_method(&open, &api_key(&["*"]), "getblock"), Ok(()));
+        assert_eq!(
+            check_method(&open, &api_key(&["*"]), "stop"),
+            Err("method is denied")
+        );
+    }
+
+    #[test]
+    fn api_key_lookup() {
+        let cfg = BitcoinRpcConfig {
+            bitcoin_rpc_api_keys: vec![api_key(&["*"])],
+            ..Default::default()
+        };
+
+        assert_eq!(find_api_key(&cfg, "secret").unwrap().name, "ops");
+        assert!(find_api_key(&cfg, "secreT").is_none());
+        assert!(find_api_key(&cfg, "secret2").is_none());
+    }
+}
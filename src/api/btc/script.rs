@@ -0,0 +1,228 @@
+use std::str::FromStr;
+
+use actix_web::{get, post, web, HttpResponse, Responder};
+use bitcoin::{
+    blockdata::{opcodes, script::Instruction},
+    hashes::hex::{FromHex, ToHex},
+    util::address::Payload,
+    Address, Network, Script,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::api::btc::{
+    handler::ErrorResponse,
+    hd::HdNetwork,
+    model::{AddressInfo, ScriptInfo},
+};
+
+// OP_RETURN outputs relayed by default nodes
+const MAX_NULLDATA_SIZE: usize = 83;
+const MAX_BARE_MULTISIG_KEYS: usize = 3;
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct DecodeScriptRequest {
+    #[validate(
+        required,
+        length(min = 1, max = 20000, message = "must be between 1 and 20000")
+    )]
+    pub hex: Option<String>,
+
+    // network of the reported addresses, bitcoin by default
+    pub network: Option<HdNetwork>,
+}
+
+fn pushnum(op: opcodes::All) -> Option<u8> {
+    let code = op.to_u8();
+    let one = opcodes::all::OP_PUSHNUM_1.to_u8();
+    let sixteen = opcodes::all::OP_PUSHNUM_16.to_u8();
+    (one..=sixteen).contains(&code).then(|| code - one + 1)
+}
+
+// m of n for OP_m <keys> OP_n OP_CHECKMULTISIG
+fn multisig(script: &Script) -> Option<(u8, usize)> {
+    let instructions = script.instructions().collect::<Result<Vec<_>, _>>().ok()?;
+
+    let (first, rest) = instructions.split_first()?;
+    let (last, rest) = rest.split_last()?;
+    let (count, keys) = rest.split_last()?;
+
+    let required = match first {
+        Instruction::Op(op) => pushnum(*op)?,
+        _ => return None,
+    };
+    let total = match count {
+        Instruction::Op(op) => pushnum(*op)?,
+        _ => return None,
+    };
+    if *last != Instruction::Op(opcodes::all::OP_CHECKMULTISIG)
+        || keys.len() != total as usize
+        || required > total
+    {
+        return None;
+    }
+
+    let valid_keys = keys.iter().all(|key| match key {
+        Instruction::PushBytes(key) => bitcoin::PublicKey::from_slice(key).is_ok(),
+        _ => false,
+    });
+
+    valid_keys.then_some((required, keys.len()))
+}
+
+fn is_nulldata(script: &Script) -> bool {
+    script.is_op_return()
+        && script.len() <= MAX_NULLDATA_SIZE
+        && script
+            .instructions()
+            .skip(1)
+            .all(|instruction| matches!(instruction, Ok(Instruction::PushBytes(_))))
+}
+
+pub fn decode_script(script: &Script, network: Network) -> ScriptInfo {
+    let multisig = multisig(script);
+
+    let (script_type, is_standard) = if script.is_p2pkh() {
+        ("p2pkh", true)
+    } else if script.is_p2sh() {
+        ("p2sh", true)
+    } else if script.is_v0_p2wpkh() {
+        ("p2wpkh", true)
+    } else if script.is_v0_p2wsh() {
+        ("p2wsh", true)
+    } else if script.is_v1_p2tr() {
+        ("p2tr", true)
+    } else if script.is_witness_program() {
+        ("witness_unknown", true)
+    } else if script.is_p2pk() {
+        ("pubkey", true)
+    } else if let Some((_, keys)) = multisig {
+        ("multisig", keys <= MAX_BARE_MULTISIG_KEYS)
+    } else if is_nulldata(script) {
+        ("nulldata", true)
+    } else if script.is_op_return() {
+        ("nulldata", false)
+    } else {
+        ("nonstandard", false)
+    };
+
+    let wrappable = !script.is_p2sh() && !script.is_witness_program() && !script.is_op_return();
+
+    ScriptInfo {
+        hex: script.to_hex(),
+        asm: script.asm(),
+        script_type: script_type.to_string(),
+        required_signatures: multisig.map(|(required, _)| required),
+        address: Address::from_script(script, network)
+            .ok()
+            .map(|address| address.to_string()),
+        p2sh: wrappable
+            .then(|| Address::p2sh(script, network).ok())
+            .flatten()
+            .map(|address| address.to_string()),
+        p2wsh: wrappable.then(|| Address::p2wsh(script, network).to_string()),
+        is_standard,
+    }
+}
+
+pub fn address_info(address: &Address) -> AddressInfo {
+    let script_pubkey = address.script_pubkey();
+
+    let (witness_version, witness_program) = match &address.payload {
+        Payload::WitnessProgram { version, program } => {
+            (Some(version.to_num()), Some(program.to_hex()))
+        }
+        _ => (None, None),
+    };
+
+    AddressInfo {
+        address: address.to_string(),
+        network: address.network.to_string(),
+        address_type: address.address_type().map(|t| t.to_string()),
+        witness_version,
+        witness_program,
+        script_pubkey: script_pubkey.to_hex(),
+        script_asm: script_pubkey.asm(),
+    }
+}
+
+#[get("/address/{address}/info")]
+pub(super) async fn address_details(path: web::Path<String>) -> impl Responder {
+    match Address::from_str(&path.into_inner()) {
+        Ok(address) => HttpResponse::Ok().json(address_info(&address)),
+        Err(_) => HttpResponse::BadRequest().json(ErrorResponse {
+            message: "invalid Bitcoin address".to_string(),
+        }),
+    }
+}
+
+#[post("/script/decode")]
+pub(super) async fn script_decode(json: web::Json<DecodeScriptRequest>) -> impl Responder {
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    let script = match Vec::<u8>::from_hex(json.hex.as_ref().unwrap()) {
+        Ok(bytes) => Script::from(bytes),
+        Err(_) => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                message: "invalid script hex".to_string(),
+            })
+        }
+    };
+
+    let network = json.network.map(Network::from).unwrap_or(Network::Bitcoin);
+    HttpResponse::Ok().json(decode_script(&script, network))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_multisig() {
+        // 1 of 2 bare multisig
+        let script = Script::from(Vec::from_hex("512102c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee52102f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f952ae").unwrap());
+        let info = decode_script(&script, Network::Bitcoin);
+
+        assert_eq!(info.script_type, "multisig");
+        assert_eq!(info.required_signatures, Some(1));
+        assert!(info.is_standard);
+        assert!(info.address.is_none());
+        assert!(info.p2sh.unwrap().starts_with('3'));
+        assert!(info.p2wsh.unwrap().starts_with("bc1q"));
+    }
+
+    #[test]
+    fn decode_output_scripts() {
+        let script =
+            Script::from(Vec::from_hex("0014c0cebcd6c3d3ca8c75dc5ec62ebe55330ef910e2").unwrap());
+        let info = decode_script(&script, Network::Bitcoin);
+        assert_eq!(info.script_type, "p2wpkh");
+        assert_eq!(
+            info.address.as_deref(),
+            Some("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu")
+        );
+        assert!(info.p2sh.is_none());
+
+        let info = decode_script(
+            &Script::from(vec![0x6a, 0x02, 0xbe, 0xef]),
+            Network::Bitcoin,
+        );
+        assert_eq!(info.script_type, "nulldata");
+        assert!(info.is_standard);
+    }
+
+    #[test]
+    fn taproot_address_info() {
+        let address =
+            Address::from_str("bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr")
+                .unwrap();
+        let info = address_info(&address);
+
+        assert_eq!(info.network, "bitcoin");
+        assert_eq!(info.address_type.as_deref(), Some("p2tr"));
+        assert_eq!(info.witness_version, Some(1));
+        assert_eq!(info.witness_program.unwrap().len(), 64);
+    }
+}
@@ -1,20 +1,214 @@
 use std::str::FromStr;
 
 use bitcoin::{
-    consensus::encode, hashes::hex::FromHex, locktime::PackedLockTime, psbt::Psbt, Address, Amount,
-    OutPoint, Script, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
+    blockdata::{opcodes::all as opcodes, script::Instruction},
+    consensus::encode::VarInt,
+    hashes::hex::FromHex,
+    locktime::PackedLockTime,
+    psbt::Psbt,
+    util::taproot::ControlBlock,
+    Address, Amount, Network, OutPoint, Script, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
 };
 
-use super::handler::{ToAddresses, Utxo};
+use super::{
+    handler::{ToAddresses, Utxo},
+    model::{FeeHistogramBucket, MempoolEntryResult},
+};
+
+// der signature with sighash byte, low r
+const ECDSA_SIG_SIZE: usize = 72;
+// default sighash, no sighash byte
+const SCHNORR_SIG_SIZE: usize = 64;
+const PUBKEY_SIZE: usize = 33;
+// outpoint and sequence
+const TX_IN_BASE_SIZE: usize = 40;
+// version and lock time
+const TX_BASE_SIZE: usize = 8;
+
+// sat/vB lower bounds of the mempool histogram buckets
+const FEE_HISTOGRAM_BOUNDS: [f64; 22] = [
+    1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 8.0, 10.0, 12.0, 15.0, 20.0, 30.0, 40.0, 50.0, 70.0, 100.0,
+    150.0, 200.0, 300.0, 500.0, 700.0, 1000.0,
+];
+
+// network of a getblockchaininfo chain name
+pub fn chain_network(chain: &str) -> Option<Network> {
+    match chain {
+        "main" => Some(Network::Bitcoin),
+        "test" | "testnet4" => Some(Network::Testnet),
+        "signet" => Some(Network::Signet),
+        "regtest" => Some(Network::Regtest),
+        _ => None,
+    }
+}
 
+// addresses of another network than the node's are rejected, the tx is built locally
 pub fn create_transaction(
-    utxos: &Vec<Utxo>,
-    to: &Vec<ToAddresses>,
+    utxos: &[Utxo],
+    to: &[ToAddresses],
     change: &str,
     fee_rate: f64,
-) -> Result<String, &'static str> {
+    network: Network,
+) -> Result<Psbt, &'static str> {
+    let mut outputs = Vec::new();
+    for t in to {
+        let script_pubkey = match Address::from_str(t.to_address.as_ref().unwrap()) {
+            Ok(address) if address.is_valid_for_network(network) => address.script_pubkey(),
+            Ok(_) => return Err("to_address belongs to another network"),
+            Err(_err) => return Err("failed to decode script_pubkey"),
+        };
+
+        outputs.push((script_pubkey, t.amount.unwrap()));
+    }
+
+    let change_address_script = match Address::from_str(change) {
+        Ok(address) if address.is_valid_for_network(network) => address.script_pubkey(),
+        Ok(_) => return Err("change address belongs to another network"),
+        Err(_err) => return Err("failed to decode change address to script"),
+    };
+
+    build_transaction(utxos, &outputs, change_address_script, size_fee(fee_rate))
+}
+
+// fee in coins of a tx of the estimated size at a coins/kvB rate
+pub fn size_fee(fee_rate: f64) -> impl Fn(usize) -> f64 {
+    move |tx_byte_size| Amount::from_sat((fee_rate * 1.0e5 * tx_byte_size as f64) as u64).to_btc()
+}
+
+fn var_int_size(n: usize) -> usize {
+    VarInt(n as u64).len()
+}
+
+// serialized size of a witness with items of the given sizes
+fn witness_size(items: &[usize]) -> usize {
+    var_int_size(items.len())
+        + items
+            .iter()
+            .map(|item| var_int_size(*item) + item)
+            .sum::<usize>()
+}
+
+// signatures a script asks for, m of a m-of-n checkmultisig, one per checksig otherwise
+fn script_signatures(script: &Script) -> usize {
+    let ops = script
+        .instructions()
+        .filter_map(|instruction| match instruction {
+            Ok(Instruction::Op(op)) => Some(op),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if ops.last() == Some(&opcodes::OP_CHECKMULTISIG) {
+        let m = ops[0].to_u8();
+        if (opcodes::OP_PUSHNUM_1.to_u8()..=opcodes::OP_PUSHNUM_16.to_u8()).contains(&m) {
+            return (m - opcodes::OP_PUSHNUM_1.to_u8() + 1) as usize;
+        }
+    }
+
+    ops.iter()
+        .filter(|op| {
+            [
+                opcodes::OP_CHECKSIG,
+                opcodes::OP_CHECKSIGVERIFY,
+                opcodes::OP_CHECKSIGADD,
+            ]
+            .contains(op)
+        })
+        .count()
+        .max(1)
+}
+
+fn decode_script(hex: &str, err: &'static str) -> Result<Script, &'static str> {
+    Vec::<u8>::from_hex(hex).map(Script::from).map_err(|_| err)
+}
+
+// (non-witness, witness) bytes of the signed input, by the type of the spent output
+fn input_size(utxo: &Utxo) -> Result<(usize, usize), &'static str> {
+    let pk_script = decode_script(
+        utxo.pk_script.as_ref().unwrap(),
+        "failed to decode pk_script",
+    )?;
+    let base = |script_sig: usize| TX_IN_BASE_SIZE + var_int_size(script_sig) + script_sig;
+
+    if let Some(witness_script) = &utxo.witness_script {
+        let witness_script = decode_script(witness_script, "failed to decode witness_script")?;
+        // checkmultisig pops an extra empty item
+        let mut items = vec![0; 1];
+        items.extend(vec![ECDSA_SIG_SIZE; script_signatures(&witness_script)]);
+        items.push(witness_script.len());
+
+        // p2sh-p2wsh pushes the 34 byte p2wsh program
+        let script_sig = if pk_script.is_p2sh() { 35 } else { 0 };
+        return Ok((base(script_sig), witness_size(&items)));
+    }
+
+    if !pk_script.is_v1_p2tr() && (utxo.leaf_script.is_some() || utxo.control_block.is_some()) {
+        return Err("leaf_script and control_block are for p2tr outputs only");
+    }
+
+    if pk_script.is_v1_p2tr() {
+        return match (&utxo.leaf_script, &utxo.control_block) {
+            (None, None) => Ok((base(0), witness_size(&[SCHNORR_SIG_SIZE]))),
+            (Some(leaf_script), Some(control_block)) => {
+                let leaf_script = decode_script(leaf_script, "failed to decode leaf_script")?;
+                let control_block = Vec::<u8>::from_hex(control_block)
+                    .map_err(|_| "failed to decode control_block")?;
+
+                let mut items = vec![SCHNORR_SIG_SIZE; script_signatures(&leaf_script)];
+                items.push(leaf_script.len());
+                items.push(control_block.len());
+                Ok((base(0), witness_size(&items)))
+            }
+            _ => Err("leaf_script and control_block must be given together"),
+        };
+    }
+
+    if pk_script.is_v0_p2wpkh() {
+        Ok((base(0), witness_size(&[ECDSA_SIG_SIZE, PUBKEY_SIZE])))
+    } else if pk_script.is_p2sh() {
+        // without a witness script taken as p2sh-p2wpkh, pushing the 22 byte program
+        Ok((base(23), witness_size(&[ECDSA_SIG_SIZE, PUBKEY_SIZE])))
+    } else {
+        // p2pkh, signature and pubkey pushes
+        Ok((base(2 + ECDSA_SIG_SIZE + PUBKEY_SIZE), 0))
+    }
+}
+
+// virtual size of the signed tx, witness bytes count a quarter
+fn estimate_vsize(utxos: &[Utxo], outputs: &[TxOut]) -> Result<usize, &'static str> {
+    let mut base = TX_BASE_SIZE + var_int_size(utxos.len()) + var_int_size(outputs.len());
+    let mut witness = 0;
+    let mut legacy_inputs = 0;
+    for utxo in utxos {
+        let (input_base, input_witness) = input_size(utxo)?;
+        base += input_base;
+        witness += input_witness;
+        if input_witness == 0 {
+            legacy_inputs += 1;
+        }
+    }
+    for output in outputs {
+        base += 8 + var_int_size(output.script_pubkey.len()) + output.script_pubkey.len();
+    }
+
+    if witness > 0 {
+        // segwit marker and flag, legacy inputs get an empty witness
+        witness += 2 + legacy_inputs;
+    }
+
+    Ok((base * 4 + witness).div_ceil(4))
+}
+
+// chain agnostic part of create_transaction, outputs are (script_pubkey, amount) pairs
+// and fee maps the estimated tx size to the total fee in coins
+pub fn build_transaction<F: Fn(usize) -> f64>(
+    utxos: &[Utxo],
+    outputs: &[(Script, f64)],
+    change_script: Script,
+    fee: F,
+) -> Result<Psbt, &'static str> {
     let tx_in_amount: f64 = utxos.iter().map(|utxo| utxo.amount.unwrap()).sum();
-    let tx_out_amount: f64 = to.iter().map(|t| t.amount.unwrap()).sum();
+    let tx_out_amount: f64 = outputs.iter().map(|(_, amount)| amount).sum();
 
     let mut txs_in: Vec<TxIn> = Vec::new();
     for utxo in utxos {
@@ -35,20 +229,15 @@ pub fn create_transaction(
     }
 
     let mut txs_out: Vec<TxOut> = Vec::new();
-    for t in to {
-        let script_pubkey = match Address::from_str(t.to_address.as_ref().unwrap()) {
-            Ok(key) => key.script_pubkey(),
-            Err(_err) => return Err("failed to decode script_pubkey"),
-        };
-
-        let value = match Amount::from_btc(t.amount.unwrap()) {
+    for (script_pubkey, amount) in outputs {
+        let value = match Amount::from_btc(*amount) {
             Ok(value) => value.to_sat(),
             Err(_err) => return Err("failed decode to address amount to sat"),
         };
 
         txs_out.push(TxOut {
             value,
-            script_pubkey,
+            script_pubkey: script_pubkey.clone(),
         })
     }
 
@@ -62,20 +251,13 @@ pub fn create_transaction(
         Ok(amount) => amount.to_sat(),
         Err(_err) => return Err("failed parse change amount"),
     };
-    let change_address_script = match Address::from_str(change) {
-        Ok(address) => address.script_pubkey(),
-        Err(_err) => return Err("failed to decode change address to script"),
-    };
-
     // push change address and amount
     txs_out.push(TxOut {
         value: change_amount,
-        script_pubkey: change_address_script,
+        script_pubkey: change_script,
     });
 
-    let tx_byte_size = txs_in.len() * 180 + txs_out.len() * 34 + 10 + txs_in.len();
-    // convert fee to satoshis to kb and mul
-    let total_fee = Amount::from_sat((fee_rate * 1.0e5 * tx_byte_size as f64) as u64).to_btc();
+    let total_fee = fee(estimate_vsize(utxos, &txs_out)?);
     let total_fee_sat = match Amount::from_btc(total_fee) {
         Ok(value) => value.to_sat(),
         Err(_err) => return Err("failed to convert total_fee to sat"),
@@ -92,10 +274,16 @@ pub fn create_transaction(
                 Err(_err) => return Err("failed to convert fee_for_each_tx to sat"),
             };
 
-            tx.value = tx.value - converted_fee_for_each_tx;
+            tx.value = match tx.value.checked_sub(converted_fee_for_each_tx) {
+                Some(value) => value,
+                None => return Err("output amount too low to pay the fee"),
+            };
         }
     } else {
-        txs_out[0].value = txs_out[0].value - total_fee_sat;
+        txs_out[0].value = match txs_out[0].value.checked_sub(total_fee_sat) {
+            Some(value) => value,
+            None => return Err("output amount too low to pay the fee"),
+        };
     }
 
     let tx = Transaction {
@@ -105,10 +293,316 @@ pub fn create_transaction(
         output: txs_out,
     };
 
-    let psbt = match Psbt::from_unsigned_tx(tx) {
+    let mut psbt = match Psbt::from_unsigned_tx(tx) {
         Ok(psbt) => psbt,
         Err(_err) => return Err("failed decode transaction to unsigned"),
     };
 
-    Ok(encode::serialize_hex(&psbt.extract_tx()))
+    for (input, utxo) in psbt.inputs.iter_mut().zip(utxos) {
+        let script_pubkey = match Vec::<u8>::from_hex(utxo.pk_script.as_ref().unwrap()) {
+            Ok(script) => Script::from(script),
+            Err(_err) => return Err("failed to decode pk_script"),
+        };
+
+        if let Some(witness_script) = &utxo.witness_script {
+            let witness_script = match Vec::<u8>::from_hex(witness_script) {
+                Ok(script) => Script::from(script),
+                Err(_err) => return Err("failed to decode witness_script"),
+            };
+
+            let p2wsh = witness_script.to_v0_p2wsh();
+            if script_pubkey == p2wsh.to_p2sh() {
+                input.redeem_script = Some(p2wsh);
+            } else if script_pubkey != p2wsh {
+                return Err("witness_script does not match pk_script");
+            }
+            input.witness_script = Some(witness_script);
+        } else if !script_pubkey.is_witness_program() {
+            // legacy inputs need the full previous tx, left to the signer
+            continue;
+        }
+
+        if let (Some(leaf_script), Some(control_block)) = (&utxo.leaf_script, &utxo.control_block) {
+            let control_block = match Vec::<u8>::from_hex(control_block)
+                .ok()
+                .and_then(|bytes| ControlBlock::from_slice(&bytes).ok())
+            {
+                Some(control_block) => control_block,
+                None => return Err("invalid control_block"),
+            };
+            let leaf_script = decode_script(leaf_script, "failed to decode leaf_script")?;
+            let leaf_version = control_block.leaf_version;
+            input
+                .tap_scripts
+                .insert(control_block, (leaf_script, leaf_version));
+        }
+
+        let value = match Amount::from_btc(utxo.amount.unwrap()) {
+            Ok(value) => value.to_sat(),
+            Err(_err) => return Err("failed decode utxo amount to sat"),
+        };
+        input.witness_utxo = Some(TxOut {
+            value,
+            script_pubkey,
+        });
+    }
+
+    Ok(psbt)
+}
+
+pub fn fee_histogram<'a, I>(entries: I) -> Vec<FeeHistogramBucket>
+where
+    I: IntoIterator<Item = &'a MempoolEntryResult>,
+{
+    let mut buckets: Vec<FeeHistogramBucket> = FEE_HISTOGRAM_BOUNDS
+        .iter()
+        .enumerate()
+        .map(|(i, from)| FeeHistogramBucket {
+            from_fee_rate: *from,
+            to_fee_rate: FEE_HISTOGRAM_BOUNDS.get(i + 1).copied(),
+            count: 0,
+            vsize: 0,
+            total_fees: 0.0,
+        })
+        .collect();
+    let mut bucket_fees = vec![0u64; buckets.len()];
+
+    for entry in entries {
+        if entry.vsize == 0 {
+            continue;
+        }
+
+        let fee_sat = Amount::from_btc(entry.fees.base).map_or(0, |a| a.to_sat());
+        let fee_rate = fee_sat as f64 / entry.vsize as f64;
+
+        // txs below 1 sat/vB (possible with a lowered minrelaytxfee) go to the first bucket
+        let idx = FEE_HISTOGRAM_BOUNDS
+            .iter()
+            .rposition(|from| fee_rate >= *from)
+            .unwrap_or(0);
+
+        buckets[idx].count += 1;
+        buckets[idx].vsize += entry.vsize;
+        bucket_fees[idx] += fee_sat;
+    }
+
+    for (bucket, fee) in buckets.iter_mut().zip(bucket_fees) {
+        bucket.total_fees = Amount::from_sat(fee).to_btc();
+    }
+
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::btc::model::MempoolEntryFees;
+    use bitcoin::{hashes::hex::ToHex, Network};
+
+    fn entry(vsize: usize, fee: f64) -> MempoolEntryResult {
+        MempoolEntryResult {
+            vsize,
+            weight: vsize * 4,
+            time: 0,
+            height: 0,
+            descendantcount: 1,
+            descendantsize: vsize,
+            ancestorcount: 1,
+            ancestorsize: vsize,
+            wtxid: String::new(),
+            fees: MempoolEntryFees {
+                base: fee,
+                modified: fee,
+                ancestor: fee,
+                descendant: fee,
+            },
+            depends: vec![],
+            spentby: vec![],
+            bip125_replaceable: false,
+            unbroadcast: None,
+        }
+    }
+
+    #[test]
+    fn fee_histogram_buckets() {
+        let entries = vec![
+            entry(100, 0.00000100),
+            entry(200, 0.00000250),
+            entry(100, 0.00000500),
+            entry(100, 0.00200000),
+        ];
+
+        let histogram = fee_histogram(&entries);
+
+        assert_eq!(histogram.len(), FEE_HISTOGRAM_BOUNDS.len());
+        assert_eq!(histogram[0].count, 2);
+        assert_eq!(histogram[0].vsize, 300);
+        assert_eq!(histogram[0].total_fees, 0.0000035);
+        assert_eq!(histogram[4].count, 1);
+        assert_eq!(histogram[4].from_fee_rate, 5.0);
+        assert_eq!(histogram[21].count, 1);
+        assert_eq!(histogram[21].to_fee_rate, None);
+    }
+
+    #[test]
+    fn estimated_vsizes() {
+        let utxo =
+            |pk_script: &str, witness_script: Option<&str>, leaf: Option<(&str, &str)>| Utxo {
+                tx_id: Some("00".repeat(32)),
+                vout: Some(0),
+                amount: Some(0.001),
+                pk_script: Some(pk_script.to_string()),
+                witness_script: witness_script.map(String::from),
+                leaf_script: leaf.map(|(script, _)| script.to_string()),
+                control_block: leaf.map(|(_, control_block)| control_block.to_string()),
+            };
+        let output = |pk_script: &str| TxOut {
+            value: 0,
+            script_pubkey: Script::from(Vec::from_hex(pk_script).unwrap()),
+        };
+        let p2pkh = "76a914751e76e8199196d454941c45d1b3a323f1433bd688ac";
+        let p2wpkh = "0014751e76e8199196d454941c45d1b3a323f1433bd6";
+        let p2tr = format!("5120{}", "79".repeat(32));
+
+        // the usual 192 and 141 vB
+        assert_eq!(
+            estimate_vsize(&[utxo(p2pkh, None, None)], &[output(p2pkh)]).unwrap(),
+            192
+        );
+        assert_eq!(
+            estimate_vsize(
+                &[utxo(p2wpkh, None, None)],
+                &[output(p2wpkh), output(p2wpkh)]
+            )
+            .unwrap(),
+            141
+        );
+
+        // key path, 57.5 vB input
+        assert_eq!(input_size(&utxo(&p2tr, None, None)).unwrap(), (41, 66));
+        assert_eq!(
+            estimate_vsize(&[utxo(&p2tr, None, None)], &[output(&p2tr)]).unwrap(),
+            111
+        );
+
+        // script path, one signature, the leaf and a depth 0 control block
+        let leaf_script = format!("20{}ac", "79".repeat(32));
+        let control_block = format!("c0{}", "79".repeat(32));
+        assert_eq!(
+            input_size(&utxo(&p2tr, None, Some((&leaf_script, &control_block)))).unwrap(),
+            (41, 1 + 65 + 35 + 34)
+        );
+
+        // 2 of 2, empty item, two signatures and the 71 byte script
+        let witness_script = "522102fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f2102ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f852ae";
+        let p2wsh = Script::from(Vec::from_hex(witness_script).unwrap()).to_v0_p2wsh();
+        assert_eq!(
+            input_size(&utxo(&p2wsh.to_hex(), Some(witness_script), None)).unwrap(),
+            (41, 1 + 1 + 2 * 73 + 72)
+        );
+        assert_eq!(
+            input_size(&utxo(&p2wsh.to_p2sh().to_hex(), Some(witness_script), None))
+                .unwrap()
+                .0,
+            76
+        );
+
+        // one legacy input among segwit ones gets an empty witness
+        assert_eq!(
+            estimate_vsize(
+                &[utxo(p2pkh, None, None), utxo(p2wpkh, None, None)],
+                &[output(p2wpkh)]
+            )
+            .unwrap(),
+            (4 * (10 + 148 + 41 + 31) + 2 + 1 + 108_usize).div_ceil(4)
+        );
+
+        assert!(input_size(&utxo(p2wpkh, None, Some((&leaf_script, &control_block)))).is_err());
+    }
+
+    #[test]
+    fn multisig_psbt_inputs() {
+        // BIP67 vector 1, 2 of 2
+        let witness_script = "522102fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f2102ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f852ae";
+        let p2wsh = Script::from(Vec::from_hex(witness_script).unwrap()).to_v0_p2wsh();
+        let utxo = |vout, pk_script: &Script| Utxo {
+            tx_id: Some(
+                "989d301c546841d0ac5c8354c7d78079e3603b089682d1639b2ee1c1a8010c6a".to_string(),
+            ),
+            vout: Some(vout),
+            amount: Some(0.001),
+            pk_script: Some(pk_script.to_hex()),
+            witness_script: Some(witness_script.to_string()),
+            leaf_script: None,
+            control_block: None,
+        };
+        let to = vec![ToAddresses {
+            to_address: Some("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx".to_string()),
+            amount: Some(0.0005),
+        }];
+        let change = Address::p2wsh(
+            &Script::from(Vec::from_hex(witness_script).unwrap()),
+            Network::Testnet,
+        );
+
+        let psbt = create_transaction(
+            &[utxo(0, &p2wsh), utxo(1, &p2wsh.to_p2sh())],
+            &to,
+            &change.to_string(),
+            0.00001,
+            Network::Testnet,
+        )
+        .unwrap();
+
+        assert_eq!(
+            psbt.inputs[0].witness_script.as_ref().unwrap().to_hex(),
+            witness_script
+        );
+        assert!(psbt.inputs[0].redeem_script.is_none());
+        assert_eq!(psbt.inputs[0].witness_utxo.as_ref().unwrap().value, 100_000);
+        assert_eq!(psbt.inputs[1].redeem_script.as_ref(), Some(&p2wsh));
+        assert_eq!(
+            psbt.inputs[1].witness_utxo.as_ref().unwrap().script_pubkey,
+            p2wsh.to_p2sh()
+        );
+
+        let mismatch = create_transaction(
+            &[utxo(0, &change.script_pubkey().to_p2sh().to_p2sh())],
+            &to,
+            &change.to_string(),
+            0.00001,
+            Network::Testnet,
+        );
+        assert_eq!(
+            mismatch.unwrap_err(),
+            "witness_script does not match pk_script"
+        );
+
+        // mainnet recipient on a testnet node
+        let mainnet = create_transaction(
+            &[utxo(0, &p2wsh)],
+            &[ToAddresses {
+                to_address: Some("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4".to_string()),
+                amount: Some(0.0005),
+            }],
+            &change.to_string(),
+            0.00001,
+            Network::Testnet,
+        );
+        assert_eq!(
+            mainnet.unwrap_err(),
+            "to_address belongs to another network"
+        );
+        let mainnet_change = create_transaction(
+            &[utxo(0, &p2wsh)],
+            &to,
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            0.00001,
+            Network::Testnet,
+        );
+        assert_eq!(
+            mainnet_change.unwrap_err(),
+            "change address belongs to another network"
+        );
+    }
 }
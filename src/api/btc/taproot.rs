@@ -0,0 +1,444 @@
+use std::str::FromStr;
+
+use actix_web::{post, web, HttpResponse, Responder};
+use bitcoin::{
+    blockdata::script::Instruction,
+    consensus::encode,
+    hashes::{hex::FromHex, Hash},
+    secp256k1::{KeyPair, Message, Secp256k1, Signing, Verification, XOnlyPublicKey},
+    util::{
+        schnorr::{SchnorrSig, TapTweak},
+        sighash::{Prevouts, SighashCache},
+        taproot::{ControlBlock, TapBranchHash, TapLeafHash},
+    },
+    Amount, PrivateKey, SchnorrSighashType, Script, Transaction, TxOut, Witness,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::api::btc::{handler::ErrorResponse, model::TaprootSigned};
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TaprootPrevout {
+    #[validate(required, range(min = 0.0, message = "cannot be negative"))]
+    pub amount: Option<f64>,
+
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub pk_script: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TaprootInput {
+    #[validate(required)]
+    pub index: Option<usize>,
+
+    // WIF, the internal key for key path spends
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub private_key: Option<String>,
+
+    // key path only, hex root of the script tree the output commits to
+    #[validate(length(equal = 64, message = "must be 32 bytes hex"))]
+    pub merkle_root: Option<String>,
+
+    // script path, both hex
+    #[validate(length(min = 1, message = "cannot be empty"))]
+    pub leaf_script: Option<String>,
+    #[validate(length(min = 66, message = "must be at least 33 bytes hex"))]
+    pub control_block: Option<String>,
+
+    // SIGHASH_DEFAULT by default
+    pub sighash_type: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TaprootSignRequest {
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub raw_tx: Option<String>,
+
+    // outputs spent by every input of the tx, in input order
+    #[validate]
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub prevouts: Option<Vec<TaprootPrevout>>,
+
+    #[validate]
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub inputs: Option<Vec<TaprootInput>>,
+}
+
+pub enum SpendPath {
+    Key(Option<TapBranchHash>),
+    Script(Script, ControlBlock),
+}
+
+pub struct TaprootSpend {
+    pub index: usize,
+    pub keypair: KeyPair,
+    pub path: SpendPath,
+    pub sighash_type: SchnorrSighashType,
+}
+
+fn hex_field(value: &str, name: &str) -> Result<Vec<u8>, String> {
+    Vec::from_hex(value).map_err(|_| format!("invalid {} hex", name))
+}
+
+impl TaprootInput {
+    fn spend<C: Signing>(&self, secp: &Secp256k1<C>) -> Result<TaprootSpend, String> {
+        let private_key = PrivateKey::from_wif(self.private_key.as_ref().unwrap())
+            .map_err(|_| "invalid WIF private key".to_string())?;
+
+        let path = match (&self.leaf_script, &self.control_block) {
+            (None, None) => {
+                let merkle_root = match &self.merkle_root {
+                    Some(root) => Some(
+                        TapBranchHash::from_slice(&hex_field(root, "merkle_root")?)
+                            .map_err(|err| err.to_string())?,
+                    ),
+                    None => None,
+                };
+                SpendPath::Key(merkle_root)
+            }
+            (Some(leaf_script), Some(control_block)) => {
+                let leaf_script = Script::from(hex_field(leaf_script, "leaf_script")?);
+                let control_block =
+                    ControlBlock::from_slice(&hex_field(control_block, "control_block")?)
+                        .map_err(|err| err.to_string())?;
+                SpendPath::Script(leaf_script, control_block)
+            }
+            _ => return Err("leaf_script and control_block must be given together".to_string()),
+        };
+
+        let sighash_type = match &self.sighash_type {
+            Some(sighash_type) => {
+                SchnorrSighashType::from_str(sighash_type).map_err(|err| err.to_string())?
+            }
+            None => SchnorrSighashType::Default,
+        };
+
+        Ok(TaprootSpend {
+            index: self.index.unwrap(),
+            keypair: KeyPair::from_secret_key(secp, &private_key.inner),
+            path,
+            sighash_type,
+        })
+    }
+}
+
+fn output_key(prevout: &TxOut) -> Result<XOnlyPublicKey, String> {
+    if !prevout.script_pubkey.is_v1_p2tr() {
+        return Err("prevout is not a P2TR output".to_string());
+    }
+    XOnlyPublicKey::from_slice(&prevout.script_pubkey[2..]).map_err(|err| err.to_string())
+}
+
+// BIP341 signatures over all prevouts, other inputs are left untouched
+pub fn sign_taproot<C: Signing + Verification>(
+    secp: &Secp256k1<C>,
+    tx: &mut Transaction,
+    prevouts: &[TxOut],
+    spends: &[TaprootSpend],
+) -> Result<(), String> {
+    if prevouts.len() != tx.input.len() {
+        return Err("a prevout is required for every input".to_string());
+    }
+
+    let mut witnesses = Vec::with_capacity(spends.len());
+    let mut cache = SighashCache::new(&*tx);
+
+    for spend in spends {
+        let prevout = prevouts
+            .get(spend.index)
+            .ok_or_else(|| format!("input {} does not exist", spend.index))?;
+        let output_key = output_key(prevout)?;
+        let (internal_key, _) = XOnlyPublicKey::from_keypair(&spend.keypair);
+
+        let witness = match &spend.path {
+            SpendPath::Key(merkle_root) => {
+                let tweaked = spend.keypair.tap_tweak(secp, *merkle_root).to_inner();
+                if XOnlyPublicKey::from_keypair(&tweaked).0 != output_key {
+                    return Err(format!(
+                        "private key does not match the output of input {}",
+                        spend.index
+                    ));
+                }
+
+                let sighash = cache
+                    .taproot_key_spend_signature_hash(
+                        spend.index,
+                        &Prevouts::All(prevouts),
+                        spend.sighash_type,
+                    )
+                    .map_err(|err| err.to_string())?;
+                let sig = schnorr_sig(secp, &sighash, &tweaked, spend.sighash_type)?;
+                Witness::from_vec(vec![sig.to_vec()])
+            }
+            SpendPath::Script(leaf_script, control_block) => {
+                if !control_block.verify_taproot_commitment(secp, output_key, leaf_script) {
+                    return Err(format!(
+                        "control block does not commit to the leaf script of input {}",
+                        spend.index
+                    ));
+                }
+                let key = internal_key.serialize();
+                if !leaf_script
+                    .instructions()
+                    .any(|instruction| instruction == Ok(Instruction::PushBytes(&key)))
+                {
+                    return Err(format!(
+                        "private key is not used by the leaf script of input {}",
+                        spend.index
+                    ));
+                }
+
+                let leaf_hash = TapLeafHash::from_script(leaf_script, control_block.leaf_version);
+                let sighash = cache
+                    .taproot_script_spend_signature_hash(
+                        spend.index,
+                        &Prevouts::All(prevouts),
+                        leaf_hash,
+                        spend.sighash_type,
+                    )
+                    .map_err(|err| err.to_string())?;
+                let sig = schnorr_sig(secp, &sighash, &spend.keypair, spend.sighash_type)?;
+                Witness::from_vec(vec![
+                    sig.to_vec(),
+                    leaf_script.to_bytes(),
+                    control_block.serialize(),
+                ])
+            }
+        };
+
+        witnesses.push((spend.index, witness));
+    }
+
+    for (index, witness) in witnesses {
+        tx.input[index].witness = witness;
+    }
+
+    Ok(())
+}
+
+fn schnorr_sig<C: Signing>(
+    secp: &Secp256k1<C>,
+    sighash: &[u8],
+    keypair: &KeyPair,
+    hash_ty: SchnorrSighashType,
+) -> Result<SchnorrSig, String> {
+    let msg = Message::from_slice(sighash).map_err(|err| err.to_string())?;
+    Ok(SchnorrSig {
+        sig: secp.sign_schnorr_with_aux_rand(&msg, keypair, &rand::random()),
+        hash_ty,
+    })
+}
+
+#[post("/taproot/sign")]
+pub(super) async fn taproot_sign(json: web::Json<TaprootSignRequest>) -> impl Responder {
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    let secp = Secp256k1::new();
+    match sign_request(&secp, &json) {
+        Ok(tx) => HttpResponse::Ok().json(TaprootSigned {
+            hex: encode::serialize_hex(&tx),
+            complete: tx
+                .input
+                .iter()
+                .all(|input| !input.witness.is_empty() || !input.script_sig.is_empty()),
+        }),
+        Err(message) => HttpResponse::BadRequest().json(ErrorResponse { message }),
+    }
+}
+
+fn sign_request<C: Signing + Verification>(
+    secp: &Secp256k1<C>,
+    json: &TaprootSignRequest,
+) -> Result<Transaction, String> {
+    let mut tx: Transaction =
+        encode::deserialize(&hex_field(json.raw_tx.as_ref().unwrap(), "raw_tx")?)
+            .map_err(|_| "failed to decode raw_tx".to_string())?;
+
+    let prevouts = json
+        .prevouts
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|prevout| {
+            Ok(TxOut {
+                value: Amount::from_btc(prevout.amount.unwrap())
+                    .map_err(|err| err.to_string())?
+                    .to_sat(),
+                script_pubkey: Script::from(hex_field(
+                    prevout.pk_script.as_ref().unwrap(),
+                    "pk_script",
+                )?),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let spends = json
+        .inputs
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|input| input.spend(secp))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    sign_taproot(secp, &mut tx, &prevouts, &spends)?;
+    Ok(tx)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{
+        blockdata::{opcodes, script::Builder},
+        util::taproot::{LeafVersion, TaprootBuilder},
+        OutPoint, PackedLockTime, Sequence, TxIn,
+    };
+
+    use super::*;
+
+    fn spending_tx(inputs: usize) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: (0..inputs as u32)
+                .map(|vout| TxIn {
+                    previous_output: OutPoint::new(bitcoin::Txid::all_zeros(), vout),
+                    script_sig: Script::new(),
+                    sequence: Sequence::MAX,
+                    witness: Witness::default(),
+                })
+                .collect(),
+            output: vec![TxOut {
+                value: 90_000,
+                script_pubkey: Script::new_op_return(&[]),
+            }],
+        }
+    }
+
+    fn keypair(secp: &Secp256k1<bitcoin::secp256k1::All>, byte: u8) -> KeyPair {
+        KeyPair::from_seckey_slice(secp, &[byte; 32]).unwrap()
+    }
+
+    #[test]
+    fn key_path_spend() {
+        let secp = Secp256k1::new();
+        let keypair = keypair(&secp, 1);
+        let (internal_key, _) = XOnlyPublicKey::from_keypair(&keypair);
+        let prevouts = vec![
+            TxOut {
+                value: 50_000,
+                script_pubkey: Script::new_v1_p2tr(&secp, internal_key, None),
+            },
+            TxOut {
+                value: 50_000,
+                script_pubkey: Script::new_op_return(&[]),
+            },
+        ];
+
+        let mut tx = spending_tx(2);
+        let spend = |index| TaprootSpend {
+            index,
+            keypair,
+            path: SpendPath::Key(None),
+            sighash_type: SchnorrSighashType::Default,
+        };
+        sign_taproot(&secp, &mut tx, &prevouts, &[spend(0)]).unwrap();
+
+        let sig = SchnorrSig::from_slice(&tx.input[0].witness.to_vec()[0]).unwrap();
+        assert_eq!(sig.hash_ty, SchnorrSighashType::Default);
+        let sighash = SighashCache::new(&tx)
+            .taproot_key_spend_signature_hash(
+                0,
+                &Prevouts::All(&prevouts),
+                SchnorrSighashType::Default,
+            )
+            .unwrap();
+        let output_key = output_key(&prevouts[0]).unwrap();
+        secp.verify_schnorr(
+            &sig.sig,
+            &Message::from_slice(&sighash).unwrap(),
+            &output_key,
+        )
+        .unwrap();
+        assert!(tx.input[1].witness.is_empty());
+
+        assert!(sign_taproot(&secp, &mut tx, &prevouts, &[spend(1)]).is_err());
+        assert!(sign_taproot(&secp, &mut tx, &prevouts[..1], &[spend(0)]).is_err());
+    }
+
+    #[test]
+    fn script_path_spend() {
+        let secp = Secp256k1::new();
+        let internal = keypair(&secp, 1);
+        let leaf_key = keypair(&secp, 2);
+        let (internal_key, _) = XOnlyPublicKey::from_keypair(&internal);
+        let (leaf_pubkey, _) = XOnlyPublicKey::from_keypair(&leaf_key);
+
+        let leaf_script = Builder::new()
+            .push_slice(&leaf_pubkey.serialize())
+            .push_opcode(opcodes::all::OP_CHECKSIG)
+            .into_script();
+        let other_script = Builder::new().push_opcode(opcodes::OP_TRUE).into_script();
+        let spend_info = TaprootBuilder::new()
+            .add_leaf(1, leaf_script.clone())
+            .unwrap()
+            .add_leaf(1, other_script.clone())
+            .unwrap()
+            .finalize(&secp, internal_key)
+            .unwrap();
+        let prevouts = vec![TxOut {
+            value: 100_000,
+            script_pubkey: Script::new_v1_p2tr_tweaked(spend_info.output_key()),
+        }];
+        let control_block = spend_info
+            .control_block(&(leaf_script.clone(), LeafVersion::TapScript))
+            .unwrap();
+
+        let mut tx = spending_tx(1);
+        let spend = |keypair, script: &Script| TaprootSpend {
+            index: 0,
+            keypair,
+            path: SpendPath::Script(script.clone(), control_block.clone()),
+            sighash_type: SchnorrSighashType::All,
+        };
+        sign_taproot(&secp, &mut tx, &prevouts, &[spend(leaf_key, &leaf_script)]).unwrap();
+
+        let witness = tx.input[0].witness.to_vec();
+        assert_eq!(witness.len(), 3);
+        assert_eq!(witness[1], leaf_script.to_bytes());
+        assert_eq!(witness[2], control_block.serialize());
+
+        let sig = SchnorrSig::from_slice(&witness[0]).unwrap();
+        let sighash = SighashCache::new(&tx)
+            .taproot_script_spend_signature_hash(
+                0,
+                &Prevouts::All(&prevouts),
+                TapLeafHash::from_script(&leaf_script, LeafVersion::TapScript),
+                SchnorrSighashType::All,
+            )
+            .unwrap();
+        secp.verify_schnorr(
+            &sig.sig,
+            &Message::from_slice(&sighash).unwrap(),
+            &leaf_pubkey,
+        )
+        .unwrap();
+
+        // wrong key for the leaf, and a leaf the control block doesn't prove
+        assert!(sign_taproot(&secp, &mut tx, &prevouts, &[spend(internal, &leaf_script)]).is_err());
+        assert!(
+            sign_taproot(&secp, &mut tx, &prevouts, &[spend(leaf_key, &other_script)]).is_err()
+        );
+
+        // the output also stays spendable by the internal key with the merkle root
+        let key_spend = TaprootSpend {
+            index: 0,
+            keypair: internal,
+            path: SpendPath::Key(spend_info.merkle_root()),
+            sighash_type: SchnorrSighashType::Default,
+        };
+        sign_taproot(&secp, &mut tx, &prevouts, &[key_spend]).unwrap();
+        assert_eq!(tx.input[0].witness.len(), 1);
+    }
+}
@@ -0,0 +1,371 @@
+use actix_web::{get, post, web, HttpResponse, Responder};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use validator::Validate;
+
+use crate::{
+    api::btc::{
+        handler::{empty_result_response, rpc_call_url, rpc_error_response, ErrorResponse, Utxo},
+        model::{
+            ListUnspent, NewAddress, RPCError, WalletBalances, WalletDir, WalletLoad,
+            WalletLoadResult, WalletNames, WalletTransactions, Wallets,
+        },
+    },
+    config::BitcoinRpcConfig,
+    request::RequestClient,
+};
+
+// bitcoin core error code for a wallet which is not loaded or does not exist
+const RPC_WALLET_NOT_FOUND: isize = -18;
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateWalletRequest {
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub name: Option<String>,
+
+    pub disable_private_keys: Option<bool>,
+    pub blank: Option<bool>,
+    pub passphrase: Option<String>,
+    pub descriptors: Option<bool>,
+    pub load_on_startup: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AddressType {
+    Legacy,
+    P2shSegwit,
+    Bech32,
+    Bech32m,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewAddressRequest {
+    pub label: Option<String>,
+    pub address_type: Option<AddressType>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnspentQuery {
+    pub min_conf: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TransactionsQuery {
+    #[validate(range(min = 1, max = 1000, message = "must be between 1 and 1000"))]
+    pub count: Option<usize>,
+
+    pub skip: Option<usize>,
+}
+
+// node endpoint of a wallet, the name is percent encoded as a single path segment
+pub(super) fn wallet_url(btc_rpc_cfg: &BitcoinRpcConfig, name: &str) -> Option<String> {
+    let mut url = Url::parse(&btc_rpc_cfg.bitcoin_rpc_url_one).ok()?;
+    url.path_segments_mut()
+        .ok()?
+        .pop_if_empty()
+        .push("wallet")
+        .push(name);
+    Some(url.to_string())
+}
+
+pub(super) fn wallet_error_response(err: RPCError) -> HttpResponse {
+    if err.code == RPC_WALLET_NOT_FOUND {
+        HttpResponse::NotFound().json(ErrorResponse {
+            message: err.message,
+        })
+    } else {
+        rpc_error_response(err)
+    }
+}
+
+pub(super) fn invalid_url_response() -> HttpResponse {
+    HttpResponse::InternalServerError().json(ErrorResponse {
+        message: "invalid bitcoin rpc url".to_string(),
+    })
+}
+
+#[get("/wallets")]
+pub(super) async fn list_wallets(
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let url = &btc_rpc_cfg.bitcoin_rpc_url_one;
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "listwallets", "params": []});
+    let loaded = match rpc_call_url::<WalletNames>(&rq_client, &btc_rpc_cfg, url, &payload).await {
+        Ok(WalletNames {
+            result: Some(loaded),
+            ..
+        }) => loaded,
+        Ok(WalletNames {
+            error: Some(err), ..
+        }) => return rpc_error_response(err),
+        Ok(_) => return empty_result_response(),
+        Err(resp) => return resp,
+    };
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "listwalletdir", "params": []});
+    match rpc_call_url::<WalletDir>(&rq_client, &btc_rpc_cfg, url, &payload).await {
+        Ok(WalletDir {
+            result: Some(dir), ..
+        }) => HttpResponse::Ok().json(Wallets {
+            loaded,
+            available: dir.wallets.into_iter().map(|w| w.name).collect(),
+        }),
+        Ok(WalletDir {
+            error: Some(err), ..
+        }) => rpc_error_response(err),
+        Ok(_) => empty_result_response(),
+        Err(resp) => resp,
+    }
+}
+
+#[post("/wallets")]
+pub(super) async fn create_wallet(
+    json: web::Json<CreateWalletRequest>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "createwallet", "params": [
+        json.name,
+        json.disable_private_keys.unwrap_or(false),
+        json.blank.unwrap_or(false),
+        json.passphrase.clone().unwrap_or_default(),
+        false,
+        json.descriptors.unwrap_or(true),
+        json.load_on_startup,
+    ]});
+
+    load_response(&rq_client, &btc_rpc_cfg, &payload, true).await
+}
+
+#[post("/wallets/{name}/load")]
+pub(super) async fn load_wallet(
+    path: web::Path<String>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let payload =
+        json!({ "jsonrpc": "2.0",  "method": "loadwallet", "params": [path.into_inner()]});
+
+    load_response(&rq_client, &btc_rpc_cfg, &payload, false).await
+}
+
+#[post("/wallets/{name}/unload")]
+pub(super) async fn unload_wallet(
+    path: web::Path<String>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let name = path.into_inner();
+    let payload = json!({ "jsonrpc": "2.0",  "method": "unloadwallet", "params": [name]});
+
+    match rpc_call_url::<WalletLoad>(
+        &rq_client,
+        &btc_rpc_cfg,
+        &btc_rpc_cfg.bitcoin_rpc_url_one,
+        &payload,
+    )
+    .await
+    {
+        // unloadwallet only reports warnings
+        Ok(WalletLoad {
+            error: Some(err), ..
+        }) => wallet_error_response(err),
+        Ok(WalletLoad { result, .. }) => {
+            let mut result = result.unwrap_or(WalletLoadResult {
+                name: String::new(),
+                warning: None,
+                warnings: None,
+            });
+            result.name = name;
+            HttpResponse::Ok().json(result)
+        }
+        Err(resp) => resp,
+    }
+}
+
+async fn load_response(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    payload: &serde_json::Value,
+    created: bool,
+) -> HttpResponse {
+    match rpc_call_url::<WalletLoad>(
+        rq_client,
+        btc_rpc_cfg,
+        &btc_rpc_cfg.bitcoin_rpc_url_one,
+        payload,
+    )
+    .await
+    {
+        Ok(WalletLoad {
+            result: Some(result),
+            ..
+        }) if created => HttpResponse::Created().json(result),
+        Ok(WalletLoad {
+            result: Some(result),
+            ..
+        }) => HttpResponse::Ok().json(result),
+        Ok(WalletLoad {
+            error: Some(err), ..
+        }) => wallet_error_response(err),
+        Ok(_) => empty_result_response(),
+        Err(resp) => resp,
+    }
+}
+
+#[get("/wallets/{name}/utxos")]
+pub(super) async fn wallet_utxos(
+    path: web::Path<String>,
+    query: web::Query<UnspentQuery>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let url = match wallet_url(&btc_rpc_cfg, &path.into_inner()) {
+        Some(url) => url,
+        None => return invalid_url_response(),
+    };
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "listunspent", "params": [query.min_conf.unwrap_or(1)]});
+
+    match rpc_call_url::<ListUnspent>(&rq_client, &btc_rpc_cfg, &url, &payload).await {
+        Ok(ListUnspent {
+            result: Some(unspents),
+            ..
+        }) => HttpResponse::Ok().json(
+            unspents
+                .into_iter()
+                .map(|unspent| Utxo {
+                    tx_id: Some(unspent.txid),
+                    vout: Some(unspent.vout),
+                    amount: Some(unspent.amount),
+                    pk_script: Some(unspent.script_pub_key),
+                    witness_script: unspent.witness_script,
+                    leaf_script: None,
+                    control_block: None,
+                })
+                .collect::<Vec<_>>(),
+        ),
+        Ok(ListUnspent {
+            error: Some(err), ..
+        }) => wallet_error_response(err),
+        Ok(_) => empty_result_response(),
+        Err(resp) => resp,
+    }
+}
+
+#[get("/wallets/{name}/balances")]
+pub(super) async fn wallet_balances(
+    path: web::Path<String>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let url = match wallet_url(&btc_rpc_cfg, &path.into_inner()) {
+        Some(url) => url,
+        None => return invalid_url_response(),
+    };
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "getbalances", "params": []});
+
+    match rpc_call_url::<WalletBalances>(&rq_client, &btc_rpc_cfg, &url, &payload).await {
+        Ok(WalletBalances {
+            result: Some(balances),
+            ..
+        }) => HttpResponse::Ok().json(balances),
+        Ok(WalletBalances {
+            error: Some(err), ..
+        }) => wallet_error_response(err),
+        Ok(_) => empty_result_response(),
+        Err(resp) => resp,
+    }
+}
+
+#[post("/wallets/{name}/address")]
+pub(super) async fn wallet_new_address(
+    path: web::Path<String>,
+    json: web::Json<NewAddressRequest>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    let url = match wallet_url(&btc_rpc_cfg, &path.into_inner()) {
+        Some(url) => url,
+        None => return invalid_url_response(),
+    };
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "getnewaddress", "params": [
+        json.label.clone().unwrap_or_default(),
+        json.address_type,
+    ]});
+
+    match rpc_call_url::<NewAddress>(&rq_client, &btc_rpc_cfg, &url, &payload).await {
+        Ok(NewAddress {
+            result: Some(address),
+            ..
+        }) => HttpResponse::Ok().json(json!({ "address": address })),
+        Ok(NewAddress {
+            error: Some(err), ..
+        }) => wallet_error_response(err),
+        Ok(_) => empty_result_response(),
+        Err(resp) => resp,
+    }
+}
+
+#[get("/wallets/{name}/transactions")]
+pub(super) async fn wallet_transactions(
+    path: web::Path<String>,
+    query: web::Query<TransactionsQuery>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    if let Err(err) = query.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    let url = match wallet_url(&btc_rpc_cfg, &path.into_inner()) {
+        Some(url) => url,
+        None => return invalid_url_response(),
+    };
+
+    let payload = json!({ "jsonrpc": "2.0",  "method": "listtransactions", "params": [
+        "*",
+        query.count.unwrap_or(10),
+        query.skip.unwrap_or(0),
+        true,
+    ]});
+
+    match rpc_call_url::<WalletTransactions>(&rq_client, &btc_rpc_cfg, &url, &payload).await {
+        Ok(WalletTransactions {
+            result: Some(txs), ..
+        }) => HttpResponse::Ok().json(txs),
+        Ok(WalletTransactions {
+            error: Some(err), ..
+        }) => wallet_error_response(err),
+        Ok(_) => empty_result_response(),
+        Err(resp) => resp,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wallet_url_segment() {
+        let cfg = BitcoinRpcConfig {
+            bitcoin_rpc_url_one: "http://127.0.0.1:18332/".to_string(),
+            ..BitcoinRpcConfig::default()
+        };
+
+        assert_eq!(
+            wallet_url(&cfg, "cold storage/1").unwrap(),
+            "http://127.0.0.1:18332/wallet/cold%20storage%2F1"
+        );
+    }
+}
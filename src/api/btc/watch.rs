@@ -0,0 +1,246 @@
+use std::{net::IpAddr, str::FromStr};
+
+use actix_web::{delete, get, post, web, HttpResponse, Responder};
+use bitcoin::{hashes::hex::ToHex, Address};
+use log::error;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+    api::btc::{
+        handler::{node_network, ErrorResponse},
+        model::WatchResponse,
+    },
+    config::BitcoinRpcConfig,
+    request::RequestClient,
+    webhooks::{now, random_id, Watch, WebhookStore},
+};
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateWatchRequest {
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub address: Option<String>,
+
+    #[validate(required, url(message = "invalid url"))]
+    pub callback_url: Option<String>,
+
+    #[validate(range(min = 1, max = 100, message = "must be between 1 and 100"))]
+    pub confirmations: Option<u32>,
+}
+
+fn watch_response(watch: Watch, with_secret: bool) -> WatchResponse {
+    WatchResponse {
+        id: watch.id,
+        address: watch.address,
+        script_pubkey: watch.script_pubkey,
+        callback_url: watch.callback_url,
+        confirmations: watch.confirmations,
+        secret: if with_secret {
+            Some(watch.secret)
+        } else {
+            None
+        },
+        created_at: watch.created_at,
+    }
+}
+
+// loopback, private and link-local hosts, a callback there reaches into our own network
+fn private_host(callback_url: &str) -> bool {
+    let url = match Url::parse(callback_url) {
+        Ok(url) => url,
+        Err(_) => return true,
+    };
+
+    let host = match url.host_str() {
+        Some(host) => host.trim_start_matches('[').trim_end_matches(']'),
+        None => return true,
+    };
+
+    let ip = match host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(ip)) => match ip.to_ipv4_mapped() {
+            Some(ip) => IpAddr::V4(ip),
+            None => IpAddr::V6(ip),
+        },
+        Ok(ip) => ip,
+        Err(_) => {
+            let domain = host.trim_end_matches('.').to_lowercase();
+            return domain == "localhost" || domain.ends_with(".localhost");
+        }
+    };
+
+    match ip {
+        IpAddr::V4(ip) => {
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                // shared address space, 100.64.0.0/10
+                || (ip.octets()[0] == 100 && (ip.octets()[1] & 0xc0) == 64)
+        }
+        IpAddr::V6(ip) => {
+            ip.is_loopback()
+                || ip.is_unspecified()
+                // unique local, fc00::/7
+                || (ip.segments()[0] & 0xfe00) == 0xfc00
+                // link-local, fe80::/10
+                || (ip.segments()[0] & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+fn store_error_response(err: sled::Error) -> HttpResponse {
+    error!("webhook store error: {}", err);
+    HttpResponse::InternalServerError().json(ErrorResponse {
+        message: "failed to access webhook storage".to_string(),
+    })
+}
+
+fn not_found_response() -> HttpResponse {
+    HttpResponse::NotFound().json(ErrorResponse {
+        message: "watch not found".to_string(),
+    })
+}
+
+#[post("/watches")]
+pub(super) async fn create_watch(
+    json: web::Json<CreateWatchRequest>,
+    store: web::Data<WebhookStore>,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+) -> impl Responder {
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    let callback_url = json.callback_url.as_ref().unwrap();
+    if !btc_rpc_cfg.bitcoin_webhook_allow_private && private_host(callback_url) {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            message: "callback url must not point at a loopback or private host".to_string(),
+        });
+    }
+
+    let address = json.address.as_ref().unwrap();
+    let address = match Address::from_str(address) {
+        Ok(address) => address,
+        Err(_) => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                message: "invalid Bitcoin address".to_string(),
+            })
+        }
+    };
+
+    let network = match node_network(&rq_client, &btc_rpc_cfg).await {
+        Ok(network) => network,
+        Err(resp) => return resp,
+    };
+    if !address.is_valid_for_network(network) {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            message: format!("address is not valid on the node network {}", network),
+        });
+    }
+
+    let watch = Watch {
+        id: random_id(),
+        address: address.to_string(),
+        script_pubkey: address.script_pubkey().to_hex(),
+        callback_url: callback_url.to_string(),
+        confirmations: json.confirmations.unwrap_or(6),
+        secret: hex::encode(rand::random::<[u8; 32]>()),
+        created_at: now(),
+    };
+
+    match store.insert_watch(&watch) {
+        Ok(_) => HttpResponse::Created().json(watch_response(watch, true)),
+        Err(err) => store_error_response(err),
+    }
+}
+
+#[get("/watches")]
+pub(super) async fn list_watches(store: web::Data<WebhookStore>) -> impl Responder {
+    match store.watches() {
+        Ok(watches) => HttpResponse::Ok().json(
+            watches
+                .into_iter()
+                .map(|watch| watch_response(watch, false))
+                .collect::<Vec<_>>(),
+        ),
+        Err(err) => store_error_response(err),
+    }
+}
+
+#[get("/watches/{id}")]
+pub(super) async fn get_watch(
+    path: web::Path<String>,
+    store: web::Data<WebhookStore>,
+) -> impl Responder {
+    match store.watch(&path.into_inner()) {
+        Ok(Some(watch)) => HttpResponse::Ok().json(watch_response(watch, false)),
+        Ok(None) => not_found_response(),
+        Err(err) => store_error_response(err),
+    }
+}
+
+#[delete("/watches/{id}")]
+pub(super) async fn delete_watch(
+    path: web::Path<String>,
+    store: web::Data<WebhookStore>,
+) -> impl Responder {
+    match store.remove_watch(&path.into_inner()) {
+        Ok(Some(watch)) => HttpResponse::Ok().json(watch_response(watch, false)),
+        Ok(None) => not_found_response(),
+        Err(err) => store_error_response(err),
+    }
+}
+
+#[get("/watches/{id}/deliveries")]
+pub(super) async fn watch_deliveries(
+    path: web::Path<String>,
+    store: web::Data<WebhookStore>,
+) -> impl Responder {
+    let id = path.into_inner();
+
+    match store.watch(&id) {
+        Ok(Some(_)) => match store.delivery_logs(&id) {
+            Ok(logs) => HttpResponse::Ok().json(logs),
+            Err(err) => store_error_response(err),
+        },
+        Ok(None) => not_found_response(),
+        Err(err) => store_error_response(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn private_callback_hosts() {
+        for url in [
+            "http://localhost:8080/hook",
+            "http://api.localhost/hook",
+            "http://127.0.0.1/hook",
+            "http://10.1.2.3/hook",
+            "http://172.16.0.1/hook",
+            "http://192.168.1.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/hook",
+            "http://0.0.0.0/hook",
+            "http://[::1]/hook",
+            "http://[fd00::1]/hook",
+            "http://[fe80::1]/hook",
+            "http://[::ffff:127.0.0.1]/hook",
+        ] {
+            assert!(private_host(url), "{}", url);
+        }
+
+        for url in [
+            "https://example.com/hook",
+            "http://8.8.8.8/hook",
+            "http://[2001:db8::1]/hook",
+        ] {
+            assert!(!private_host(url), "{}", url);
+        }
+    }
+}
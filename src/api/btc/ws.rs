@@ -0,0 +1,509 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    str::FromStr,
+    sync::Arc,
+};
+
+use actix_web::{get, rt, web, Error, HttpRequest, HttpResponse};
+use actix_ws::{Message, Session};
+use bitcoin::{hashes::hex::FromHex, Address, Amount, Block, BlockHash, Script, Transaction, Txid};
+use futures::StreamExt;
+use log::{error, warn};
+use tokio::sync::{
+    broadcast::{error::RecvError, Receiver},
+    mpsc::{self, UnboundedSender},
+};
+
+use crate::{
+    api::btc::{
+        handler::get_raw_transaction,
+        model::{WsEvent, WsRequest, WsTopic},
+    },
+    config::BitcoinRpcConfig,
+    events::{EventBus, NodeEvent, SequenceEvent},
+    request::RequestClient,
+};
+
+// the same notification arrives once per configured node
+const SEEN_CAPACITY: usize = 1000;
+// tx and address subscriptions of one client
+const MAX_SUBSCRIPTIONS: usize = 100;
+
+// events are only produced when zmq notifications are configured
+#[get("/ws")]
+async fn ws(
+    req: HttpRequest,
+    body: web::Payload,
+    rq_client: web::Data<RequestClient>,
+    btc_rpc_cfg: web::Data<BitcoinRpcConfig>,
+    event_bus: web::Data<EventBus>,
+) -> Result<HttpResponse, Error> {
+    let (response, session, messages) = actix_ws::handle(&req, body)?;
+
+    rt::spawn(run_session(
+        session,
+        messages,
+        event_bus.subscribe(),
+        rq_client.into_inner(),
+        btc_rpc_cfg.into_inner(),
+    ));
+
+    Ok(response)
+}
+
+async fn run_session(
+    mut session: Session,
+    mut messages: actix_ws::MessageStream,
+    mut events: Receiver<NodeEvent>,
+    rq_client: Arc<RequestClient>,
+    btc_rpc_cfg: Arc<BitcoinRpcConfig>,
+) {
+    let mut subscriptions = Subscriptions::default();
+    // current confirmations of subscribed txs, looked up aside so events keep flowing
+    let (lookup_tx, mut lookups) = mpsc::unbounded_channel();
+
+    loop {
+        let outgoing = tokio::select! {
+            message = messages.next() => match message {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str::<WsRequest>(&text) {
+                    Ok(request) => {
+                        let (events, lookup) = subscriptions.handle_request(request);
+                        if let Some(txid) = lookup {
+                            rt::spawn(lookup_confirmations(
+                                txid,
+                                rq_client.clone(),
+                                btc_rpc_cfg.clone(),
+                                lookup_tx.clone(),
+                            ));
+                        }
+                        events
+                    }
+                    Err(err) => vec![WsEvent::Error {
+                        message: err.to_string(),
+                    }],
+                },
+                Some(Ok(Message::Ping(bytes))) => {
+                    if session.pong(&bytes).await.is_err() {
+                        return;
+                    }
+                    continue;
+                }
+                Some(Ok(Message::Close(reason))) => {
+                    let _ = session.close(reason).await;
+                    return;
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(err)) => {
+                    warn!("websocket protocol error: {}", err);
+                    return;
+                }
+                None => return,
+            },
+            Some((txid, confirmations)) = lookups.recv() => {
+                subscriptions.tx_looked_up(txid, confirmations)
+            }
+            event = events.recv() => match event {
+                Ok(event) => subscriptions.handle_event(event),
+                Err(RecvError::Lagged(skipped)) => vec![WsEvent::Error {
+                    message: format!("client too slow, {} node events skipped", skipped),
+                }],
+                Err(RecvError::Closed) => {
+                    let _ = session.close(None).await;
+                    return;
+                }
+            },
+        };
+
+        for event in outgoing {
+            let text = match serde_json::to_string(&event) {
+                Ok(text) => text,
+                Err(err) => {
+                    error!("failed to encode websocket event, {}", err);
+                    continue;
+                }
+            };
+
+            if session.text(text).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+// confirmations of the tx, None when the node lacks it
+async fn lookup_confirmations(
+    txid: Txid,
+    rq_client: Arc<RequestClient>,
+    btc_rpc_cfg: Arc<BitcoinRpcConfig>,
+    lookup_tx: UnboundedSender<(Txid, Option<usize>)>,
+) {
+    let confirmations = get_raw_transaction(&rq_client, &btc_rpc_cfg, &txid.to_string())
+        .await
+        .ok()
+        .map(|raw_tx| raw_tx.confirmations.unwrap_or(0));
+
+    // the session may be gone already
+    let _ = lookup_tx.send((txid, confirmations));
+}
+
+#[derive(Default)]
+struct Subscriptions {
+    blocks: bool,
+    // txid -> confirmations
+    txs: HashMap<Txid, usize>,
+    // script -> address as subscribed
+    addresses: HashMap<Script, String>,
+    seen_blocks: Seen<BlockHash>,
+    seen_disconnected: Seen<BlockHash>,
+    seen_txs: Seen<Txid>,
+}
+
+impl Subscriptions {
+    fn full(&self) -> bool {
+        self.txs.len() + self.addresses.len() >= MAX_SUBSCRIPTIONS
+    }
+
+    // events to send and a subscribed tx whose current state is to be looked up
+    fn handle_request(&mut self, request: WsRequest) -> (Vec<WsEvent>, Option<Txid>) {
+        match request {
+            WsRequest::Subscribe(topic) => match &topic {
+                WsTopic::Blocks => {
+                    self.blocks = true;
+                    (vec![WsEvent::Subscribed(topic)], None)
+                }
+                WsTopic::Tx { txid } => {
+                    let parsed = match Txid::from_hex(txid) {
+                        Ok(parsed) => parsed,
+                        Err(_) => return (vec![error_event("invalid txid")], None),
+                    };
+                    if !self.txs.contains_key(&parsed) && self.full() {
+                        return (vec![limit_event()], None);
+                    }
+
+                    // unknown until the lookup or the tx shows up
+                    self.txs.entry(parsed).or_insert(0);
+                    (vec![WsEvent::Subscribed(topic)], Some(parsed))
+                }
+                WsTopic::Address { address } => match Address::from_str(address) {
+                    Ok(parsed) => {
+                        let script_pubkey = parsed.script_pubkey();
+                        if !self.addresses.contains_key(&script_pubkey) && self.full() {
+                            return (vec![limit_event()], None);
+                        }
+
+                        self.addresses.insert(script_pubkey, address.clone());
+                        (vec![WsEvent::Subscribed(topic)], None)
+                    }
+                    Err(_) => (vec![error_event("invalid Bitcoin address")], None),
+                },
+            },
+            WsRequest::Unsubscribe(topic) => {
+                match &topic {
+                    WsTopic::Blocks => self.blocks = false,
+                    WsTopic::Tx { txid } => {
+                        if let Ok(txid) = Txid::from_hex(txid) {
+                            self.txs.remove(&txid);
+                        }
+                    }
+                    WsTopic::Address { address } => {
+                        self.addresses.retain(|_, watched| watched != address);
+                    }
+                }
+                (vec![WsEvent::Unsubscribed(topic)], None)
+            }
+        }
+    }
+
+    fn tx_looked_up(&mut self, txid: Txid, confirmations: Option<usize>) -> Vec<WsEvent> {
+        match (self.txs.get_mut(&txid), confirmations) {
+            (Some(current), Some(confirmations)) => {
+                *current = confirmations;
+                vec![WsEvent::Tx {
+                    txid: txid.to_string(),
+                    confirmations,
+                }]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn handle_event(&mut self, event: NodeEvent) -> Vec<WsEvent> {
+        match event {
+            NodeEvent::Block { block, .. } => self.block_connected(&block),
+            NodeEvent::Tx { tx, .. } => self.tx_seen(&tx),
+            NodeEvent::Sequence {
+                sequence: SequenceEvent::BlockDisconnected(hash),
+                ..
+            } => self.block_disconnected(hash),
+            _ => Vec::new(),
+        }
+    }
+
+    fn block_connected(&mut self, block: &Block) -> Vec<WsEvent> {
+        let hash = block.block_hash();
+        if !self.seen_blocks.insert(hash) {
+            return Vec::new();
+        }
+        // a reconnected block can be disconnected again
+        self.seen_disconnected.remove(&hash);
+
+        let mut events = Vec::new();
+        if self.blocks {
+            events.push(WsEvent::Block {
+                hash: hash.to_string(),
+                height: block.bip34_block_height().ok(),
+                tx_count: block.txdata.len(),
+            });
+        }
+
+        let txids: HashSet<Txid> = block.txdata.iter().map(|tx| tx.txid()).collect();
+        for (txid, confirmations) in self.txs.iter_mut() {
+            if txids.contains(txid) {
+                *confirmations = 1;
+            } else if *confirmations > 0 {
+                *confirmations += 1;
+            } else {
+                continue;
+            }
+
+            events.push(WsEvent::Tx {
+                txid: txid.to_string(),
+                confirmations: *confirmations,
+            });
+        }
+
+        for tx in &block.txdata {
+            events.extend(self.address_events(tx, 1));
+        }
+
+        events
+    }
+
+    fn block_disconnected(&mut self, hash: BlockHash) -> Vec<WsEvent> {
+        if !self.seen_disconnected.insert(hash) {
+            return Vec::new();
+        }
+        self.seen_blocks.remove(&hash);
+
+        let mut events = Vec::new();
+        if self.blocks {
+            events.push(WsEvent::BlockDisconnected {
+                hash: hash.to_string(),
+            });
+        }
+
+        for (txid, confirmations) in self.txs.iter_mut() {
+            if *confirmations > 0 {
+                *confirmations -= 1;
+                events.push(WsEvent::Tx {
+                    txid: txid.to_string(),
+                    confirmations: *confirmations,
+                });
+            }
+        }
+
+        events
+    }
+
+    // rawtx is also published for every tx of a connected block, report only the first sighting
+    fn tx_seen(&mut self, tx: &Transaction) -> Vec<WsEvent> {
+        let txid = tx.txid();
+        if !self.seen_txs.insert(txid) {
+            return Vec::new();
+        }
+
+        let mut events = Vec::new();
+        if self.txs.get(&txid) == Some(&0) {
+            events.push(WsEvent::Tx {
+                txid: txid.to_string(),
+                confirmations: 0,
+            });
+        }
+
+        events.extend(self.address_events(tx, 0));
+        events
+    }
+
+    fn address_events(&self, tx: &Transaction, confirmations: usize) -> Vec<WsEvent> {
+        if self.addresses.is_empty() {
+            return Vec::new();
+        }
+
+        tx.output
+            .iter()
+            .enumerate()
+            .filter_map(|(vout, out)| {
+                self.addresses
+                    .get(&out.script_pubkey)
+                    .map(|address| WsEvent::AddressTx {
+                        address: address.clone(),
+                        txid: tx.txid().to_string(),
+                        vout: vout as u32,
+                        amount: Amount::from_sat(out.value).to_btc(),
+                        confirmations,
+                    })
+            })
+            .collect()
+    }
+}
+
+fn error_event(message: &str) -> WsEvent {
+    WsEvent::Error {
+        message: message.to_string(),
+    }
+}
+
+fn limit_event() -> WsEvent {
+    WsEvent::Error {
+        message: format!("subscription limit of {} reached", MAX_SUBSCRIPTIONS),
+    }
+}
+
+// bounded set of recently handled hashes
+struct Seen<T> {
+    order: VecDeque<T>,
+    items: HashSet<T>,
+}
+
+impl<T> Default for Seen<T> {
+    fn default() -> Self {
+        Seen {
+            order: VecDeque::new(),
+            items: HashSet::new(),
+        }
+    }
+}
+
+impl<T: std::hash::Hash + Eq + Copy> Seen<T> {
+    fn insert(&mut self, item: T) -> bool {
+        if !self.items.insert(item) {
+            return false;
+        }
+
+        self.order.push_back(item);
+        if self.order.len() > SEEN_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.items.remove(&oldest);
+            }
+        }
+
+        true
+    }
+
+    fn remove(&mut self, item: &T) {
+        if self.items.remove(item) {
+            self.order.retain(|seen| seen != item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::{blockdata::constants::genesis_block, Network, PackedLockTime, TxOut};
+
+    const ADDRESS: &str = "mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u";
+
+    fn payment(address: &str, value: u64) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![],
+            output: vec![TxOut {
+                value,
+                script_pubkey: Address::from_str(address).unwrap().script_pubkey(),
+            }],
+        }
+    }
+
+    #[test]
+    fn parse_request() {
+        let request: WsRequest =
+            serde_json::from_str(r#"{"op":"subscribe","topic":"address","address":"x"}"#).unwrap();
+
+        match request {
+            WsRequest::Subscribe(WsTopic::Address { address }) => assert_eq!(address, "x"),
+            _ => panic!("unexpected request"),
+        }
+    }
+
+    #[test]
+    fn address_and_tx_confirmations() {
+        let tx = payment(ADDRESS, 10_000);
+        let mut subscriptions = Subscriptions::default();
+        subscriptions.addresses.insert(
+            Address::from_str(ADDRESS).unwrap().script_pubkey(),
+            ADDRESS.to_string(),
+        );
+        subscriptions.txs.insert(tx.txid(), 0);
+
+        // mempool, the second node reports the same tx
+        let events = subscriptions.tx_seen(&tx);
+        assert_eq!(events.len(), 2);
+        assert!(subscriptions.tx_seen(&tx).is_empty());
+
+        let mut block = genesis_block(Network::Testnet);
+        block.txdata.push(tx.clone());
+        let events = subscriptions.block_connected(&block);
+        assert!(events.iter().any(|event| matches!(
+            event,
+            WsEvent::Tx {
+                confirmations: 1,
+                ..
+            }
+        )));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            WsEvent::AddressTx {
+                confirmations: 1,
+                ..
+            }
+        )));
+
+        let mut next = genesis_block(Network::Testnet);
+        next.header.prev_blockhash = block.block_hash();
+        subscriptions.block_connected(&next);
+        assert_eq!(subscriptions.txs[&tx.txid()], 2);
+
+        subscriptions.block_disconnected(next.block_hash());
+        assert_eq!(subscriptions.txs[&tx.txid()], 1);
+        assert!(subscriptions
+            .block_disconnected(next.block_hash())
+            .is_empty());
+
+        // reconnected and disconnected again
+        subscriptions.block_connected(&next);
+        assert_eq!(subscriptions.txs[&tx.txid()], 2);
+        assert!(!subscriptions
+            .block_disconnected(next.block_hash())
+            .is_empty());
+        assert_eq!(subscriptions.txs[&tx.txid()], 1);
+    }
+
+    #[test]
+    fn subscription_limit() {
+        let mut subscriptions = Subscriptions::default();
+        let subscribe = |subscriptions: &mut Subscriptions, n: usize| {
+            subscriptions.handle_request(WsRequest::Subscribe(WsTopic::Tx {
+                txid: format!("{:064x}", n),
+            }))
+        };
+
+        for n in 0..MAX_SUBSCRIPTIONS {
+            let (_, lookup) = subscribe(&mut subscriptions, n);
+            assert!(lookup.is_some());
+        }
+
+        let (events, lookup) = subscribe(&mut subscriptions, MAX_SUBSCRIPTIONS);
+        assert!(lookup.is_none());
+        assert!(matches!(events[0], WsEvent::Error { .. }));
+
+        // an existing one is still accepted
+        let (_, lookup) = subscribe(&mut subscriptions, 0);
+        assert!(lookup.is_some());
+
+        let txid = Txid::from_hex(&format!("{:064x}", 0)).unwrap();
+        assert_eq!(subscriptions.tx_looked_up(txid, Some(3)).len(), 1);
+        assert_eq!(subscriptions.txs[&txid], 3);
+    }
+}
@@ -0,0 +1,108 @@
+use bitcoin::{
+    bech32::{self, FromBase32, Variant},
+    hashes::Hash,
+    util::{address::WitnessVersion, base58},
+    PubkeyHash, Script, ScriptHash,
+};
+
+// address encoding of one network of a bitcoin derived chain
+pub struct AddressParams {
+    pub p2pkh: u8,
+    // forks may accept more than one p2sh version
+    pub p2sh: &'static [u8],
+    // None for chains without segwit
+    pub hrp: Option<&'static str>,
+}
+
+fn base58_script(address: &str, networks: &[AddressParams]) -> Option<Script> {
+    let data = base58::from_check(address).ok()?;
+    let (version, hash) = data.split_first()?;
+
+    if networks.iter().any(|params| params.p2pkh == *version) {
+        Some(Script::new_p2pkh(&PubkeyHash::from_slice(hash).ok()?))
+    } else if networks.iter().any(|params| params.p2sh.contains(version)) {
+        Some(Script::new_p2sh(&ScriptHash::from_slice(hash).ok()?))
+    } else {
+        None
+    }
+}
+
+fn segwit_script(address: &str, networks: &[AddressParams]) -> Option<Script> {
+    let (hrp, data, variant) = bech32::decode(address).ok()?;
+    if !networks
+        .iter()
+        .any(|params| params.hrp == Some(hrp.as_str()))
+    {
+        return None;
+    }
+
+    let (version, program) = data.split_first()?;
+    let version = WitnessVersion::try_from(*version).ok()?;
+    let program = Vec::<u8>::from_base32(program).ok()?;
+
+    let valid = match version {
+        WitnessVersion::V0 => variant == Variant::Bech32 && [20, 32].contains(&program.len()),
+        _ => variant == Variant::Bech32m && (2..=40).contains(&program.len()),
+    };
+
+    valid.then(|| Script::new_witness_program(version, &program))
+}
+
+// output script of an address of any of the given networks
+pub fn script_pubkey(address: &str, networks: &[AddressParams]) -> Result<Script, String> {
+    base58_script(address, networks)
+        .or_else(|| segwit_script(address, networks))
+        .ok_or_else(|| format!("invalid address {}", address))
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::hashes::hex::ToHex;
+
+    use super::*;
+
+    const PARAMS: [AddressParams; 2] = [
+        AddressParams {
+            p2pkh: 0x00,
+            p2sh: &[0x05],
+            hrp: Some("bc"),
+        },
+        AddressParams {
+            p2pkh: 0x6f,
+            p2sh: &[0xc4],
+            hrp: Some("tb"),
+        },
+    ];
+
+    #[test]
+    fn decode_addresses() {
+        assert_eq!(
+            script_pubkey("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2", &PARAMS)
+                .unwrap()
+                .to_hex(),
+            "76a91477bff20c60e522dfaa3350c39b030a5d004e839a88ac"
+        );
+        assert_eq!(
+            script_pubkey("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy", &PARAMS)
+                .unwrap()
+                .to_hex(),
+            "a914b472a266d0bd89c13706a4132ccfb16f7c3b9fcb87"
+        );
+        assert_eq!(
+            script_pubkey("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx", &PARAMS)
+                .unwrap()
+                .to_hex(),
+            "0014751e76e8199196d454941c45d1b3a323f1433bd6"
+        );
+        assert!(script_pubkey(
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+            &PARAMS
+        )
+        .unwrap()
+        .is_v1_p2tr());
+
+        // other chain and broken checksum
+        assert!(script_pubkey("ltc1qw508d6qejxtdg4y5r3zarvary0c5xw7kgmn4n9", &PARAMS).is_err());
+        assert!(script_pubkey("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN3", &PARAMS).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use actix_web::{get, post, web, HttpResponse, Responder};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::api::{
+    btc::handler::{broadcast_response, Utxo},
+    chain::{model::ChainSummary, ChainNode, ChainNodes},
+};
+
+// confirmation target of /create-tx and of /fees without a target
+const DEFAULT_FEE_TARGET: u16 = 6;
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    cfg.service(status);
+    cfg.service(fees);
+    cfg.service(create_tx);
+    cfg.service(sign_tx);
+    cfg.service(send_tx);
+    cfg.service(tx_status);
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TxOutput {
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub address: Option<String>,
+
+    #[validate(required, range(min = 0.00000001, message = "cannot be empty"))]
+    pub amount: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct BuildTxRequest {
+    #[validate]
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub utxos: Option<Vec<Utxo>>,
+
+    #[validate]
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub to: Option<Vec<TxOutput>>,
+
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub change_address: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SignRequest {
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub raw_tx: Option<String>,
+
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub private_keys: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SendRequest {
+    #[validate(required, length(min = 1, message = "cannot be empty"))]
+    pub signed_tx: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct FeeQuery {
+    #[validate(range(min = 1, max = 1008, message = "must be between 1 and 1008"))]
+    pub target: Option<u16>,
+}
+
+#[get("/chains")]
+pub(super) async fn chains(nodes: web::Data<ChainNodes>) -> impl Responder {
+    let summaries = nodes.iter().map(|node| async move {
+        match node.status().await {
+            Ok(chain_status) => ChainSummary {
+                chain: node.name().to_string(),
+                status: Some(chain_status),
+                error: None,
+            },
+            Err(resp) => ChainSummary {
+                chain: node.name().to_string(),
+                status: None,
+                error: Some(format!("node responded with {}", resp.status())),
+            },
+        }
+    });
+
+    HttpResponse::Ok().json(join_all(summaries).await)
+}
+
+#[get("/status")]
+async fn status(node: web::Data<dyn ChainNode>) -> impl Responder {
+    match node.status().await {
+        Ok(chain_status) => HttpResponse::Ok().json(chain_status),
+        Err(resp) => resp,
+    }
+}
+
+#[get("/fees")]
+async fn fees(query: web::Query<FeeQuery>, node: web::Data<dyn ChainNode>) -> impl Responder {
+    if let Err(err) = query.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    match node
+        .estimate_fee(query.target.unwrap_or(DEFAULT_FEE_TARGET))
+        .await
+    {
+        Ok(fee) => HttpResponse::Ok().json(fee),
+        Err(resp) => resp,
+    }
+}
+
+#[post("/create-tx")]
+async fn create_tx(
+    json: web::Json<BuildTxRequest>,
+    node: web::Data<dyn ChainNode>,
+) -> impl Responder {
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    match node.build_tx(&json).await {
+        Ok(tx) => HttpResponse::Ok().json(tx),
+        Err(resp) => resp,
+    }
+}
+
+#[post("/sign-tx")]
+async fn sign_tx(json: web::Json<SignRequest>, node: web::Data<dyn ChainNode>) -> impl Responder {
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    match node
+        .sign_tx(
+            json.raw_tx.as_ref().unwrap(),
+            json.private_keys.as_ref().unwrap(),
+        )
+        .await
+    {
+        Ok(signed_tx) => HttpResponse::Ok().json(signed_tx),
+        Err(resp) => resp,
+    }
+}
+
+#[post("/send-tx")]
+async fn send_tx(json: web::Json<SendRequest>, node: web::Data<dyn ChainNode>) -> impl Responder {
+    if let Err(err) = json.validate() {
+        return HttpResponse::BadRequest().json(err);
+    }
+
+    match node.broadcast(json.signed_tx.as_ref().unwrap()).await {
+        Ok(broadcast) => broadcast_response(broadcast),
+        Err(resp) => resp,
+    }
+}
+
+#[get("/tx/{txid}")]
+async fn tx_status(path: web::Path<String>, node: web::Data<dyn ChainNode>) -> impl Responder {
+    match node.tx_status(&path.into_inner()).await {
+        Ok(tx_status) => HttpResponse::Ok().json(tx_status),
+        Err(resp) => resp,
+    }
+}
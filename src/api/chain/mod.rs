@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use actix_web::{web, HttpResponse};
+use async_trait::async_trait;
+
+use crate::{
+    api::{btc::BitcoinNode, doge::DogecoinNode, ltc::LitecoinNode},
+    config::Config,
+    request::RequestClient,
+};
+
+pub mod address;
+pub mod handler;
+pub mod model;
+
+use handler::BuildTxRequest;
+use model::{Broadcast, ChainFee, ChainStatus, SignTxResult, TxStatus, UnsignedTx};
+
+// a node of one chain, served under /api/{name}
+#[async_trait(?Send)]
+pub trait ChainNode: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // routes of the chain scope, chains with their own api mount it before the generic ones
+    fn configure(&self, cfg: &mut web::ServiceConfig) {
+        handler::init(cfg);
+    }
+
+    async fn status(&self) -> Result<ChainStatus, HttpResponse>;
+
+    async fn estimate_fee(&self, target: u16) -> Result<ChainFee, HttpResponse>;
+
+    async fn build_tx(&self, request: &BuildTxRequest) -> Result<UnsignedTx, HttpResponse>;
+
+    async fn sign_tx(
+        &self,
+        raw_tx: &str,
+        private_keys: &[String],
+    ) -> Result<SignTxResult, HttpResponse>;
+
+    async fn broadcast(&self, signed_tx: &str) -> Result<Broadcast, HttpResponse>;
+
+    async fn tx_status(&self, txid: &str) -> Result<TxStatus, HttpResponse>;
+}
+
+pub type ChainNodes = Vec<Arc<dyn ChainNode>>;
+
+// one node per configured chain, in config order
+pub fn nodes(cfg: &Config, rq_client: &RequestClient) -> ChainNodes {
+    cfg.chains
+        .iter()
+        .map(|chain| -> Arc<dyn ChainNode> {
+            match chain.as_str() {
+                "bitcoin" => Arc::new(BitcoinNode::new(
+                    rq_client.clone(),
+                    cfg.bitcoin_rpc_config.clone().unwrap(),
+                )),
+                "litecoin" => Arc::new(LitecoinNode::new(
+                    rq_client.clone(),
+                    cfg.litecoin_rpc_config.clone().unwrap(),
+                )),
+                "dogecoin" => Arc::new(DogecoinNode::new(
+                    rq_client.clone(),
+                    cfg.dogecoin_rpc_config.clone().unwrap(),
+                    cfg.dogecoin_fee_per_kb,
+                )),
+                _ => panic!("unsupported chain {}", chain),
+            }
+        })
+        .collect()
+}
+
+// /chains and a scope for every node, meant for the /api scope
+pub fn init(cfg: &mut web::ServiceConfig, nodes: &ChainNodes) {
+    cfg.app_data(web::Data::new(nodes.clone()));
+    cfg.service(handler::chains);
+
+    for node in nodes {
+        cfg.service(
+            web::scope(&format!("/{}", node.name()))
+                .app_data(web::Data::from(node.clone()))
+                .configure(|cfg| node.configure(cfg)),
+        );
+    }
+}
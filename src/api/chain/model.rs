@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+// chain neutral already, shared with the bitcoin api
+pub use crate::api::btc::model::{
+    Broadcast, BroadcastNodeResult, BroadcastStatus, SignTxResult, SignTxResultErrors, TxStatus,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct ChainStatus {
+    pub chain: String,
+    // main, test, regtest... as reported by the node
+    pub network: String,
+    pub blocks: usize,
+    pub headers: usize,
+    pub best_block_hash: String,
+    pub verification_progress: f64,
+    pub initial_block_download: bool,
+    // litecoin only
+    pub mweb: Option<MwebStatus>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MwebStatus {
+    pub active: bool,
+    pub activation_height: Option<usize>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ChainFee {
+    pub target: u16,
+    // coins per kvB
+    pub fee_rate: f64,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct UnsignedTx {
+    pub hex: String,
+    // base64, chains with PSBT support only
+    pub psbt: Option<String>,
+    // coins per kvB paid by the tx
+    pub fee_rate: f64,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ChainSummary {
+    pub chain: String,
+    pub status: Option<ChainStatus>,
+    pub error: Option<String>,
+}
@@ -0,0 +1,4 @@
+mod model;
+mod node;
+
+pub use node::DogecoinNode;
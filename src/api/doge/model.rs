@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::btc::model::RPCError;
+
+// dogecoin core 1.14 is based on bitcoin core 0.14, its results lack the newer fields
+
+#[derive(Deserialize, Serialize)]
+pub struct DogecoinInfoResult {
+    pub chain: String,
+    pub blocks: usize,
+    pub headers: usize,
+    pub bestblockhash: String,
+    pub verificationprogress: f64,
+    pub initialblockdownload: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DogecoinInfo {
+    pub result: Option<DogecoinInfoResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct NetworkInfoResult {
+    // DOGE/kB
+    pub relayfee: f64,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct NetworkInfo {
+    pub result: Option<NetworkInfoResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DogecoinTxResult {
+    pub txid: String,
+    pub blockhash: Option<String>,
+    pub confirmations: Option<usize>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DogecoinTx {
+    pub result: Option<DogecoinTxResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DogecoinMempoolEntryResult {
+    // DOGE
+    pub fee: f64,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DogecoinMempoolEntry {
+    pub result: Option<DogecoinMempoolEntryResult>,
+    pub error: Option<RPCError>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DogecoinHeaderResult {
+    pub height: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DogecoinHeader {
+    pub result: Option<DogecoinHeaderResult>,
+    pub error: Option<RPCError>,
+}
@@ -0,0 +1,471 @@
+use actix_web::HttpResponse;
+use async_trait::async_trait;
+use bitcoin::{consensus, hashes::hex::FromHex, Amount, Script, Transaction, Txid};
+use serde_json::json;
+
+use crate::{
+    api::{
+        btc::{
+            handler::{
+                broadcast_payload, empty_result_response, rpc_call, rpc_error_response,
+                ErrorResponse, Utxo,
+            },
+            model::SignTx,
+            service::build_transaction,
+        },
+        chain::{
+            address::{self, AddressParams},
+            handler::BuildTxRequest,
+            model::{Broadcast, ChainFee, ChainStatus, SignTxResult, TxStatus, UnsignedTx},
+            ChainNode,
+        },
+        doge::model::{
+            DogecoinHeader, DogecoinInfo, DogecoinMempoolEntry, DogecoinTx, NetworkInfo,
+        },
+    },
+    config::BitcoinRpcConfig,
+    request::RequestClient,
+};
+
+// mainnet D/9(A), testnet n/2, regtest m/2, no segwit on dogecoin
+const ADDRESS_PARAMS: [AddressParams; 3] = [
+    AddressParams {
+        p2pkh: 0x1e,
+        p2sh: &[0x16],
+        hrp: None,
+    },
+    AddressParams {
+        p2pkh: 0x71,
+        p2sh: &[0xc4],
+        hrp: None,
+    },
+    AddressParams {
+        p2pkh: 0x6f,
+        p2sh: &[0xc4],
+        hrp: None,
+    },
+];
+
+// koinu, outputs below are non-standard
+const HARD_DUST_LIMIT: u64 = 100_000;
+// koinu, every output below costs one more fee per kb
+const SOFT_DUST_LIMIT: u64 = 1_000_000;
+// koinu, smaller change is left to the fee like the dogecoin core wallet discards it
+const CHANGE_DISCARD_LIMIT: u64 = 100_000_000;
+
+pub struct DogecoinNode {
+    rq_client: RequestClient,
+    doge_rpc_cfg: BitcoinRpcConfig,
+    // DOGE
+    fee_per_kb: f64,
+}
+
+impl DogecoinNode {
+    pub fn new(
+        rq_client: RequestClient,
+        doge_rpc_cfg: BitcoinRpcConfig,
+        fee_per_kb: f64,
+    ) -> DogecoinNode {
+        DogecoinNode {
+            rq_client,
+            doge_rpc_cfg,
+            fee_per_kb,
+        }
+    }
+}
+
+pub fn script_pubkey(address: &str) -> Result<Script, String> {
+    address::script_pubkey(address, &ADDRESS_PARAMS)
+}
+
+// outputs in koinu, non-standard below the hard dust limit
+pub fn check_dust(amounts: &[u64]) -> Result<(), String> {
+    match amounts.iter().find(|amount| **amount < HARD_DUST_LIMIT) {
+        Some(amount) => Err(format!(
+            "output of {} is below the dust limit of {}",
+            Amount::from_sat(*amount).to_btc(),
+            Amount::from_sat(HARD_DUST_LIMIT).to_btc()
+        )),
+        None => Ok(()),
+    }
+}
+
+// fee in DOGE for every started kB plus one fee per kb for each soft dust output,
+// amounts are the outputs in koinu including change
+pub fn kb_fee(fee_per_kb: f64, amounts: &[u64]) -> impl Fn(usize) -> f64 {
+    let fee_per_kb = (fee_per_kb * 1.0e8) as u64;
+    let soft_dust = amounts
+        .iter()
+        .filter(|amount| **amount < SOFT_DUST_LIMIT)
+        .count() as u64;
+
+    move |tx_byte_size| {
+        let kbs = (tx_byte_size as u64).div_ceil(1000);
+        Amount::from_sat(fee_per_kb * (kbs + soft_dust)).to_btc()
+    }
+}
+
+// the fee comes out of the recipients, so the dust limit applies to what they receive
+pub fn unsigned_tx(
+    utxos: &[Utxo],
+    outputs: &[(Script, f64)],
+    change_script: Script,
+    fee_per_kb: f64,
+) -> Result<Transaction, String> {
+    let mut amounts = outputs
+        .iter()
+        .map(|(_, amount)| to_koinu(*amount))
+        .collect::<Result<Vec<_>, _>>()?;
+    let in_amount = utxos
+        .iter()
+        .map(|utxo| to_koinu(utxo.amount.unwrap()))
+        .sum::<Result<u64, _>>()?;
+    let change = in_amount.saturating_sub(amounts.iter().sum());
+    let keep_change = change >= CHANGE_DISCARD_LIMIT;
+    if keep_change {
+        amounts.push(change);
+    }
+
+    let mut tx =
+        build_transaction(utxos, outputs, change_script, kb_fee(fee_per_kb, &amounts))?.unsigned_tx;
+    if !keep_change {
+        tx.output.pop();
+    }
+
+    let received = tx.output[..outputs.len()]
+        .iter()
+        .map(|output| output.value)
+        .collect::<Vec<_>>();
+    check_dust(&received)?;
+
+    Ok(tx)
+}
+
+fn bad_request(message: String) -> HttpResponse {
+    HttpResponse::BadRequest().json(ErrorResponse { message })
+}
+
+fn to_koinu(amount: f64) -> Result<u64, String> {
+    Amount::from_btc(amount)
+        .map(|amount| amount.to_sat())
+        .map_err(|_| format!("invalid amount {}", amount))
+}
+
+#[async_trait(?Send)]
+impl ChainNode for DogecoinNode {
+    fn name(&self) -> &'static str {
+        "dogecoin"
+    }
+
+    async fn status(&self) -> Result<ChainStatus, HttpResponse> {
+        let payload = json!({ "jsonrpc": "2.0",  "method": "getblockchaininfo"});
+
+        let result =
+            match rpc_call::<DogecoinInfo>(&self.rq_client, &self.doge_rpc_cfg, &payload).await? {
+                DogecoinInfo {
+                    result: Some(result),
+                    ..
+                } => result,
+                DogecoinInfo {
+                    error: Some(err), ..
+                } => return Err(rpc_error_response(err)),
+                _ => return Err(empty_result_response()),
+            };
+
+        Ok(ChainStatus {
+            chain: self.name().to_string(),
+            network: result.chain,
+            blocks: result.blocks,
+            headers: result.headers,
+            best_block_hash: result.bestblockhash,
+            verification_progress: result.verificationprogress,
+            // older nodes do not report it
+            initial_block_download: result
+                .initialblockdownload
+                .unwrap_or(result.verificationprogress < 0.9999),
+            mweb: None,
+        })
+    }
+
+    // no estimatesmartfee on dogecoin, the target is ignored
+    async fn estimate_fee(&self, target: u16) -> Result<ChainFee, HttpResponse> {
+        let payload = json!({ "jsonrpc": "2.0",  "method": "getnetworkinfo"});
+
+        let relay_fee =
+            match rpc_call::<NetworkInfo>(&self.rq_client, &self.doge_rpc_cfg, &payload).await? {
+                NetworkInfo {
+                    result: Some(result),
+                    ..
+                } => result.relayfee,
+                NetworkInfo {
+                    error: Some(err), ..
+                } => return Err(rpc_error_response(err)),
+                _ => return Err(empty_result_response()),
+            };
+
+        Ok(ChainFee {
+            target,
+            fee_rate: self.fee_per_kb.max(relay_fee),
+        })
+    }
+
+    async fn build_tx(&self, request: &BuildTxRequest) -> Result<UnsignedTx, HttpResponse> {
+        let utxos = request.utxos.as_ref().unwrap();
+        for utxo in utxos {
+            let pk_script = Vec::<u8>::from_hex(utxo.pk_script.as_ref().unwrap())
+                .map(Script::from)
+                .map_err(|_| bad_request("failed to decode pk_script".to_string()))?;
+            if pk_script.is_witness_program() || utxo.witness_script.is_some() {
+                return Err(bad_request(
+                    "dogecoin supports legacy scripts only".to_string(),
+                ));
+            }
+        }
+
+        let outputs = request
+            .to
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|output| {
+                script_pubkey(output.address.as_ref().unwrap())
+                    .map(|script| (script, output.amount.unwrap()))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(bad_request)?;
+        let change_script =
+            script_pubkey(request.change_address.as_ref().unwrap()).map_err(bad_request)?;
+
+        // dust before the fee stays dust after it, no need to ask the node
+        let amounts = outputs
+            .iter()
+            .map(|(_, amount)| to_koinu(*amount))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(bad_request)?;
+        check_dust(&amounts).map_err(bad_request)?;
+
+        let fee = self.estimate_fee(1).await?;
+
+        let tx = unsigned_tx(utxos, &outputs, change_script, fee.fee_rate).map_err(bad_request)?;
+
+        Ok(UnsignedTx {
+            hex: consensus::encode::serialize_hex(&tx),
+            // dogecoin core has no psbt support
+            psbt: None,
+            fee_rate: fee.fee_rate,
+        })
+    }
+
+    async fn sign_tx(
+        &self,
+        raw_tx: &str,
+        private_keys: &[String],
+    ) -> Result<SignTxResult, HttpResponse> {
+        let payload = json!({ "jsonrpc": "2.0",  "method": "signrawtransaction", "params": [raw_tx, [], private_keys]});
+
+        match rpc_call::<SignTx>(&self.rq_client, &self.doge_rpc_cfg, &payload).await? {
+            SignTx {
+                error: Some(err), ..
+            } => Err(bad_request(err.message)),
+            SignTx {
+                result: Some(signed_tx),
+                ..
+            } => Ok(signed_tx),
+            _ => Err(empty_result_response()),
+        }
+    }
+
+    // no maxfeerate param on dogecoin
+    async fn broadcast(&self, signed_tx: &str) -> Result<Broadcast, HttpResponse> {
+        let payload =
+            json!({ "jsonrpc": "2.0",  "method": "sendrawtransaction", "params": [signed_tx]});
+
+        Ok(broadcast_payload(&self.rq_client, &self.doge_rpc_cfg, &payload).await)
+    }
+
+    async fn tx_status(&self, txid: &str) -> Result<TxStatus, HttpResponse> {
+        if Txid::from_hex(txid).is_err() {
+            return Err(bad_request("invalid txid".to_string()));
+        }
+
+        let payload =
+            json!({ "jsonrpc": "2.0",  "method": "getrawtransaction", "params": [txid, true]});
+
+        let raw_tx =
+            match rpc_call::<DogecoinTx>(&self.rq_client, &self.doge_rpc_cfg, &payload).await? {
+                DogecoinTx {
+                    result: Some(result),
+                    ..
+                } => result,
+                DogecoinTx {
+                    error: Some(err), ..
+                } => return Err(rpc_error_response(err)),
+                _ => return Err(empty_result_response()),
+            };
+
+        let confirmations = raw_tx.confirmations.unwrap_or(0);
+
+        if confirmations == 0 {
+            let payload =
+                json!({ "jsonrpc": "2.0",  "method": "getmempoolentry", "params": [txid]});
+
+            // an error means the tx left the mempool between the two calls
+            let fee = match rpc_call::<DogecoinMempoolEntry>(
+                &self.rq_client,
+                &self.doge_rpc_cfg,
+                &payload,
+            )
+            .await?
+            {
+                DogecoinMempoolEntry {
+                    result: Some(entry),
+                    ..
+                } => Some(entry.fee),
+                _ => None,
+            };
+
+            return Ok(TxStatus {
+                txid: raw_tx.txid,
+                in_mempool: fee.is_some(),
+                confirmations,
+                block_hash: None,
+                block_height: None,
+                fee,
+                replaceable: None,
+            });
+        }
+
+        let block_hash = raw_tx.blockhash.unwrap_or_default();
+        let payload =
+            json!({ "jsonrpc": "2.0",  "method": "getblockheader", "params": [block_hash]});
+
+        let block_height = match rpc_call::<DogecoinHeader>(
+            &self.rq_client,
+            &self.doge_rpc_cfg,
+            &payload,
+        )
+        .await?
+        {
+            DogecoinHeader {
+                result: Some(header),
+                ..
+            } => header.height,
+            DogecoinHeader {
+                error: Some(err), ..
+            } => return Err(rpc_error_response(err)),
+            _ => return Err(empty_result_response()),
+        };
+
+        Ok(TxStatus {
+            txid: raw_tx.txid,
+            in_mempool: false,
+            confirmations,
+            block_hash: Some(block_hash),
+            block_height: Some(block_height),
+            fee: None,
+            replaceable: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{hashes::hex::ToHex, util::base58};
+
+    use super::*;
+
+    #[test]
+    fn dogecoin_addresses() {
+        let hash = "751e76e8199196d454941c45d1b3a323f1433bd6";
+        let versioned = |version: u8| {
+            let mut data = vec![version];
+            data.extend(Vec::<u8>::from_hex(hash).unwrap());
+            base58::check_encode_slice(&data)
+        };
+
+        let p2pkh = versioned(0x1e);
+        assert!(p2pkh.starts_with('D'));
+        assert_eq!(
+            script_pubkey(&p2pkh).unwrap().to_hex(),
+            format!("76a914{}88ac", hash)
+        );
+
+        let p2sh = versioned(0x16);
+        assert!(p2sh.starts_with('9') || p2sh.starts_with('A'));
+        assert_eq!(
+            script_pubkey(&p2sh).unwrap().to_hex(),
+            format!("a914{}87", hash)
+        );
+
+        assert!(script_pubkey(&versioned(0x71)).is_ok());
+
+        // bitcoin mainnet p2pkh and any bech32
+        assert!(script_pubkey(&versioned(0x00)).is_err());
+        assert!(script_pubkey("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4").is_err());
+        assert!(script_pubkey("ltc1qw508d6qejxtdg4y5r3zarvary0c5xw7kgmn4n9").is_err());
+    }
+
+    #[test]
+    fn dogecoin_fee_and_dust() {
+        // 1 DOGE recipient, 5 DOGE change
+        let fee = kb_fee(0.01, &[100_000_000, 500_000_000]);
+        assert_eq!(fee(226), 0.01);
+        assert_eq!(fee(1000), 0.01);
+        assert_eq!(fee(1001), 0.02);
+
+        // soft dust recipient and change each add a fee per kb
+        let fee = kb_fee(0.01, &[500_000, 900_000]);
+        assert_eq!(fee(226), 0.03);
+
+        assert!(check_dust(&[100_000, 100_000_000]).is_ok());
+        assert!(check_dust(&[99_999, 100_000_000]).is_err());
+    }
+
+    #[test]
+    fn dogecoin_fee_from_recipients() {
+        let p2pkh = Script::from(
+            Vec::<u8>::from_hex("76a914751e76e8199196d454941c45d1b3a323f1433bd688ac").unwrap(),
+        );
+        let utxos = [Utxo {
+            tx_id: Some(
+                "989d301c546841d0ac5c8354c7d78079e3603b089682d1639b2ee1c1a8010c6a".to_string(),
+            ),
+            vout: Some(0),
+            amount: Some(10.0),
+            pk_script: Some(p2pkh.to_hex()),
+            witness_script: None,
+            leaf_script: None,
+            control_block: None,
+        }];
+        let build = |amount: f64, fee_per_kb: f64| {
+            unsigned_tx(
+                &utxos,
+                &[(p2pkh.clone(), amount)],
+                p2pkh.clone(),
+                fee_per_kb,
+            )
+        };
+
+        // 0.04 fee, 0.02 per kB plus one for the soft dust output, is more than it sends
+        assert_eq!(
+            build(0.005, 0.02).unwrap_err(),
+            "output amount too low to pay the fee"
+        );
+
+        // 0.0105 is above the dust limit before the 0.01 fee only
+        assert!(build(0.0105, 0.01).unwrap_err().contains("dust limit"));
+
+        let tx = build(1.0, 0.01).unwrap();
+        assert_eq!(tx.output[0].value, 99_000_000);
+        assert_eq!(tx.output[1].value, 900_000_000);
+
+        // no change output for empty or sub 1 DOGE change, it goes to the fee
+        let tx = build(10.0, 0.01).unwrap();
+        assert_eq!(tx.output.len(), 1);
+        assert_eq!(tx.output[0].value, 999_000_000);
+
+        let tx = build(9.5, 0.01).unwrap();
+        assert_eq!(tx.output.len(), 1);
+        assert_eq!(tx.output[0].value, 949_000_000);
+    }
+}
@@ -0,0 +1,25 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::btc::model::{BlockchainInfoResult, RPCError};
+
+#[derive(Deserialize, Serialize)]
+pub struct Softfork {
+    pub active: bool,
+    pub height: Option<usize>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct LitecoinInfoResult {
+    #[serde(flatten)]
+    pub info: BlockchainInfoResult,
+    // carries the mweb deployment
+    pub softforks: Option<HashMap<String, Softfork>>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct LitecoinInfo {
+    pub result: Option<LitecoinInfoResult>,
+    pub error: Option<RPCError>,
+}
@@ -0,0 +1,236 @@
+use actix_web::HttpResponse;
+use async_trait::async_trait;
+use base64::encode;
+use bitcoin::{consensus, hashes::hex::FromHex, Script, Txid};
+use serde_json::json;
+
+use crate::{
+    api::{
+        btc::{
+            handler::{
+                broadcast_tx, empty_result_response, estimate_fees, rpc_call, rpc_error_response,
+                sign_raw_transaction, transaction_status, ErrorResponse,
+            },
+            model::FeeEstimateMode,
+            service::{build_transaction, size_fee},
+        },
+        chain::{
+            address::{self, AddressParams},
+            handler::BuildTxRequest,
+            model::{
+                Broadcast, ChainFee, ChainStatus, MwebStatus, SignTxResult, TxStatus, UnsignedTx,
+            },
+            ChainNode,
+        },
+        ltc::model::LitecoinInfo,
+    },
+    config::BitcoinRpcConfig,
+    request::RequestClient,
+};
+
+// mainnet L/M (and legacy 3) with ltc1, testnet and regtest share the base58 versions
+const ADDRESS_PARAMS: [AddressParams; 3] = [
+    AddressParams {
+        p2pkh: 0x30,
+        p2sh: &[0x32, 0x05],
+        hrp: Some("ltc"),
+    },
+    AddressParams {
+        p2pkh: 0x6f,
+        p2sh: &[0x3a, 0xc4],
+        hrp: Some("tltc"),
+    },
+    AddressParams {
+        p2pkh: 0x6f,
+        p2sh: &[0x3a, 0xc4],
+        hrp: Some("rltc"),
+    },
+];
+
+// mweb outputs can only be created by the node wallet
+const MWEB_PREFIXES: [&str; 3] = ["ltcmweb1", "tmweb1", "mweb1"];
+
+const CREATE_TX_FEE_TARGET: u16 = 4;
+
+pub struct LitecoinNode {
+    rq_client: RequestClient,
+    ltc_rpc_cfg: BitcoinRpcConfig,
+}
+
+impl LitecoinNode {
+    pub fn new(rq_client: RequestClient, ltc_rpc_cfg: BitcoinRpcConfig) -> LitecoinNode {
+        LitecoinNode {
+            rq_client,
+            ltc_rpc_cfg,
+        }
+    }
+}
+
+pub fn script_pubkey(address: &str) -> Result<Script, String> {
+    let lowercase = address.to_lowercase();
+    if MWEB_PREFIXES
+        .iter()
+        .any(|prefix| lowercase.starts_with(prefix))
+    {
+        return Err("MWEB addresses are not supported, send from the node wallet".to_string());
+    }
+
+    address::script_pubkey(address, &ADDRESS_PARAMS)
+}
+
+fn bad_request(message: String) -> HttpResponse {
+    HttpResponse::BadRequest().json(ErrorResponse { message })
+}
+
+#[async_trait(?Send)]
+impl ChainNode for LitecoinNode {
+    fn name(&self) -> &'static str {
+        "litecoin"
+    }
+
+    async fn status(&self) -> Result<ChainStatus, HttpResponse> {
+        let payload = json!({ "jsonrpc": "2.0",  "method": "getblockchaininfo"});
+
+        let result =
+            match rpc_call::<LitecoinInfo>(&self.rq_client, &self.ltc_rpc_cfg, &payload).await? {
+                LitecoinInfo {
+                    result: Some(result),
+                    ..
+                } => result,
+                LitecoinInfo {
+                    error: Some(err), ..
+                } => return Err(rpc_error_response(err)),
+                _ => return Err(empty_result_response()),
+            };
+
+        let mweb = result
+            .softforks
+            .as_ref()
+            .and_then(|softforks| softforks.get("mweb"))
+            .map(|mweb| MwebStatus {
+                active: mweb.active,
+                activation_height: mweb.height,
+            });
+
+        Ok(ChainStatus {
+            chain: self.name().to_string(),
+            network: result.info.chain,
+            blocks: result.info.blocks,
+            headers: result.info.headers,
+            best_block_hash: result.info.bestblockhash,
+            verification_progress: result.info.verificationprogress,
+            initial_block_download: result.info.initialblockdownload,
+            mweb,
+        })
+    }
+
+    async fn estimate_fee(&self, target: u16) -> Result<ChainFee, HttpResponse> {
+        let mut estimates = estimate_fees(
+            &self.rq_client,
+            &self.ltc_rpc_cfg,
+            &[target],
+            &[FeeEstimateMode::Conservative],
+        )
+        .await?;
+
+        Ok(ChainFee {
+            target,
+            fee_rate: estimates.remove(0).btc_per_kvb,
+        })
+    }
+
+    async fn build_tx(&self, request: &BuildTxRequest) -> Result<UnsignedTx, HttpResponse> {
+        let outputs = request
+            .to
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|output| {
+                script_pubkey(output.address.as_ref().unwrap())
+                    .map(|script| (script, output.amount.unwrap()))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(bad_request)?;
+        let change_script =
+            script_pubkey(request.change_address.as_ref().unwrap()).map_err(bad_request)?;
+
+        let fee = self.estimate_fee(CREATE_TX_FEE_TARGET).await?;
+
+        let psbt = build_transaction(
+            request.utxos.as_ref().unwrap(),
+            &outputs,
+            change_script,
+            size_fee(fee.fee_rate),
+        )
+        .map_err(|err| bad_request(err.to_string()))?;
+
+        Ok(UnsignedTx {
+            hex: consensus::encode::serialize_hex(&psbt.unsigned_tx),
+            psbt: Some(encode(consensus::encode::serialize(&psbt))),
+            fee_rate: fee.fee_rate,
+        })
+    }
+
+    async fn sign_tx(
+        &self,
+        raw_tx: &str,
+        private_keys: &[String],
+    ) -> Result<SignTxResult, HttpResponse> {
+        sign_raw_transaction(&self.rq_client, &self.ltc_rpc_cfg, raw_tx, private_keys).await
+    }
+
+    async fn broadcast(&self, signed_tx: &str) -> Result<Broadcast, HttpResponse> {
+        Ok(broadcast_tx(&self.rq_client, &self.ltc_rpc_cfg, signed_tx).await)
+    }
+
+    async fn tx_status(&self, txid: &str) -> Result<TxStatus, HttpResponse> {
+        if Txid::from_hex(txid).is_err() {
+            return Err(bad_request("invalid txid".to_string()));
+        }
+
+        transaction_status(&self.rq_client, &self.ltc_rpc_cfg, txid.to_string()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{hashes::hex::ToHex, util::base58};
+
+    use super::*;
+
+    #[test]
+    fn litecoin_addresses() {
+        let hash = "751e76e8199196d454941c45d1b3a323f1433bd6";
+        let versioned = |version: u8| {
+            let mut data = vec![version];
+            data.extend(Vec::<u8>::from_hex(hash).unwrap());
+            base58::check_encode_slice(&data)
+        };
+
+        let p2pkh = versioned(0x30);
+        assert!(p2pkh.starts_with('L'));
+        assert_eq!(
+            script_pubkey(&p2pkh).unwrap().to_hex(),
+            format!("76a914{}88ac", hash)
+        );
+
+        let p2sh = versioned(0x32);
+        assert!(p2sh.starts_with('M'));
+        assert_eq!(
+            script_pubkey(&p2sh).unwrap().to_hex(),
+            format!("a914{}87", hash)
+        );
+
+        assert_eq!(
+            script_pubkey("ltc1qw508d6qejxtdg4y5r3zarvary0c5xw7kgmn4n9")
+                .unwrap()
+                .to_hex(),
+            format!("0014{}", hash)
+        );
+
+        // bitcoin mainnet p2pkh and bech32
+        assert!(script_pubkey(&versioned(0x00)).is_err());
+        assert!(script_pubkey("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4").is_err());
+        assert!(script_pubkey("ltcmweb1qq0000").is_err());
+    }
+}
@@ -2,7 +2,10 @@ use actix_web::HttpResponse;
 use serde::Serialize;
 
 pub mod btc;
+pub mod chain;
+pub mod doge;
 pub mod health;
+pub mod ltc;
 
 pub use btc::handler::init as init_bitcoin_handler;
 pub use health::handler::init as init_health_handler;
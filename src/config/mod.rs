@@ -5,8 +5,19 @@ use std::env;
 pub struct Config {
     pub port: u16,
     pub environment: String,
+    // directory of the embedded database
+    pub storage_path: String,
+    // chains served under /api/{chain}
+    pub chains: Vec<String>,
 
-    pub bitcoin_rpc_config: BitcoinRpcConfig,
+    // set when bitcoin is in chains
+    pub bitcoin_rpc_config: Option<BitcoinRpcConfig>,
+    // litecoin core speaks the bitcoin core rpc dialect, set when litecoin is in chains
+    pub litecoin_rpc_config: Option<BitcoinRpcConfig>,
+    // set when dogecoin is in chains, max fee rate is unused as dogecoin core has no maxfeerate
+    pub dogecoin_rpc_config: Option<BitcoinRpcConfig>,
+    // DOGE per started kB, dogecoin core has no fee estimation
+    pub dogecoin_fee_per_kb: f64,
 }
 
 #[derive(Default, Debug, Clone)]
@@ -14,6 +25,362 @@ pub struct BitcoinRpcConfig {
     pub bitcoin_rpc_user: String,
     pub bitcoin_rpc_password: String,
     pub bitcoin_rpc_url_one: String,
+    // all nodes, bitcoin_rpc_url_one first, used for tx broadcast
+    pub bitcoin_rpc_urls: Vec<String>,
+    // rest url of each node in bitcoin_rpc_urls order, None when the node has no rest=1,
+    // read endpoints use it instead of json-rpc
+    pub bitcoin_rest_urls: Vec<Option<String>>,
+    // zmq endpoints of the nodes in bitcoin_rpc_urls order, notifications are disabled
+    // when empty
+    pub bitcoin_zmq_urls: Vec<String>,
+    // BTC/kvB, txs paying more than this are rejected by the node
+    pub bitcoin_max_fee_rate: f64,
+    // confirmation targets reported by the fees endpoint
+    pub bitcoin_fee_targets: Vec<u16>,
+    // local address index, follows the chain of bitcoin_rpc_url_one
+    pub bitcoin_indexer_enabled: bool,
+    // first block indexed on a fresh database
+    pub bitcoin_indexer_start_height: u64,
+    // mnemonic and private key endpoints, off in production unless enabled explicitly
+    pub bitcoin_key_tools_enabled: bool,
+    // methods the rpc passthrough forwards, "*" allows everything not denied
+    pub bitcoin_rpc_allowlist: Vec<String>,
+    // methods never forwarded, takes precedence over the allowlist, always includes the
+    // built-in ones
+    pub bitcoin_rpc_denylist: Vec<String>,
+    // keys of the rpc passthrough, disabled when empty
+    pub bitcoin_rpc_api_keys: Vec<RpcApiKey>,
+    // webhook callbacks may point at loopback and private hosts
+    pub bitcoin_webhook_allow_private: bool,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct RpcApiKey {
+    // reported in the audit log instead of the key
+    pub name: String,
+    pub key: String,
+    // "*" for every allowlisted method
+    pub methods: Vec<String>,
+}
+
+const DEFAULT_RPC_ALLOWLIST: [&str; 18] = [
+    "getbestblockhash",
+    "getblock",
+    "getblockchaininfo",
+    "getblockcount",
+    "getblockhash",
+    "getblockheader",
+    "getblockstats",
+    "getchaintips",
+    "getdifficulty",
+    "getmempoolentry",
+    "getmempoolinfo",
+    "getrawmempool",
+    "gettxout",
+    "getrawtransaction",
+    "decoderawtransaction",
+    "decodescript",
+    "estimatesmartfee",
+    "getnetworkinfo",
+];
+
+const DEFAULT_RPC_DENYLIST: [&str; 92] = [
+    // node control
+    "stop",
+    "setnetworkactive",
+    "addnode",
+    "disconnectnode",
+    "setban",
+    "clearbanned",
+    "logging",
+    "pruneblockchain",
+    "invalidateblock",
+    "reconsiderblock",
+    "preciousblock",
+    "prioritisetransaction",
+    "setmocktime",
+    // mining
+    "submitblock",
+    "generatetoaddress",
+    "generateblock",
+    "generatetodescriptor",
+    // relay, /send-tx and /test-tx cap the fee rate, the raw methods take any maxfeerate
+    "sendrawtransaction",
+    "testmempoolaccept",
+    "submitpackage",
+    // reads or writes files on the node
+    "dumptxoutset",
+    "loadtxoutset",
+    "savemempool",
+    "importmempool",
+    // the whole wallet category: key exports, spending and wallet loading
+    "abandontransaction",
+    "abortrescan",
+    "addmultisigaddress",
+    "backupwallet",
+    "bumpfee",
+    "createwallet",
+    "createwalletdescriptor",
+    "dumpprivkey",
+    "dumpwallet",
+    "encryptwallet",
+    "getaddressesbylabel",
+    "getaddressinfo",
+    "getbalance",
+    "getbalances",
+    "gethdkeys",
+    "getnewaddress",
+    "getrawchangeaddress",
+    "getreceivedbyaddress",
+    "getreceivedbylabel",
+    "gettransaction",
+    "getunconfirmedbalance",
+    "getwalletinfo",
+    "importaddress",
+    "importdescriptors",
+    "importmulti",
+    "importprivkey",
+    "importprunedfunds",
+    "importpubkey",
+    "importwallet",
+    "keypoolrefill",
+    "listaddressgroupings",
+    "listdescriptors",
+    "listlabels",
+    "listlockunspent",
+    "listreceivedbyaddress",
+    "listreceivedbylabel",
+    "listsinceblock",
+    "listtransactions",
+    "listunspent",
+    "listwalletdir",
+    "listwallets",
+    "loadwallet",
+    "lockunspent",
+    "migratewallet",
+    "newkeypool",
+    "psbtbumpfee",
+    "removeprunedfunds",
+    "rescanblockchain",
+    "restorewallet",
+    "send",
+    "sendall",
+    "sendmany",
+    "sendtoaddress",
+    "sethdseed",
+    "setlabel",
+    "settxfee",
+    "setwalletflag",
+    "signmessage",
+    "signrawtransactionwithwallet",
+    "simulaterawtransaction",
+    "unloadwallet",
+    "upgradewallet",
+    "walletcreatefundedpsbt",
+    "walletdisplayaddress",
+    "walletlock",
+    "walletpassphrase",
+    "walletpassphrasechange",
+    "walletprocesspsbt",
+];
+
+fn method_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|method| method.trim().to_lowercase())
+        .filter(|method| !method.is_empty())
+        .collect()
+}
+
+// name:key:method|method entries separated by commas, "*" allows every method
+fn parse_rpc_api_keys(keys: &str) -> Vec<RpcApiKey> {
+    keys.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let mut parts = entry.splitn(3, ':');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(name), Some(key), Some(methods)) if !name.is_empty() && !key.is_empty() => {
+                    RpcApiKey {
+                        name: name.to_string(),
+                        key: key.to_string(),
+                        methods: methods
+                            .split('|')
+                            .map(|method| method.trim().to_lowercase())
+                            .filter(|method| !method.is_empty())
+                            .collect(),
+                    }
+                }
+                _ => panic!("Can't parse bitcoin rpc api key, expected name:key:methods"),
+            }
+        })
+        .collect()
+}
+
+impl BitcoinRpcConfig {
+    // rest url of the node behind rpc_url
+    pub fn rest_url(&self, rpc_url: &str) -> Option<&str> {
+        let node = self
+            .bitcoin_rpc_urls
+            .iter()
+            .position(|url| url == rpc_url)?;
+        self.bitcoin_rest_urls.get(node)?.as_deref()
+    }
+
+    // rpc url of the node behind zmq_url, endpoints past the rpc nodes go to the first one
+    pub fn zmq_rpc_url(&self, zmq_url: &str) -> &str {
+        self.bitcoin_zmq_urls
+            .iter()
+            .position(|url| url == zmq_url)
+            .and_then(|node| self.bitcoin_rpc_urls.get(node))
+            .unwrap_or(&self.bitcoin_rpc_url_one)
+    }
+}
+
+// node settings of bitcoin and its forks, read from the {prefix}_ variables, e.g.
+// BITCOIN_RPC_URL or LITECOIN_RPC_URL
+fn core_rpc_config(prefix: &str, environment: &str) -> BitcoinRpcConfig {
+    let var = |name: &str| env::var(format!("{}_{}", prefix, name));
+    let chain = prefix.to_lowercase();
+
+    let bitcoin_rpc_user = match var("RPC_USER") {
+        Ok(user) => user,
+        Err(_) => panic!("incorrect {} rpc user", chain),
+    };
+
+    let bitcoin_rpc_password = match var("RPC_PASSWORD") {
+        Ok(password) => password,
+        Err(_) => panic!("incorrect {} rpc password", chain),
+    };
+
+    let bitcoin_rpc_url_one = match var("RPC_URL") {
+        Ok(url) => url,
+        Err(_) => panic!("incorrect {} rpc url", chain),
+    };
+
+    // optional comma separated list of additional nodes
+    let mut bitcoin_rpc_urls = vec![bitcoin_rpc_url_one.clone()];
+    if let Ok(urls) = var("RPC_URLS") {
+        for url in urls.split(',').map(str::trim) {
+            if !url.is_empty() && !bitcoin_rpc_urls.iter().any(|u| u == url) {
+                bitcoin_rpc_urls.push(url.to_string());
+            }
+        }
+    }
+
+    // one entry per node in bitcoin_rpc_urls order, empty for nodes without rest
+    let bitcoin_rest_urls: Vec<Option<String>> = match var("REST_URLS") {
+        Ok(urls) => {
+            let urls: Vec<Option<String>> = urls
+                .split(',')
+                .map(str::trim)
+                .map(|url| (!url.is_empty()).then(|| url.to_string()))
+                .collect();
+            if urls.len() > bitcoin_rpc_urls.len() {
+                panic!("{} rest urls must not outnumber the rpc nodes", chain);
+            }
+            urls
+        }
+        Err(_) => Vec::new(),
+    };
+
+    let bitcoin_zmq_urls: Vec<String> = match var("ZMQ_URLS") {
+        Ok(urls) => urls
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(String::from)
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    let bitcoin_max_fee_rate: f64 = match var("MAX_FEE_RATE") {
+        Ok(rate) => rate
+            .parse()
+            .unwrap_or_else(|_| panic!("Can't parse {} max fee rate into number", chain)),
+        Err(_) => 0.1,
+    };
+
+    let bitcoin_fee_targets: Vec<u16> = match var("FEE_TARGETS") {
+        Ok(targets) => targets
+            .split(',')
+            .map(|t| {
+                let target: u16 = t
+                    .trim()
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Can't parse {} fee target into number", chain));
+                // estimatesmartfee only accepts 1 to 1008 blocks
+                if !(1..=1008).contains(&target) {
+                    panic!("{} fee target {} must be between 1 and 1008", chain, target);
+                }
+                target
+            })
+            .collect(),
+        Err(_) => vec![1, 2, 3, 6, 12, 24, 144],
+    };
+
+    let bitcoin_indexer_enabled = match var("INDEXER_ENABLED") {
+        Ok(enabled) => enabled == "true" || enabled == "1",
+        Err(_) => false,
+    };
+
+    let bitcoin_indexer_start_height: u64 = match var("INDEXER_START_HEIGHT") {
+        Ok(height) => height
+            .parse()
+            .unwrap_or_else(|_| panic!("Can't parse {} indexer start height into number", chain)),
+        Err(_) => 0,
+    };
+
+    let bitcoin_key_tools_enabled = match var("KEY_TOOLS_ENABLED") {
+        Ok(enabled) => enabled == "true" || enabled == "1",
+        Err(_) => environment != "production",
+    };
+
+    let bitcoin_rpc_allowlist = match var("RPC_ALLOWLIST") {
+        Ok(methods) => method_list(&methods),
+        Err(_) => DEFAULT_RPC_ALLOWLIST
+            .iter()
+            .map(|m| m.to_string())
+            .collect(),
+    };
+
+    // the built-in methods stay denied, the variable only adds to them
+    let mut bitcoin_rpc_denylist: Vec<String> =
+        DEFAULT_RPC_DENYLIST.iter().map(|m| m.to_string()).collect();
+    if let Ok(methods) = var("RPC_DENYLIST") {
+        for method in method_list(&methods) {
+            if !bitcoin_rpc_denylist.contains(&method) {
+                bitcoin_rpc_denylist.push(method);
+            }
+        }
+    }
+
+    let bitcoin_rpc_api_keys = match var("RPC_API_KEYS") {
+        Ok(keys) => parse_rpc_api_keys(&keys),
+        Err(_) => Vec::new(),
+    };
+
+    let bitcoin_webhook_allow_private = match var("WEBHOOK_ALLOW_PRIVATE") {
+        Ok(allowed) => allowed == "true" || allowed == "1",
+        Err(_) => false,
+    };
+
+    BitcoinRpcConfig {
+        bitcoin_rpc_user,
+        bitcoin_rpc_password,
+        bitcoin_rpc_url_one,
+        bitcoin_rpc_urls,
+        bitcoin_rest_urls,
+        bitcoin_zmq_urls,
+        bitcoin_max_fee_rate,
+        bitcoin_fee_targets,
+        bitcoin_indexer_enabled,
+        bitcoin_indexer_start_height,
+        bitcoin_key_tools_enabled,
+        bitcoin_rpc_allowlist,
+        bitcoin_rpc_denylist,
+        bitcoin_rpc_api_keys,
+        bitcoin_webhook_allow_private,
+    }
 }
 
 impl Config {
@@ -32,29 +399,51 @@ impl Config {
             Err(_) => panic!("incorrect app_env"),
         };
 
-        let bitcoin_rpc_user = match env::var("BITCOIN_RPC_USER") {
-            Ok(environment) => environment,
-            Err(_) => panic!("incorrect bitcoin rpc user"),
+        let storage_path = match env::var("STORAGE_PATH") {
+            Ok(storage_path) => storage_path,
+            Err(_) => "data/multi-nodes".to_string(),
         };
 
-        let bitcoin_rpc_password = match env::var("BITCOIN_RPC_PASSWORD") {
-            Ok(environment) => environment,
-            Err(_) => panic!("incorrect bitcoin rpc password"),
+        let chains: Vec<String> = match env::var("CHAINS") {
+            Ok(chains) => chains
+                .split(',')
+                .map(|chain| chain.trim().to_lowercase())
+                .filter(|chain| !chain.is_empty())
+                .collect(),
+            Err(_) => vec!["bitcoin".to_string()],
         };
 
-        let bitcoin_rpc_url_one = match env::var("BITCOIN_RPC_URL") {
-            Ok(environment) => environment,
-            Err(_) => panic!("incorrect bitcoin rpc url"),
+        let bitcoin_rpc_config = chains
+            .iter()
+            .any(|chain| chain == "bitcoin")
+            .then(|| core_rpc_config("BITCOIN", &environment));
+
+        let litecoin_rpc_config = chains
+            .iter()
+            .any(|chain| chain == "litecoin")
+            .then(|| core_rpc_config("LITECOIN", &environment));
+
+        let dogecoin_rpc_config = chains
+            .iter()
+            .any(|chain| chain == "dogecoin")
+            .then(|| core_rpc_config("DOGECOIN", &environment));
+
+        let dogecoin_fee_per_kb: f64 = match env::var("DOGECOIN_FEE_PER_KB") {
+            Ok(fee) => fee
+                .parse()
+                .expect("Can't parse dogecoin fee per kb into number"),
+            Err(_) => 0.01,
         };
 
         Config {
             port,
             environment,
-            bitcoin_rpc_config: BitcoinRpcConfig {
-                bitcoin_rpc_user,
-                bitcoin_rpc_password,
-                bitcoin_rpc_url_one,
-            },
+            storage_path,
+            chains,
+            bitcoin_rpc_config,
+            litecoin_rpc_config,
+            dogecoin_rpc_config,
+            dogecoin_fee_per_kb,
         }
     }
 }
@@ -66,19 +455,101 @@ mod tests {
     #[test]
     fn create_config() {
         let c = Config::init();
-        assert_eq!(c.port > 0, true);
+        assert!(c.port > 0);
+        assert!(!c.chains.is_empty());
+        assert_eq!(
+            c.litecoin_rpc_config.is_some(),
+            c.chains.iter().any(|chain| chain == "litecoin")
+        );
+        assert_eq!(
+            c.dogecoin_rpc_config.is_some(),
+            c.chains.iter().any(|chain| chain == "dogecoin")
+        );
+        assert!(c.dogecoin_fee_per_kb > 0.0);
         assert_eq!(c.environment, env::var("APP_ENV").unwrap());
         assert_eq!(
-            c.bitcoin_rpc_config.bitcoin_rpc_user,
-            env::var("BITCOIN_RPC_USER").unwrap()
+            c.bitcoin_rpc_config.is_some(),
+            c.chains.iter().any(|chain| chain == "bitcoin")
         );
+
+        let btc = match c.bitcoin_rpc_config {
+            Some(btc) => btc,
+            None => return,
+        };
+        assert_eq!(btc.bitcoin_rpc_user, env::var("BITCOIN_RPC_USER").unwrap());
         assert_eq!(
-            c.bitcoin_rpc_config.bitcoin_rpc_password,
+            btc.bitcoin_rpc_password,
             env::var("BITCOIN_RPC_PASSWORD").unwrap()
         );
         assert_eq!(
-            c.bitcoin_rpc_config.bitcoin_rpc_url_one,
+            btc.bitcoin_rpc_url_one,
             env::var("BITCOIN_RPC_URL").unwrap()
         );
+        assert_eq!(btc.bitcoin_rpc_urls[0], btc.bitcoin_rpc_url_one);
+        assert!(btc.bitcoin_max_fee_rate > 0.0);
+        assert!(!btc.bitcoin_fee_targets.is_empty());
+        assert!(btc
+            .bitcoin_fee_targets
+            .iter()
+            .all(|target| (1..=1008).contains(target)));
+        if env::var("BITCOIN_KEY_TOOLS_ENABLED").is_err() {
+            assert_eq!(btc.bitcoin_key_tools_enabled, c.environment != "production");
+        }
+        if env::var("BITCOIN_WEBHOOK_ALLOW_PRIVATE").is_err() {
+            assert!(!btc.bitcoin_webhook_allow_private);
+        }
+        assert!(DEFAULT_RPC_DENYLIST
+            .iter()
+            .all(|method| btc.bitcoin_rpc_denylist.contains(&method.to_string())));
+        for method in [
+            "listdescriptors",
+            "sendtoaddress",
+            "dumptxoutset",
+            "loadwallet",
+            "submitblock",
+            "sendrawtransaction",
+        ] {
+            assert!(btc.bitcoin_rpc_denylist.contains(&method.to_string()));
+        }
+    }
+
+    #[test]
+    fn prefixed_core_config() {
+        for (name, value) in [
+            ("TESTCOIN_RPC_USER", "user"),
+            ("TESTCOIN_RPC_PASSWORD", "password"),
+            ("TESTCOIN_RPC_URL", "http://127.0.0.1:1"),
+            (
+                "TESTCOIN_RPC_URLS",
+                "http://127.0.0.1:2, http://127.0.0.1:1",
+            ),
+            ("TESTCOIN_ZMQ_URLS", "tcp://127.0.0.1:3"),
+            ("TESTCOIN_FEE_TARGETS", "2,6"),
+        ] {
+            env::set_var(name, value);
+        }
+
+        let c = core_rpc_config("TESTCOIN", "production");
+        assert_eq!(c.bitcoin_rpc_user, "user");
+        assert_eq!(
+            c.bitcoin_rpc_urls,
+            vec!["http://127.0.0.1:1", "http://127.0.0.1:2"]
+        );
+        assert_eq!(c.bitcoin_zmq_urls, vec!["tcp://127.0.0.1:3"]);
+        assert_eq!(c.bitcoin_fee_targets, vec![2, 6]);
+        assert_eq!(c.bitcoin_max_fee_rate, 0.1);
+        assert!(!c.bitcoin_key_tools_enabled);
+        assert_eq!(c.bitcoin_rpc_denylist.len(), DEFAULT_RPC_DENYLIST.len());
+    }
+
+    #[test]
+    fn parse_api_keys() {
+        let keys = parse_rpc_api_keys("ops:secret:getblock|GetBlockCount, admin:other:*");
+
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].name, "ops");
+        assert_eq!(keys[0].methods, vec!["getblock", "getblockcount"]);
+        assert_eq!(keys[1].key, "other");
+        assert_eq!(keys[1].methods, vec!["*"]);
     }
 }
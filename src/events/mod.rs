@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use bitcoin::{Block, BlockHash, Transaction, Txid};
+use tokio::sync::broadcast;
+
+pub mod tip;
+pub mod zmq;
+
+const EVENT_BUS_CAPACITY: usize = 1024;
+
+#[derive(Clone, Debug)]
+pub enum NodeEvent {
+    // hashblock
+    BlockHash {
+        node: String,
+        hash: BlockHash,
+    },
+    // rawblock
+    Block {
+        node: String,
+        block: Arc<Block>,
+    },
+    // rawtx, sent for mempool acceptance and again for every tx of a connected block
+    Tx {
+        node: String,
+        tx: Arc<Transaction>,
+    },
+    // sequence
+    Sequence {
+        node: String,
+        sequence: SequenceEvent,
+    },
+    // detected by the tip tracker
+    Reorg {
+        node: String,
+        reorg: Arc<tip::Reorg>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SequenceEvent {
+    BlockConnected(BlockHash),
+    BlockDisconnected(BlockHash),
+    TxAdded { txid: Txid, mempool_sequence: u64 },
+    TxRemoved { txid: Txid, mempool_sequence: u64 },
+}
+
+// in-process fan-out of node notifications, slow subscribers lose the oldest events
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<NodeEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        EventBus::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> EventBus {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+
+        EventBus { sender }
+    }
+
+    pub fn publish(&self, event: NodeEvent) {
+        // sending only fails when nobody listens
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<NodeEvent> {
+        self.sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::hashes::Hash;
+
+    #[actix_web::test]
+    async fn publish_subscribe() {
+        let bus = EventBus::new();
+        let mut receiver = bus.subscribe();
+
+        bus.publish(NodeEvent::BlockHash {
+            node: "node".to_string(),
+            hash: BlockHash::all_zeros(),
+        });
+
+        match receiver.recv().await.unwrap() {
+            NodeEvent::BlockHash { node, hash } => {
+                assert_eq!(node, "node");
+                assert_eq!(hash, BlockHash::all_zeros());
+            }
+            _ => panic!("unexpected event"),
+        }
+    }
+}
@@ -0,0 +1,370 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use actix_web::rt;
+use bitcoin::{hashes::hex::FromHex, Block, BlockHash, Txid};
+use log::warn;
+use serde::Serialize;
+use serde_json::json;
+use tokio::sync::broadcast::error::RecvError;
+
+use super::{EventBus, NodeEvent};
+use crate::{
+    api::btc::{handler::rpc_call_url, model::GetBlockHash},
+    config::BitcoinRpcConfig,
+    request::RequestClient,
+};
+
+// heights remembered per node, deeper reorgs are reported from the oldest known block
+const TRACKED_BLOCKS: usize = 100;
+const REORG_HISTORY: usize = 100;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Reorg {
+    pub node: String,
+    pub old_tip: String,
+    pub old_tip_height: u64,
+    pub new_tip: String,
+    pub new_tip_height: u64,
+    // number of blocks removed from the active chain
+    pub depth: u64,
+    pub orphaned_blocks: Vec<String>,
+    // txs of the orphaned blocks which are not in the new tip
+    pub affected_txids: Vec<String>,
+    pub detected_at: u64,
+}
+
+struct TrackedBlock {
+    hash: BlockHash,
+    txids: Vec<Txid>,
+}
+
+#[derive(Default)]
+struct TipState {
+    chains: HashMap<String, BTreeMap<u64, TrackedBlock>>,
+    history: VecDeque<Reorg>,
+}
+
+// remembers recent blocks of every node and reports when a node switches branch
+#[derive(Clone, Default)]
+pub struct TipTracker {
+    state: Arc<Mutex<TipState>>,
+}
+
+impl TipTracker {
+    pub fn new() -> TipTracker {
+        TipTracker::default()
+    }
+
+    // tracked blocks below the parent of the block, newest first, which may be on an
+    // abandoned branch. empty when the parent is the tracked one
+    pub fn suspect_blocks(&self, node: &str, block: &Block) -> Vec<(u64, BlockHash)> {
+        let parent_height = match block
+            .bip34_block_height()
+            .ok()
+            .and_then(|height| height.checked_sub(1))
+        {
+            Some(height) => height,
+            None => return Vec::new(),
+        };
+
+        let state = self.state.lock().unwrap();
+        let chain = match state.chains.get(node) {
+            Some(chain) => chain,
+            None => return Vec::new(),
+        };
+
+        match chain.get(&parent_height) {
+            Some(parent) if parent.hash != block.header.prev_blockhash => chain
+                .range(..parent_height)
+                .rev()
+                .map(|(height, b)| (*height, b.hash))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    // ancestors are hashes of the node's active chain below the parent, the walk back
+    // stops at the first height that matches or is missing
+    pub fn block_connected(
+        &self,
+        node: &str,
+        block: &Block,
+        ancestors: &HashMap<u64, BlockHash>,
+    ) -> Option<Reorg> {
+        let height = block.bip34_block_height().ok()?;
+        let hash = block.block_hash();
+
+        let mut state = self.state.lock().unwrap();
+        let chain = state.chains.entry(node.to_string()).or_default();
+
+        if chain.get(&height).map(|known| known.hash) == Some(hash) {
+            return None;
+        }
+
+        let mut orphaned: Vec<(u64, TrackedBlock)> = Vec::new();
+        let replaced: Vec<u64> = chain.range(height..).map(|(h, _)| *h).collect();
+        for h in replaced {
+            orphaned.extend(chain.remove(&h).map(|b| (h, b)));
+        }
+
+        // missed notifications, drop the tracked blocks down to the common ancestor
+        let mut ancestor_height = height.checked_sub(1);
+        while let Some(h) = ancestor_height {
+            let ancestor = if h + 1 == height {
+                Some(block.header.prev_blockhash)
+            } else {
+                ancestors.get(&h).copied()
+            };
+
+            match (chain.get(&h), ancestor) {
+                (Some(known), Some(ancestor)) if known.hash != ancestor => {
+                    orphaned.extend(chain.remove(&h).map(|b| (h, b)));
+                }
+                _ => break,
+            }
+            ancestor_height = h.checked_sub(1);
+        }
+
+        chain.insert(
+            height,
+            TrackedBlock {
+                hash,
+                txids: block.txdata.iter().map(|tx| tx.txid()).collect(),
+            },
+        );
+        while chain.len() > TRACKED_BLOCKS {
+            chain.pop_first();
+        }
+
+        if orphaned.is_empty() {
+            return None;
+        }
+
+        orphaned.sort_by_key(|(h, _)| *h);
+        let (old_tip_height, old_tip) = orphaned.last().map(|(h, b)| (*h, b.hash))?;
+
+        let new_txids: HashSet<Txid> = block.txdata.iter().map(|tx| tx.txid()).collect();
+        let affected_txids = orphaned
+            .iter()
+            // coinbase txs can never come back
+            .flat_map(|(_, b)| b.txids.iter().skip(1))
+            .filter(|txid| !new_txids.contains(*txid))
+            .map(|txid| txid.to_string())
+            .collect();
+
+        let reorg = Reorg {
+            node: node.to_string(),
+            old_tip: old_tip.to_string(),
+            old_tip_height,
+            new_tip: hash.to_string(),
+            new_tip_height: height,
+            depth: orphaned.len() as u64,
+            orphaned_blocks: orphaned.iter().map(|(_, b)| b.hash.to_string()).collect(),
+            affected_txids,
+            detected_at: crate::webhooks::now(),
+        };
+
+        state.history.push_front(reorg.clone());
+        state.history.truncate(REORG_HISTORY);
+
+        Some(reorg)
+    }
+
+    // newest first
+    pub fn history(&self) -> Vec<Reorg> {
+        self.state.lock().unwrap().history.iter().cloned().collect()
+    }
+}
+
+// active chain hashes of the node at the suspect heights, down to the first match
+async fn active_ancestors(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    rpc_url: &str,
+    suspects: Vec<(u64, BlockHash)>,
+) -> HashMap<u64, BlockHash> {
+    let mut ancestors = HashMap::new();
+    for (height, tracked) in suspects {
+        let payload = json!({ "jsonrpc": "2.0",  "method": "getblockhash", "params": [height]});
+
+        let hash =
+            match rpc_call_url::<GetBlockHash>(rq_client, btc_rpc_cfg, rpc_url, &payload).await {
+                Ok(GetBlockHash {
+                    result: Some(hash), ..
+                }) => BlockHash::from_hex(&hash).ok(),
+                _ => None,
+            };
+
+        match hash {
+            Some(hash) => {
+                ancestors.insert(height, hash);
+                if hash == tracked {
+                    break;
+                }
+            }
+            None => break,
+        }
+    }
+    ancestors
+}
+
+pub fn spawn_tip_tracker(
+    tracker: TipTracker,
+    bus: EventBus,
+    rq_client: RequestClient,
+    btc_rpc_cfg: BitcoinRpcConfig,
+) {
+    let mut events = bus.subscribe();
+
+    rt::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(NodeEvent::Block { node, block }) => {
+                    let suspects = tracker.suspect_blocks(&node, &block);
+                    let ancestors = if suspects.is_empty() {
+                        HashMap::new()
+                    } else {
+                        let rpc_url = btc_rpc_cfg.zmq_rpc_url(&node);
+                        active_ancestors(&rq_client, &btc_rpc_cfg, rpc_url, suspects).await
+                    };
+
+                    if let Some(reorg) = tracker.block_connected(&node, &block, &ancestors) {
+                        warn!(
+                            "reorg of depth {} on {}, new tip {}",
+                            reorg.depth, reorg.node, reorg.new_tip
+                        );
+                        bus.publish(NodeEvent::Reorg {
+                            node,
+                            reorg: Arc::new(reorg),
+                        });
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("tip tracker skipped {} node events", skipped)
+                }
+                Err(RecvError::Closed) => return,
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::{
+        blockdata::{constants::genesis_block, script::Builder},
+        hashes::Hash,
+        Network, OutPoint, PackedLockTime, Script, Sequence, Transaction, TxIn, TxOut, Witness,
+    };
+
+    fn block(height: i64, prev: BlockHash, nonce: u32, txs: Vec<Transaction>) -> Block {
+        let mut block = genesis_block(Network::Testnet);
+        block.header.version = 2;
+        block.header.prev_blockhash = prev;
+        block.header.nonce = nonce;
+        block.txdata = vec![Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Builder::new().push_int(height).into_script(),
+                sequence: Sequence::MAX,
+                witness: Witness::default(),
+            }],
+            output: vec![TxOut {
+                value: 0,
+                script_pubkey: Script::new(),
+            }],
+        }];
+        block.txdata.extend(txs);
+        block
+    }
+
+    fn tx(value: u64) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![],
+            output: vec![TxOut {
+                value,
+                script_pubkey: Script::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn detects_reorg() {
+        let tracker = TipTracker::new();
+
+        let a100 = block(100, BlockHash::all_zeros(), 0, vec![]);
+        let a101 = block(101, a100.block_hash(), 0, vec![tx(1), tx(2)]);
+        let a102 = block(102, a101.block_hash(), 0, vec![]);
+        for b in [&a100, &a101, &a102, &a102] {
+            assert!(tracker
+                .block_connected("node", b, &HashMap::new())
+                .is_none());
+        }
+
+        // competing branch from a100, tx(1) made it into the new block
+        let b101 = block(101, a100.block_hash(), 1, vec![tx(1)]);
+        assert!(tracker.suspect_blocks("node", &b101).is_empty());
+        let reorg = tracker
+            .block_connected("node", &b101, &HashMap::new())
+            .unwrap();
+
+        assert_eq!(reorg.depth, 2);
+        assert_eq!(reorg.old_tip, a102.block_hash().to_string());
+        assert_eq!(reorg.new_tip, b101.block_hash().to_string());
+        assert_eq!(reorg.affected_txids, vec![tx(2).txid().to_string()]);
+        assert_eq!(tracker.history().len(), 1);
+
+        // other nodes keep their own chain
+        assert!(tracker
+            .block_connected("other", &a101, &HashMap::new())
+            .is_none());
+    }
+
+    #[test]
+    fn missed_blocks_of_the_new_branch() {
+        let tracker = TipTracker::new();
+
+        let a100 = block(100, BlockHash::all_zeros(), 0, vec![]);
+        let a101 = block(101, a100.block_hash(), 0, vec![tx(1)]);
+        let a102 = block(102, a101.block_hash(), 0, vec![tx(2)]);
+        let a103 = block(103, a102.block_hash(), 0, vec![]);
+        for b in [&a100, &a101, &a102, &a103] {
+            tracker.block_connected("node", b, &HashMap::new());
+        }
+
+        // b101 and b102 were missed, only b103 arrives
+        let b101 = block(101, a100.block_hash(), 1, vec![]);
+        let b102 = block(102, b101.block_hash(), 1, vec![]);
+        let b103 = block(103, b102.block_hash(), 1, vec![]);
+
+        assert_eq!(
+            tracker.suspect_blocks("node", &b103),
+            vec![(101, a101.block_hash()), (100, a100.block_hash())]
+        );
+
+        let ancestors = HashMap::from([(101, b101.block_hash()), (100, a100.block_hash())]);
+        let reorg = tracker.block_connected("node", &b103, &ancestors).unwrap();
+
+        assert_eq!(reorg.depth, 3);
+        assert_eq!(reorg.old_tip, a103.block_hash().to_string());
+        assert_eq!(
+            reorg.affected_txids,
+            vec![tx(1).txid().to_string(), tx(2).txid().to_string()]
+        );
+
+        // the abandoned branch is gone, the new one follows from b103
+        let b104 = block(104, b103.block_hash(), 1, vec![]);
+        assert!(tracker.suspect_blocks("node", &b104).is_empty());
+        assert!(tracker
+            .block_connected("node", &b104, &HashMap::new())
+            .is_none());
+    }
+}
@@ -0,0 +1,175 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use actix_web::rt;
+use bitcoin::{consensus::deserialize, hashes::hex::FromHex, BlockHash, Txid};
+use log::{error, info, warn};
+use zeromq::{Socket, SocketRecv, SubSocket, ZmqResult};
+
+use super::{EventBus, NodeEvent, SequenceEvent};
+
+const TOPICS: [&str; 4] = ["rawblock", "rawtx", "hashblock", "sequence"];
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+// one subscriber per node zmq endpoint, e.g. tcp://127.0.0.1:28332
+pub fn spawn_subscribers(urls: &[String], bus: EventBus) {
+    for url in urls {
+        let url = url.clone();
+        let bus = bus.clone();
+
+        rt::spawn(async move {
+            loop {
+                if let Err(err) = subscribe(&url, &bus).await {
+                    error!("zmq subscriber {} failed: {}", url, err);
+                }
+
+                rt::time::sleep(RECONNECT_DELAY).await;
+            }
+        });
+    }
+}
+
+async fn subscribe(url: &str, bus: &EventBus) -> ZmqResult<()> {
+    let mut socket = SubSocket::new();
+    socket.connect(url).await?;
+    for topic in TOPICS {
+        socket.subscribe(topic).await?;
+    }
+    info!("zmq subscribed to {}", url);
+
+    let mut last_sequence: HashMap<Vec<u8>, u32> = HashMap::new();
+
+    loop {
+        let message = socket.recv().await?.into_vec();
+        if message.len() != 3 {
+            warn!(
+                "zmq {} unexpected message with {} frames",
+                url,
+                message.len()
+            );
+            continue;
+        }
+
+        let (topic, body, sequence) = (&message[0], &message[1], &message[2]);
+
+        if let Ok(sequence) = <[u8; 4]>::try_from(sequence.as_ref()) {
+            let sequence = u32::from_le_bytes(sequence);
+            if let Some(last) = last_sequence.insert(topic.to_vec(), sequence) {
+                if sequence != last.wrapping_add(1) {
+                    warn!(
+                        "zmq {} missed {} {} notifications",
+                        url,
+                        sequence.wrapping_sub(last).wrapping_sub(1),
+                        String::from_utf8_lossy(topic)
+                    );
+                }
+            }
+        }
+
+        match parse_message(url, topic, body) {
+            Some(event) => bus.publish(event),
+            None => warn!(
+                "zmq {} failed to parse {} message",
+                url,
+                String::from_utf8_lossy(topic)
+            ),
+        }
+    }
+}
+
+pub(crate) fn parse_message(node: &str, topic: &[u8], body: &[u8]) -> Option<NodeEvent> {
+    let node = node.to_string();
+
+    match topic {
+        b"rawblock" => Some(NodeEvent::Block {
+            node,
+            block: Arc::new(deserialize(body).ok()?),
+        }),
+        b"rawtx" => Some(NodeEvent::Tx {
+            node,
+            tx: Arc::new(deserialize(body).ok()?),
+        }),
+        b"hashblock" => Some(NodeEvent::BlockHash {
+            node,
+            hash: BlockHash::from_hex(&hex::encode(body)).ok()?,
+        }),
+        b"sequence" => Some(NodeEvent::Sequence {
+            node,
+            sequence: parse_sequence(body)?,
+        }),
+        _ => None,
+    }
+}
+
+// <32 byte hash><1 byte label>[<8 byte LE mempool sequence> for A and R]
+fn parse_sequence(body: &[u8]) -> Option<SequenceEvent> {
+    if body.len() < 33 {
+        return None;
+    }
+
+    // hashes are published in rpc (reversed) byte order
+    let hash = hex::encode(&body[..32]);
+    let mempool_sequence = body
+        .get(33..41)
+        .and_then(|seq| <[u8; 8]>::try_from(seq).ok())
+        .map(u64::from_le_bytes);
+
+    match body[32] {
+        b'C' => Some(SequenceEvent::BlockConnected(
+            BlockHash::from_hex(&hash).ok()?,
+        )),
+        b'D' => Some(SequenceEvent::BlockDisconnected(
+            BlockHash::from_hex(&hash).ok()?,
+        )),
+        b'A' => Some(SequenceEvent::TxAdded {
+            txid: Txid::from_hex(&hash).ok()?,
+            mempool_sequence: mempool_sequence?,
+        }),
+        b'R' => Some(SequenceEvent::TxRemoved {
+            txid: Txid::from_hex(&hash).ok()?,
+            mempool_sequence: mempool_sequence?,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TXID: &str = "989d301c546841d0ac5c8354c7d78079e3603b089682d1639b2ee1c1a8010c6a";
+
+    #[test]
+    fn parse_sequence_tx_added() {
+        let mut body = hex::decode(TXID).unwrap();
+        body.push(b'A');
+        body.extend_from_slice(&7u64.to_le_bytes());
+
+        match parse_message("node", b"sequence", &body) {
+            Some(NodeEvent::Sequence { sequence, .. }) => assert_eq!(
+                sequence,
+                SequenceEvent::TxAdded {
+                    txid: Txid::from_hex(TXID).unwrap(),
+                    mempool_sequence: 7,
+                }
+            ),
+            _ => panic!("expected sequence event"),
+        }
+    }
+
+    #[test]
+    fn parse_hashblock() {
+        let body = hex::decode(TXID).unwrap();
+
+        match parse_message("node", b"hashblock", &body) {
+            Some(NodeEvent::BlockHash { hash, .. }) => assert_eq!(hash.to_string(), TXID),
+            _ => panic!("expected hashblock event"),
+        }
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(parse_message("node", b"rawtx", &[0, 1, 2]).is_none());
+        assert!(parse_message("node", b"sequence", &[0; 32]).is_none());
+        assert!(parse_message("node", b"unknown", &[]).is_none());
+    }
+}
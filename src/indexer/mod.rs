@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+pub mod store;
+pub mod sync;
+
+pub use store::IndexStore;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IndexedScript {
+    pub address: String,
+    // hex
+    pub script_pubkey: String,
+    // index tip when the script was registered, older blocks are backfilled for it
+    pub registered_height: Option<u64>,
+    pub created_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct IndexedUtxo {
+    pub txid: String,
+    pub vout: u32,
+    // sats
+    pub value: u64,
+    pub script_pubkey: String,
+    pub height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub txid: String,
+    pub height: u64,
+    pub block_hash: String,
+    // sats paid to and spent from the script
+    pub received: u64,
+    pub sent: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct IndexTip {
+    pub height: u64,
+    pub hash: String,
+}
+
+// changes of an indexed block, used to roll it back on reorg
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct BlockUndo {
+    pub hash: String,
+    pub created: Vec<IndexedUtxo>,
+    pub spent: Vec<IndexedUtxo>,
+    // script hex and txid of the history entries
+    pub history: Vec<(String, String)>,
+}
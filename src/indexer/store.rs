@@ -0,0 +1,606 @@
+use std::collections::{BTreeMap, HashMap};
+
+use bitcoin::{hashes::hex::ToHex, Block};
+use sled::{
+    transaction::{TransactionError, TransactionalTree},
+    Db, Transactional, Tree,
+};
+
+use super::{BlockUndo, HistoryEntry, IndexTip, IndexedScript, IndexedUtxo};
+use crate::storage::{decode, decode_height, encode, key};
+
+// blocks kept for rollback, deeper reorgs need a reindex
+pub const UNDO_DEPTH: u64 = 100;
+
+const UNDO_FROM: &str = "undo_from";
+
+// sled trees:
+//   scripts    script hex -> IndexedScript
+//   backfill   script hex -> next height (be u64), scripts still catching up with the tip
+//   utxos      script hex / txid:vout -> IndexedUtxo
+//   outpoints  txid:vout -> script hex, finds the indexed output spent by an input
+//   history    script hex / height (be u64) / txid -> HistoryEntry
+//   blocks     height (be u64) -> BlockUndo, the last one is the index tip
+//   meta       undo_from -> lowest height (be u64) with an undo entry once trimmed
+#[derive(Clone)]
+pub struct IndexStore {
+    scripts: Tree,
+    backfill: Tree,
+    utxos: Tree,
+    outpoints: Tree,
+    history: Tree,
+    blocks: Tree,
+    meta: Tree,
+}
+
+// changes of one block for a set of scripts
+struct BlockChanges {
+    undo: BlockUndo,
+    history: Vec<(String, HistoryEntry)>,
+}
+
+fn outpoint(txid: &str, vout: u32) -> String {
+    format!("{}:{}", txid, vout)
+}
+
+fn utxo_key(utxo: &IndexedUtxo) -> Vec<u8> {
+    key(&[
+        utxo.script_pubkey.as_bytes(),
+        outpoint(&utxo.txid, utxo.vout).as_bytes(),
+    ])
+}
+
+fn history_key(script_pubkey: &str, height: u64, txid: &str) -> Vec<u8> {
+    key(&[
+        script_pubkey.as_bytes(),
+        &height.to_be_bytes(),
+        txid.as_bytes(),
+    ])
+}
+
+fn abort(err: TransactionError<sled::Error>) -> sled::Error {
+    match err {
+        TransactionError::Abort(err) | TransactionError::Storage(err) => err,
+    }
+}
+
+fn insert_utxo(
+    utxos: &TransactionalTree,
+    outpoints: &TransactionalTree,
+    utxo: &IndexedUtxo,
+    value: &[u8],
+) -> sled::transaction::ConflictableTransactionResult<(), sled::Error> {
+    utxos.insert(utxo_key(utxo), value)?;
+    outpoints.insert(
+        outpoint(&utxo.txid, utxo.vout).as_bytes(),
+        utxo.script_pubkey.as_bytes(),
+    )?;
+    Ok(())
+}
+
+fn remove_utxo(
+    utxos: &TransactionalTree,
+    outpoints: &TransactionalTree,
+    utxo: &IndexedUtxo,
+) -> sled::transaction::ConflictableTransactionResult<(), sled::Error> {
+    utxos.remove(utxo_key(utxo))?;
+    outpoints.remove(outpoint(&utxo.txid, utxo.vout).as_bytes())?;
+    Ok(())
+}
+
+impl IndexStore {
+    pub fn open(db: &Db) -> sled::Result<IndexStore> {
+        Ok(IndexStore {
+            scripts: db.open_tree("index_scripts")?,
+            backfill: db.open_tree("index_backfill")?,
+            utxos: db.open_tree("index_utxos")?,
+            outpoints: db.open_tree("index_outpoints")?,
+            history: db.open_tree("index_history")?,
+            blocks: db.open_tree("index_blocks")?,
+            meta: db.open_tree("index_meta")?,
+        })
+    }
+
+    // backfill_from is the first block to scan for the script, None when nothing is indexed yet
+    pub fn add_script(
+        &self,
+        script: &IndexedScript,
+        backfill_from: Option<u64>,
+    ) -> sled::Result<()> {
+        if let Some(height) = backfill_from {
+            self.backfill
+                .insert(&script.script_pubkey, &height.to_be_bytes())?;
+        }
+        self.scripts
+            .insert(&script.script_pubkey, encode(script)?)?;
+        Ok(())
+    }
+
+    // scripts with the next block to scan for them
+    pub fn backfills(&self) -> sled::Result<Vec<(String, u64)>> {
+        self.backfill
+            .iter()
+            .map(|entry| {
+                let (script_pubkey, height) = entry?;
+                Ok((
+                    String::from_utf8_lossy(&script_pubkey).to_string(),
+                    decode_height(&height),
+                ))
+            })
+            .collect()
+    }
+
+    // the script caught up with the tip, e.g. after a rollback below its next height
+    pub fn finish_backfill(&self, script_pubkey: &str) -> sled::Result<()> {
+        self.backfill.remove(script_pubkey)?;
+        Ok(())
+    }
+
+    // scripts followed block by block, backfilling ones are scanned separately
+    fn is_live(&self, script_pubkey: &str) -> sled::Result<bool> {
+        Ok(self.scripts.contains_key(script_pubkey)?
+            && !self.backfill.contains_key(script_pubkey)?)
+    }
+
+    // hash of an indexed block still kept for rollback
+    pub fn block_hash(&self, height: u64) -> sled::Result<Option<String>> {
+        self.blocks
+            .get(height.to_be_bytes())?
+            .map(|bytes| decode::<BlockUndo>(&bytes).map(|undo| undo.hash))
+            .transpose()
+    }
+
+    pub fn script(&self, script_pubkey: &str) -> sled::Result<Option<IndexedScript>> {
+        self.scripts
+            .get(script_pubkey)?
+            .map(|bytes| decode(&bytes))
+            .transpose()
+    }
+
+    pub fn scripts(&self) -> sled::Result<Vec<IndexedScript>> {
+        self.scripts
+            .iter()
+            .values()
+            .map(|bytes| decode(&bytes?))
+            .collect()
+    }
+
+    pub fn remove_script(&self, script_pubkey: &str) -> sled::Result<Option<IndexedScript>> {
+        let script = match self.script(script_pubkey)? {
+            Some(script) => script,
+            None => return Ok(None),
+        };
+
+        self.scripts.remove(script_pubkey)?;
+        self.backfill.remove(script_pubkey)?;
+        for utxo in self.utxos(script_pubkey)? {
+            self.utxos.remove(utxo_key(&utxo))?;
+            self.outpoints.remove(outpoint(&utxo.txid, utxo.vout))?;
+        }
+        for entry in self
+            .history
+            .scan_prefix(key(&[script_pubkey.as_bytes(), b""]))
+        {
+            self.history.remove(entry?.0)?;
+        }
+
+        Ok(Some(script))
+    }
+
+    pub fn utxos(&self, script_pubkey: &str) -> sled::Result<Vec<IndexedUtxo>> {
+        self.utxos
+            .scan_prefix(key(&[script_pubkey.as_bytes(), b""]))
+            .values()
+            .map(|bytes| decode(&bytes?))
+            .collect()
+    }
+
+    // oldest first
+    pub fn history(&self, script_pubkey: &str) -> sled::Result<Vec<HistoryEntry>> {
+        self.history
+            .scan_prefix(key(&[script_pubkey.as_bytes(), b""]))
+            .values()
+            .map(|bytes| decode(&bytes?))
+            .collect()
+    }
+
+    pub fn tip(&self) -> sled::Result<Option<IndexTip>> {
+        match self.blocks.last()? {
+            Some((height, bytes)) => {
+                let undo: BlockUndo = decode(&bytes)?;
+                Ok(Some(IndexTip {
+                    height: decode_height(&height),
+                    hash: undo.hash,
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // outputs paying the scripts and the inputs spending them
+    fn block_changes<F>(&self, height: u64, block: &Block, indexed: F) -> sled::Result<BlockChanges>
+    where
+        F: Fn(&str) -> sled::Result<bool>,
+    {
+        let hash = block.block_hash().to_string();
+        let mut undo = BlockUndo {
+            hash: hash.clone(),
+            ..BlockUndo::default()
+        };
+
+        // outputs created in this block, spends inside the block remove them again
+        let mut created: BTreeMap<String, IndexedUtxo> = BTreeMap::new();
+        let mut history: Vec<(String, HistoryEntry)> = Vec::new();
+
+        for tx in &block.txdata {
+            let txid = tx.txid().to_string();
+            // script hex -> (received, sent)
+            let mut touched: HashMap<String, (u64, u64)> = HashMap::new();
+
+            if !tx.is_coin_base() {
+                for input in &tx.input {
+                    let prev = outpoint(
+                        &input.previous_output.txid.to_string(),
+                        input.previous_output.vout,
+                    );
+
+                    let utxo = match created.remove(&prev) {
+                        Some(utxo) => utxo,
+                        None => match self.outpoints.get(&prev)? {
+                            Some(script) => {
+                                let script = String::from_utf8_lossy(&script).to_string();
+                                if !indexed(&script)? {
+                                    continue;
+                                }
+                                match self.utxos.get(key(&[script.as_bytes(), prev.as_bytes()]))? {
+                                    Some(bytes) => {
+                                        let utxo: IndexedUtxo = decode(&bytes)?;
+                                        undo.spent.push(utxo.clone());
+                                        utxo
+                                    }
+                                    None => continue,
+                                }
+                            }
+                            None => continue,
+                        },
+                    };
+
+                    touched.entry(utxo.script_pubkey).or_default().1 += utxo.value;
+                }
+            }
+
+            for (vout, out) in tx.output.iter().enumerate() {
+                let script_pubkey = out.script_pubkey.to_hex();
+                if !indexed(&script_pubkey)? {
+                    continue;
+                }
+
+                let utxo = IndexedUtxo {
+                    txid: txid.clone(),
+                    vout: vout as u32,
+                    value: out.value,
+                    script_pubkey: script_pubkey.clone(),
+                    height,
+                };
+                created.insert(outpoint(&txid, vout as u32), utxo);
+                touched.entry(script_pubkey).or_default().0 += out.value;
+            }
+
+            for (script_pubkey, (received, sent)) in touched {
+                undo.history.push((script_pubkey.clone(), txid.clone()));
+                history.push((
+                    script_pubkey,
+                    HistoryEntry {
+                        txid: txid.clone(),
+                        height,
+                        block_hash: hash.clone(),
+                        received,
+                        sent,
+                    },
+                ));
+            }
+        }
+
+        undo.created = created.into_values().collect();
+
+        Ok(BlockChanges { undo, history })
+    }
+
+    // writes the changes atomically, together with the undo entry and the backfill progress
+    fn write_changes(
+        &self,
+        height: u64,
+        changes: &BlockChanges,
+        undo: Option<&BlockUndo>,
+        backfill: Option<(&str, Option<u64>)>,
+    ) -> sled::Result<()> {
+        let created = changes
+            .undo
+            .created
+            .iter()
+            .map(|utxo| Ok((utxo, encode(utxo)?)))
+            .collect::<sled::Result<Vec<_>>>()?;
+        let history = changes
+            .history
+            .iter()
+            .map(|(script, entry)| {
+                Ok((
+                    history_key(script, entry.height, &entry.txid),
+                    encode(entry)?,
+                ))
+            })
+            .collect::<sled::Result<Vec<_>>>()?;
+        let undo_bytes = undo.map(encode).transpose()?;
+
+        (
+            &self.utxos,
+            &self.outpoints,
+            &self.history,
+            &self.blocks,
+            &self.backfill,
+        )
+            .transaction(|(utxos, outpoints, history_tree, blocks, backfill_tree)| {
+                for utxo in &changes.undo.spent {
+                    remove_utxo(utxos, outpoints, utxo)?;
+                }
+                for (utxo, value) in &created {
+                    insert_utxo(utxos, outpoints, utxo, value)?;
+                }
+                for (entry_key, value) in &history {
+                    history_tree.insert(entry_key.as_slice(), value.as_slice())?;
+                }
+                if let Some(undo_bytes) = &undo_bytes {
+                    blocks.insert(&height.to_be_bytes(), undo_bytes.as_slice())?;
+                }
+                match backfill {
+                    Some((script_pubkey, Some(next))) => {
+                        backfill_tree.insert(script_pubkey, &next.to_be_bytes())?;
+                    }
+                    Some((script_pubkey, None)) => {
+                        backfill_tree.remove(script_pubkey)?;
+                    }
+                    None => {}
+                }
+                Ok(())
+            })
+            .map_err(abort)
+    }
+
+    // indexes the block for the live scripts, all changes of the block are written
+    // atomically together with the new tip
+    pub fn apply_block(&self, height: u64, block: &Block) -> sled::Result<()> {
+        let changes = self.block_changes(height, block, |script| self.is_live(script))?;
+        self.write_changes(height, &changes, Some(&changes.undo), None)?;
+        self.trim_undo(height)
+    }
+
+    // indexes an already indexed block for a backfilling script, the changes join the
+    // undo entry of the block so a rollback covers them, the script goes live after the tip
+    pub fn backfill_block(
+        &self,
+        script_pubkey: &str,
+        height: u64,
+        block: &Block,
+    ) -> sled::Result<()> {
+        let changes = self.block_changes(height, block, |script| Ok(script == script_pubkey))?;
+
+        let undo = match self.blocks.get(height.to_be_bytes())? {
+            Some(bytes) => {
+                let mut undo: BlockUndo = decode(&bytes)?;
+                undo.created.extend(changes.undo.created.iter().cloned());
+                undo.spent.extend(changes.undo.spent.iter().cloned());
+                undo.history.extend(changes.undo.history.iter().cloned());
+                Some(undo)
+            }
+            None => None,
+        };
+
+        let tip = self.tip()?.map(|tip| tip.height).unwrap_or_default();
+        let next = (height < tip).then(|| height + 1);
+
+        self.write_changes(height, &changes, undo.as_ref(), Some((script_pubkey, next)))
+    }
+
+    // drops undo entries deeper than UNDO_DEPTH below the tip
+    fn trim_undo(&self, tip: u64) -> sled::Result<()> {
+        let undo_from = match tip.checked_sub(UNDO_DEPTH) {
+            Some(undo_from) if undo_from > 0 => undo_from,
+            _ => return Ok(()),
+        };
+
+        let mut trimmed = false;
+        for entry in self.blocks.range(..undo_from.to_be_bytes()) {
+            self.blocks.remove(entry?.0)?;
+            trimmed = true;
+        }
+        if trimmed {
+            self.meta.insert(UNDO_FROM, &undo_from.to_be_bytes())?;
+        }
+        Ok(())
+    }
+
+    // undoes the tip block, returns it
+    pub fn rollback_tip(&self) -> sled::Result<Option<IndexTip>> {
+        let (height, undo) = match self.blocks.last()? {
+            Some((height, bytes)) => (decode_height(&height), decode::<BlockUndo>(&bytes)?),
+            None => return Ok(None),
+        };
+
+        // the block below has no undo entry left
+        if let Some(undo_from) = self.meta.get(UNDO_FROM)? {
+            if height <= decode_height(&undo_from) {
+                return Err(sled::Error::Unsupported(format!(
+                    "reorg deeper than {} blocks, the index needs a rebuild",
+                    UNDO_DEPTH
+                )));
+            }
+        }
+
+        // scripts removed in the meantime are not restored
+        let mut restored = Vec::new();
+        for utxo in &undo.spent {
+            if self.scripts.contains_key(&utxo.script_pubkey)? {
+                restored.push((utxo, encode(utxo)?));
+            }
+        }
+
+        (&self.utxos, &self.outpoints, &self.history, &self.blocks)
+            .transaction(|(utxos, outpoints, history, blocks)| {
+                for utxo in &undo.created {
+                    remove_utxo(utxos, outpoints, utxo)?;
+                }
+                for (utxo, value) in &restored {
+                    insert_utxo(utxos, outpoints, utxo, value)?;
+                }
+                for (script_pubkey, txid) in &undo.history {
+                    history.remove(history_key(script_pubkey, height, txid))?;
+                }
+                blocks.remove(&height.to_be_bytes())?;
+                Ok(())
+            })
+            .map_err(abort)?;
+
+        Ok(Some(IndexTip {
+            height,
+            hash: undo.hash,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::{
+        blockdata::constants::genesis_block, hashes::Hash, Network, OutPoint, PackedLockTime,
+        Script, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
+    };
+
+    fn store() -> IndexStore {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        IndexStore::open(&db).unwrap()
+    }
+
+    fn script() -> Script {
+        Script::from(vec![0x00, 0x14, 0xaa])
+    }
+
+    fn tx(spends: Option<OutPoint>, value: u64) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: spends.unwrap_or_else(|| OutPoint::new(Txid::all_zeros(), 7)),
+                script_sig: Script::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::default(),
+            }],
+            output: vec![TxOut {
+                value,
+                script_pubkey: script(),
+            }],
+        }
+    }
+
+    fn block(nonce: u32, txs: Vec<Transaction>) -> Block {
+        let mut block = genesis_block(Network::Testnet);
+        block.header.nonce = nonce;
+        block.txdata.extend(txs);
+        block
+    }
+
+    fn indexed_script() -> IndexedScript {
+        IndexedScript {
+            address: String::new(),
+            script_pubkey: script().to_hex(),
+            registered_height: None,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn apply_and_rollback() {
+        let store = store();
+        store.add_script(&indexed_script(), None).unwrap();
+
+        let funding = tx(None, 1000);
+        store
+            .apply_block(1, &block(1, vec![funding.clone()]))
+            .unwrap();
+        assert_eq!(store.utxos(&script().to_hex()).unwrap().len(), 1);
+
+        let spend = tx(Some(OutPoint::new(funding.txid(), 0)), 900);
+        store
+            .apply_block(2, &block(2, vec![spend.clone()]))
+            .unwrap();
+
+        let utxos = store.utxos(&script().to_hex()).unwrap();
+        assert_eq!(utxos.len(), 1);
+        assert_eq!(utxos[0].txid, spend.txid().to_string());
+
+        let history = store.history(&script().to_hex()).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!((history[1].received, history[1].sent), (900, 1000));
+        assert_eq!(store.tip().unwrap().unwrap().height, 2);
+
+        store.rollback_tip().unwrap();
+        let utxos = store.utxos(&script().to_hex()).unwrap();
+        assert_eq!(utxos.len(), 1);
+        assert_eq!(utxos[0].txid, funding.txid().to_string());
+        assert_eq!(store.history(&script().to_hex()).unwrap().len(), 1);
+        assert_eq!(store.tip().unwrap().unwrap().height, 1);
+    }
+
+    #[test]
+    fn backfill_joins_undo() {
+        let store = store();
+        let funding = tx(None, 1000);
+        let spend = tx(Some(OutPoint::new(funding.txid(), 0)), 900);
+        let first = block(1, vec![funding]);
+        let second = block(2, vec![spend.clone()]);
+
+        // indexed before the script was registered
+        store.apply_block(1, &first).unwrap();
+        store.apply_block(2, &second).unwrap();
+        store.add_script(&indexed_script(), Some(1)).unwrap();
+        assert!(store.utxos(&script().to_hex()).unwrap().is_empty());
+
+        // live blocks skip the script until it caught up
+        store.backfill_block(&script().to_hex(), 1, &first).unwrap();
+        assert_eq!(store.backfills().unwrap(), vec![(script().to_hex(), 2)]);
+        store
+            .backfill_block(&script().to_hex(), 2, &second)
+            .unwrap();
+        assert!(store.backfills().unwrap().is_empty());
+
+        let utxos = store.utxos(&script().to_hex()).unwrap();
+        assert_eq!(utxos.len(), 1);
+        assert_eq!(utxos[0].txid, spend.txid().to_string());
+        assert_eq!(store.history(&script().to_hex()).unwrap().len(), 2);
+
+        store.rollback_tip().unwrap();
+        let utxos = store.utxos(&script().to_hex()).unwrap();
+        assert_eq!(utxos.len(), 1);
+        assert_ne!(utxos[0].txid, spend.txid().to_string());
+        assert_eq!(store.history(&script().to_hex()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn undo_is_trimmed() {
+        let store = store();
+        let tip = UNDO_DEPTH + 5;
+        for height in 1..=tip {
+            store
+                .apply_block(height, &block(height as u32, vec![]))
+                .unwrap();
+        }
+
+        assert_eq!(store.block_hash(4).unwrap(), None);
+        assert!(store.block_hash(5).unwrap().is_some());
+
+        for _ in 0..UNDO_DEPTH {
+            store.rollback_tip().unwrap();
+        }
+        assert_eq!(store.tip().unwrap().unwrap().height, 5);
+        assert!(store.rollback_tip().is_err());
+    }
+}
@@ -0,0 +1,223 @@
+use std::{fmt, time::Duration};
+
+use actix_web::rt;
+use bitcoin::{consensus::deserialize, hashes::hex::FromHex, Block};
+use log::{error, info, warn};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+use super::IndexStore;
+use crate::{
+    api::btc::{
+        handler::rpc_headers,
+        model::{GetBlockHash, RawBlock},
+    },
+    config::BitcoinRpcConfig,
+    events::{EventBus, NodeEvent},
+    request::RequestClient,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+// bitcoin core error code for a height above the tip
+const RPC_INVALID_PARAMETER: isize = -8;
+
+#[derive(Debug)]
+pub enum SyncError {
+    Store(sled::Error),
+    Request(String),
+    Rpc(isize, String),
+    Decode(String),
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SyncError::Store(err) => write!(f, "store error: {}", err),
+            SyncError::Request(err) => write!(f, "request error: {}", err),
+            SyncError::Rpc(code, message) => write!(f, "rpc error {}: {}", code, message),
+            SyncError::Decode(err) => write!(f, "decode error: {}", err),
+        }
+    }
+}
+
+impl From<sled::Error> for SyncError {
+    fn from(err: sled::Error) -> SyncError {
+        SyncError::Store(err)
+    }
+}
+
+// follows the chain of the first node block by block, wakes up on zmq block notifications
+pub fn spawn_indexer(
+    store: IndexStore,
+    rq_client: RequestClient,
+    btc_rpc_cfg: BitcoinRpcConfig,
+    bus: &EventBus,
+) {
+    if !btc_rpc_cfg.bitcoin_indexer_enabled {
+        return;
+    }
+
+    let mut events = bus.subscribe();
+
+    rt::spawn(async move {
+        loop {
+            if let Err(err) = sync(&store, &rq_client, &btc_rpc_cfg).await {
+                error!("indexer sync failed, {}", err);
+            }
+
+            let _ = rt::time::timeout(POLL_INTERVAL, next_block(&mut events)).await;
+        }
+    });
+}
+
+async fn next_block(events: &mut Receiver<NodeEvent>) {
+    loop {
+        match events.recv().await {
+            Ok(NodeEvent::BlockHash { .. }) | Ok(NodeEvent::Block { .. }) => return,
+            Ok(_) => {}
+            Err(RecvError::Lagged(_)) => return,
+            Err(RecvError::Closed) => std::future::pending().await,
+        }
+    }
+}
+
+pub async fn sync(
+    store: &IndexStore,
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+) -> Result<(), SyncError> {
+    loop {
+        let tip = store.tip()?;
+        let height = match &tip {
+            Some(tip) => tip.height + 1,
+            None => btc_rpc_cfg.bitcoin_indexer_start_height,
+        };
+
+        let hash = match block_hash(rq_client, btc_rpc_cfg, height).await? {
+            Some(hash) => hash,
+            None => {
+                // caught up, unless the node switched to a shorter branch
+                if let Some(tip) = tip {
+                    let node_hash = block_hash(rq_client, btc_rpc_cfg, tip.height).await?;
+                    if node_hash.as_ref() != Some(&tip.hash) {
+                        warn!("indexer rolls back block {} at {}", tip.hash, tip.height);
+                        store.rollback_tip()?;
+                        continue;
+                    }
+
+                    backfill(store, rq_client, btc_rpc_cfg, tip.height).await?;
+                }
+                return Ok(());
+            }
+        };
+
+        let block = block(rq_client, btc_rpc_cfg, &hash).await?;
+
+        if let Some(tip) = tip {
+            if block.header.prev_blockhash.to_string() != tip.hash {
+                warn!("indexer rolls back block {} at {}", tip.hash, tip.height);
+                store.rollback_tip()?;
+                continue;
+            }
+        }
+
+        store.apply_block(height, &block)?;
+        if height % 1000 == 0 {
+            info!("indexer synced block {}", height);
+        }
+    }
+}
+
+// scans the indexed blocks for scripts registered after them, up to the tip
+async fn backfill(
+    store: &IndexStore,
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    tip: u64,
+) -> Result<(), SyncError> {
+    for (script_pubkey, from) in store.backfills()? {
+        if from > tip {
+            store.finish_backfill(&script_pubkey)?;
+            continue;
+        }
+
+        info!("indexer backfills {} from block {}", script_pubkey, from);
+        for height in from..=tip {
+            let hash = match block_hash(rq_client, btc_rpc_cfg, height).await? {
+                Some(hash) => hash,
+                None => return Ok(()),
+            };
+
+            // the node moved to another branch, the next sync rolls back first
+            if let Some(indexed_hash) = store.block_hash(height)? {
+                if indexed_hash != hash {
+                    return Ok(());
+                }
+            }
+
+            let block = block(rq_client, btc_rpc_cfg, &hash).await?;
+            store.backfill_block(&script_pubkey, height, &block)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn rpc<T: DeserializeOwned>(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    payload: &Value,
+) -> Result<T, SyncError> {
+    let headers = rpc_headers(btc_rpc_cfg);
+
+    rq_client
+        .post(&btc_rpc_cfg.bitcoin_rpc_url_one, Some(&headers), payload)
+        .await
+        .map_err(|err| SyncError::Request(err.to_string()))?
+        .json::<T>()
+        .await
+        .map_err(|err| SyncError::Decode(err.to_string()))
+}
+
+async fn block_hash(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    height: u64,
+) -> Result<Option<String>, SyncError> {
+    let payload = json!({ "jsonrpc": "2.0",  "method": "getblockhash", "params": [height]});
+
+    match rpc::<GetBlockHash>(rq_client, btc_rpc_cfg, &payload).await? {
+        GetBlockHash {
+            result: Some(hash), ..
+        } => Ok(Some(hash)),
+        GetBlockHash {
+            error: Some(err), ..
+        } if err.code == RPC_INVALID_PARAMETER => Ok(None),
+        GetBlockHash {
+            error: Some(err), ..
+        } => Err(SyncError::Rpc(err.code, err.message)),
+        _ => Err(SyncError::Decode("empty getblockhash result".to_string())),
+    }
+}
+
+async fn block(
+    rq_client: &RequestClient,
+    btc_rpc_cfg: &BitcoinRpcConfig,
+    hash: &str,
+) -> Result<Block, SyncError> {
+    let payload = json!({ "jsonrpc": "2.0",  "method": "getblock", "params": [hash, 0]});
+
+    match rpc::<RawBlock>(rq_client, btc_rpc_cfg, &payload).await? {
+        RawBlock {
+            result: Some(hex), ..
+        } => Vec::<u8>::from_hex(&hex)
+            .map_err(|err| SyncError::Decode(err.to_string()))
+            .and_then(|raw| deserialize(&raw).map_err(|err| SyncError::Decode(err.to_string()))),
+        RawBlock {
+            error: Some(err), ..
+        } => Err(SyncError::Rpc(err.code, err.message)),
+        _ => Err(SyncError::Decode("empty getblock result".to_string())),
+    }
+}
@@ -1,6 +1,10 @@
 pub mod api;
 pub mod config;
+pub mod events;
+pub mod indexer;
 pub mod request;
+mod storage;
+pub mod webhooks;
 
 use actix_web::{middleware::Logger, web, App, HttpServer};
 use config::Config;
@@ -13,15 +17,48 @@ pub async fn init_server() -> Result<(), std::io::Error> {
 
     let request_client = request::RequestClient::new();
 
+    let event_bus = events::EventBus::new();
+    let tip_tracker = events::tip::TipTracker::new();
+
+    let db = sled::open(&cfg.storage_path)?;
+    let webhook_store = webhooks::WebhookStore::open(&db)?;
+    let index_store = indexer::IndexStore::open(&db)?;
+
+    // zmq, reorg tracking, webhooks and the indexer follow the bitcoin node only
+    if let Some(btc_rpc_cfg) = &cfg.bitcoin_rpc_config {
+        events::zmq::spawn_subscribers(&btc_rpc_cfg.bitcoin_zmq_urls, event_bus.clone());
+        events::tip::spawn_tip_tracker(
+            tip_tracker.clone(),
+            event_bus.clone(),
+            request_client.clone(),
+            btc_rpc_cfg.clone(),
+        );
+
+        webhooks::watcher::spawn_watcher(webhook_store.clone(), &event_bus);
+        webhooks::delivery::spawn_delivery(webhook_store.clone(), request_client.clone());
+
+        indexer::sync::spawn_indexer(
+            index_store.clone(),
+            request_client.clone(),
+            btc_rpc_cfg.clone(),
+            &event_bus,
+        );
+    }
+
+    let chain_nodes = api::chain::nodes(&cfg, &request_client);
+
     HttpServer::new(move || {
         App::new()
             .wrap(Logger::default())
             .app_data(web::Data::new(request_client.clone()))
-            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone()))
+            .app_data(web::Data::new(event_bus.clone()))
+            .app_data(web::Data::new(webhook_store.clone()))
+            .app_data(web::Data::new(tip_tracker.clone()))
+            .app_data(web::Data::new(index_store.clone()))
             .service(
                 web::scope("/api")
                     .configure(api::init_health_handler)
-                    .service(web::scope("/bitcoin").configure(api::init_bitcoin_handler)),
+                    .configure(|cfg| api::chain::init(cfg, &chain_nodes)),
             )
             .default_service(web::to(api::not_found))
     })
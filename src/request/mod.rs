@@ -44,10 +44,13 @@ impl RequestClient {
 }
 
 impl RequestClient {
-    // pub async fn get(&self, url: &str, payload: &Query) -> Result<String, RequestError> {
-    //     self.request(Method::GET, url, headers, |req| req.query(payload))
-    //         .await
-    // }
+    pub async fn get(
+        &self,
+        url: &str,
+        headers: Option<&Headers>,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        self.request(Method::GET, url, headers, |req| req).await
+    }
 
     pub async fn post(
         &self,
@@ -0,0 +1,22 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+// helpers shared by the sled backed stores, values are stored as json
+
+pub(crate) fn encode<T: Serialize>(value: &T) -> sled::Result<Vec<u8>> {
+    serde_json::to_vec(value)
+        .map_err(|err| sled::Error::Unsupported(format!("failed to encode value, {}", err)))
+}
+
+pub(crate) fn decode<T: DeserializeOwned>(bytes: &[u8]) -> sled::Result<T> {
+    serde_json::from_slice(bytes)
+        .map_err(|err| sled::Error::Unsupported(format!("failed to decode value, {}", err)))
+}
+
+// parts joined with '/'
+pub(crate) fn key(parts: &[&[u8]]) -> Vec<u8> {
+    parts.join(&b'/')
+}
+
+pub(crate) fn decode_height(bytes: &[u8]) -> u64 {
+    u64::from_be_bytes(bytes.try_into().unwrap_or_default())
+}
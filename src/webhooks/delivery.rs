@@ -0,0 +1,227 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    rc::Rc,
+    time::Duration,
+};
+
+use actix_web::rt;
+use bitcoin::hashes::{hex::ToHex, hmac, sha256, Hash, HashEngine};
+use log::{error, warn};
+use serde_json::json;
+
+use super::{now, Delivery, DeliveryLog, WebhookStore};
+use crate::request::{Headers, RequestClient};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_ATTEMPTS: u32 = 10;
+const MAX_BACKOFF: u64 = 60 * 60;
+
+// posts due deliveries, failed ones are retried with exponential backoff. every watch
+// has its own queue, so a slow callback only holds back the deliveries of its watch
+pub fn spawn_delivery(store: WebhookStore, rq_client: RequestClient) {
+    rt::spawn(async move {
+        let busy: Rc<RefCell<HashSet<String>>> = Rc::default();
+
+        loop {
+            match store.due_deliveries(now()) {
+                Ok(deliveries) => {
+                    for (watch_id, deliveries) in by_watch(deliveries) {
+                        if !busy.borrow_mut().insert(watch_id.clone()) {
+                            continue;
+                        }
+
+                        let (store, rq_client, busy) =
+                            (store.clone(), rq_client.clone(), busy.clone());
+                        rt::spawn(async move {
+                            for delivery in deliveries {
+                                if let Err(err) = deliver(&store, &rq_client, delivery).await {
+                                    error!("webhook delivery store error: {}", err);
+                                }
+                            }
+                            busy.borrow_mut().remove(&watch_id);
+                        });
+                    }
+                }
+                Err(err) => error!("failed to read webhook queue: {}", err),
+            }
+
+            rt::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+// due deliveries per watch, in queue order
+fn by_watch(deliveries: Vec<Delivery>) -> BTreeMap<String, Vec<Delivery>> {
+    let mut watches: BTreeMap<String, Vec<Delivery>> = BTreeMap::new();
+    for delivery in deliveries {
+        watches
+            .entry(delivery.payload.watch_id.clone())
+            .or_default()
+            .push(delivery);
+    }
+    watches
+}
+
+async fn deliver(
+    store: &WebhookStore,
+    rq_client: &RequestClient,
+    mut delivery: Delivery,
+) -> sled::Result<()> {
+    let payload = json!(delivery.payload);
+    let timestamp = now();
+    let body = payload.to_string();
+
+    let mut headers: Headers = HashMap::new();
+    headers.insert("Content-Type".to_string(), "application/json".to_string());
+    headers.insert("X-Webhook-Id".to_string(), delivery.payload.id.to_string());
+    headers.insert("X-Webhook-Timestamp".to_string(), timestamp.to_string());
+    headers.insert(
+        "X-Webhook-Signature".to_string(),
+        format!("sha256={}", sign(&delivery.secret, timestamp, &body)),
+    );
+
+    let attempt = delivery.attempts + 1;
+    let (status, error) = match rt::time::timeout(
+        DELIVERY_TIMEOUT,
+        rq_client.post(&delivery.callback_url, Some(&headers), &payload),
+    )
+    .await
+    {
+        Ok(Ok(response)) => (Some(response.status().as_u16()), None),
+        Ok(Err(err)) => (None, Some(err.to_string())),
+        Err(_) => (None, Some("callback timed out".to_string())),
+    };
+    let delivered = status.is_some_and(|status| (200..300).contains(&status));
+
+    store.log(&DeliveryLog {
+        delivery_id: delivery.payload.id.clone(),
+        watch_id: delivery.payload.watch_id.clone(),
+        event: delivery.payload.event,
+        attempt,
+        at: timestamp,
+        status,
+        error,
+        delivered,
+    })?;
+
+    store.dequeue(&delivery)?;
+
+    if delivered {
+        return Ok(());
+    }
+
+    if attempt >= MAX_ATTEMPTS {
+        warn!(
+            "webhook {} to {} dropped after {} attempts",
+            delivery.payload.id, delivery.callback_url, attempt
+        );
+        return Ok(());
+    }
+
+    delivery.attempts = attempt;
+    delivery.next_attempt_at = timestamp + backoff(attempt);
+    store.enqueue(&delivery)
+}
+
+fn backoff(attempt: u32) -> u64 {
+    (5u64 << attempt.min(16)).min(MAX_BACKOFF)
+}
+
+// hex hmac-sha256 of "<timestamp>.<body>"
+pub fn sign(secret: &str, timestamp: u64, body: &str) -> String {
+    let mut engine = hmac::HmacEngine::<sha256::Hash>::new(secret.as_bytes());
+    engine.input(timestamp.to_string().as_bytes());
+    engine.input(b".");
+    engine.input(body.as_bytes());
+
+    hmac::Hmac::<sha256::Hash>::from_engine(engine).to_hex()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhooks::{WebhookEvent, WebhookPayload};
+
+    #[test]
+    fn sign_payload() {
+        // echo -n '1.{}' | openssl dgst -sha256 -hmac secret
+        assert_eq!(
+            sign("secret", 1, "{}"),
+            "1122767b193110cfec322b6f199b599edbf608ed087f2d27afb0b97d99523908"
+        );
+    }
+
+    fn delivery(watch_id: &str, id: &str, callback_url: &str) -> Delivery {
+        Delivery {
+            payload: WebhookPayload {
+                id: id.to_string(),
+                watch_id: watch_id.to_string(),
+                event: WebhookEvent::FirstSeen,
+                address: "mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u".to_string(),
+                txid: "00".repeat(32),
+                vout: 0,
+                amount: 0.0001,
+                confirmations: 0,
+                block_hash: None,
+                block_height: None,
+                created_at: 0,
+            },
+            callback_url: callback_url.to_string(),
+            secret: "secret".to_string(),
+            attempts: 0,
+            next_attempt_at: 0,
+        }
+    }
+
+    #[test]
+    fn deliveries_by_watch() {
+        let watches = by_watch(vec![
+            delivery("a", "1", ""),
+            delivery("b", "2", ""),
+            delivery("a", "3", ""),
+        ]);
+
+        let ids = |watch: &str| {
+            watches[watch]
+                .iter()
+                .map(|d| d.payload.id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids("a"), vec!["1", "3"]);
+        assert_eq!(ids("b"), vec!["2"]);
+    }
+
+    #[actix_web::test]
+    async fn hanging_callback_times_out() {
+        // accepts connections into the backlog but never answers
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let store = WebhookStore::open(&db).unwrap();
+        let pending = delivery("a", "1", &url);
+        store.enqueue(&pending).unwrap();
+
+        deliver(&store, &RequestClient::new(), pending)
+            .await
+            .unwrap();
+
+        let logs = store.delivery_logs("a").unwrap();
+        assert_eq!(logs[0].error.as_deref(), Some("callback timed out"));
+        // retried later
+        assert!(store.due_deliveries(now()).unwrap().is_empty());
+        assert_eq!(
+            store.due_deliveries(now() + MAX_BACKOFF).unwrap()[0].attempts,
+            1
+        );
+    }
+
+    #[test]
+    fn backoff_grows_to_limit() {
+        assert_eq!(backoff(1), 10);
+        assert_eq!(backoff(2), 20);
+        assert_eq!(backoff(30), MAX_BACKOFF);
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+pub mod delivery;
+pub mod store;
+pub mod watcher;
+
+pub use store::WebhookStore;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Watch {
+    pub id: String,
+    pub address: String,
+    // hex
+    pub script_pubkey: String,
+    pub callback_url: String,
+    // confirmations after which the deposit is no longer reported
+    pub confirmations: u32,
+    // hmac key of the payload signature
+    pub secret: String,
+    pub created_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    FirstSeen,
+    Confirmation,
+    ReorgOut,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WebhookPayload {
+    // unique per event, receivers can use it for deduplication
+    pub id: String,
+    pub watch_id: String,
+    pub event: WebhookEvent,
+    pub address: String,
+    pub txid: String,
+    pub vout: u32,
+    // BTC
+    pub amount: f64,
+    pub confirmations: u32,
+    pub block_hash: Option<String>,
+    pub block_height: Option<u64>,
+    pub created_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Delivery {
+    pub payload: WebhookPayload,
+    pub callback_url: String,
+    pub secret: String,
+    pub attempts: u32,
+    pub next_attempt_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DeliveryLog {
+    pub delivery_id: String,
+    pub watch_id: String,
+    pub event: WebhookEvent,
+    pub attempt: u32,
+    pub at: u64,
+    // http status of the callback, None when the request failed
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub delivered: bool,
+}
+
+// output paying a watched script
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Deposit {
+    pub watch_id: String,
+    pub txid: String,
+    pub vout: u32,
+    pub amount: f64,
+    pub block_hash: Option<String>,
+    pub block_height: Option<u64>,
+    pub notified_confirmations: u32,
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn random_id() -> String {
+    hex::encode(rand::random::<[u8; 16]>())
+}
@@ -0,0 +1,336 @@
+use sled::{Db, Tree};
+
+use super::{Delivery, DeliveryLog, Deposit, Watch};
+use crate::storage::{decode, decode_height, encode, key};
+
+// sled trees:
+//   watches        id -> Watch
+//   watch_scripts  script hex / id -> ()
+//   deposits       watch id / txid:vout -> Deposit
+//   mined          block height (be u64) / watch id / txid:vout -> (), deposits in a block
+//   blocks         block hash -> height, blocks already handled
+//   queue          next attempt (be u64) / delivery id -> Delivery
+//   log            watch id / time (be u64) / delivery id / attempt -> DeliveryLog
+#[derive(Clone)]
+pub struct WebhookStore {
+    watches: Tree,
+    watch_scripts: Tree,
+    deposits: Tree,
+    mined: Tree,
+    blocks: Tree,
+    queue: Tree,
+    log: Tree,
+}
+
+fn queue_key(delivery: &Delivery) -> Vec<u8> {
+    key(&[
+        &delivery.next_attempt_at.to_be_bytes(),
+        delivery.payload.id.as_bytes(),
+    ])
+}
+
+impl WebhookStore {
+    pub fn open(db: &Db) -> sled::Result<WebhookStore> {
+        let store = WebhookStore {
+            watches: db.open_tree("webhook_watches")?,
+            watch_scripts: db.open_tree("webhook_watch_scripts")?,
+            deposits: db.open_tree("webhook_deposits")?,
+            mined: db.open_tree("webhook_mined")?,
+            blocks: db.open_tree("webhook_blocks")?,
+            queue: db.open_tree("webhook_queue")?,
+            log: db.open_tree("webhook_log")?,
+        };
+
+        // databases written before the mined index existed
+        if store.mined.is_empty() {
+            for deposit in store.deposits()? {
+                store.save_deposit(&deposit)?;
+            }
+        }
+
+        Ok(store)
+    }
+
+    pub fn insert_watch(&self, watch: &Watch) -> sled::Result<()> {
+        self.watches.insert(&watch.id, encode(watch)?)?;
+        self.watch_scripts.insert(
+            key(&[watch.script_pubkey.as_bytes(), watch.id.as_bytes()]),
+            &[],
+        )?;
+        Ok(())
+    }
+
+    pub fn watch(&self, id: &str) -> sled::Result<Option<Watch>> {
+        self.watches
+            .get(id)?
+            .map(|bytes| decode(&bytes))
+            .transpose()
+    }
+
+    pub fn watches(&self) -> sled::Result<Vec<Watch>> {
+        self.watches
+            .iter()
+            .values()
+            .map(|bytes| decode(&bytes?))
+            .collect()
+    }
+
+    pub fn watches_by_script(&self, script_pubkey: &str) -> sled::Result<Vec<Watch>> {
+        let mut watches = Vec::new();
+        for entry in self
+            .watch_scripts
+            .scan_prefix(key(&[script_pubkey.as_bytes(), b""]))
+        {
+            let (entry_key, _) = entry?;
+            let id = String::from_utf8_lossy(&entry_key[script_pubkey.len() + 1..]).to_string();
+            if let Some(watch) = self.watch(&id)? {
+                watches.push(watch);
+            }
+        }
+        Ok(watches)
+    }
+
+    pub fn remove_watch(&self, id: &str) -> sled::Result<Option<Watch>> {
+        let watch = match self.watch(id)? {
+            Some(watch) => watch,
+            None => return Ok(None),
+        };
+
+        self.watches.remove(id)?;
+        self.watch_scripts
+            .remove(key(&[watch.script_pubkey.as_bytes(), id.as_bytes()]))?;
+        for deposit in self
+            .deposits
+            .scan_prefix(key(&[id.as_bytes(), b""]))
+            .values()
+        {
+            self.remove_deposit(&decode(&deposit?)?)?;
+        }
+
+        Ok(Some(watch))
+    }
+
+    pub fn deposit(&self, watch_id: &str, txid: &str, vout: u32) -> sled::Result<Option<Deposit>> {
+        self.deposits
+            .get(deposit_key(watch_id, txid, vout))?
+            .map(|bytes| decode(&bytes))
+            .transpose()
+    }
+
+    pub fn deposits(&self) -> sled::Result<Vec<Deposit>> {
+        self.deposits
+            .iter()
+            .values()
+            .map(|bytes| decode(&bytes?))
+            .collect()
+    }
+
+    // deposits in a block at or below height, the ones the watcher still follows
+    pub fn mined_deposits(&self, height: u64) -> sled::Result<Vec<Deposit>> {
+        let mut deposits = Vec::new();
+        for entry in self.mined.range(..key(&[&(height + 1).to_be_bytes(), b""])) {
+            let (entry_key, _) = entry?;
+            if let Some(deposit) = self.deposits.get(&entry_key[9..])? {
+                deposits.push(decode(&deposit)?);
+            }
+        }
+        Ok(deposits)
+    }
+
+    // keeps the mined index in step with the block of the deposit
+    pub fn save_deposit(&self, deposit: &Deposit) -> sled::Result<()> {
+        let deposit_key = deposit_key(&deposit.watch_id, &deposit.txid, deposit.vout);
+
+        let previous = self.deposits.insert(&deposit_key, encode(deposit)?)?;
+        if let Some(previous_height) = previous
+            .map(|bytes| decode::<Deposit>(&bytes))
+            .transpose()?
+            .and_then(|previous| previous.block_height)
+        {
+            self.mined
+                .remove(key(&[&previous_height.to_be_bytes(), &deposit_key]))?;
+        }
+        if let Some(height) = deposit.block_height {
+            self.mined
+                .insert(key(&[&height.to_be_bytes(), &deposit_key]), &[])?;
+        }
+        Ok(())
+    }
+
+    pub fn remove_deposit(&self, deposit: &Deposit) -> sled::Result<()> {
+        let deposit_key = deposit_key(&deposit.watch_id, &deposit.txid, deposit.vout);
+
+        self.deposits.remove(&deposit_key)?;
+        if let Some(height) = deposit.block_height {
+            self.mined
+                .remove(key(&[&height.to_be_bytes(), &deposit_key]))?;
+        }
+        Ok(())
+    }
+
+    // false when the block was handled before, e.g. reported by another node
+    pub fn mark_block(&self, hash: &str, height: u64) -> sled::Result<bool> {
+        Ok(self.blocks.insert(hash, &height.to_be_bytes())?.is_none())
+    }
+
+    // height of the block, None when it was not handled
+    pub fn unmark_block(&self, hash: &str) -> sled::Result<Option<u64>> {
+        Ok(self
+            .blocks
+            .remove(hash)?
+            .map(|height| decode_height(&height)))
+    }
+
+    pub fn enqueue(&self, delivery: &Delivery) -> sled::Result<()> {
+        self.queue.insert(queue_key(delivery), encode(delivery)?)?;
+        Ok(())
+    }
+
+    pub fn due_deliveries(&self, now: u64) -> sled::Result<Vec<Delivery>> {
+        self.queue
+            .range(..(now + 1).to_be_bytes().to_vec())
+            .values()
+            .map(|bytes| decode(&bytes?))
+            .collect()
+    }
+
+    pub fn dequeue(&self, delivery: &Delivery) -> sled::Result<()> {
+        self.queue.remove(queue_key(delivery))?;
+        Ok(())
+    }
+
+    pub fn log(&self, entry: &DeliveryLog) -> sled::Result<()> {
+        self.log.insert(
+            key(&[
+                entry.watch_id.as_bytes(),
+                &entry.at.to_be_bytes(),
+                entry.delivery_id.as_bytes(),
+                &entry.attempt.to_be_bytes(),
+            ]),
+            encode(entry)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn delivery_logs(&self, watch_id: &str) -> sled::Result<Vec<DeliveryLog>> {
+        self.log
+            .scan_prefix(key(&[watch_id.as_bytes(), b""]))
+            .values()
+            .map(|bytes| decode(&bytes?))
+            .collect()
+    }
+}
+
+fn deposit_key(watch_id: &str, txid: &str, vout: u32) -> Vec<u8> {
+    key(&[watch_id.as_bytes(), format!("{}:{}", txid, vout).as_bytes()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhooks::{WebhookEvent, WebhookPayload};
+
+    fn store() -> WebhookStore {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        WebhookStore::open(&db).unwrap()
+    }
+
+    fn delivery(id: &str, next_attempt_at: u64) -> Delivery {
+        Delivery {
+            payload: WebhookPayload {
+                id: id.to_string(),
+                watch_id: "watch".to_string(),
+                event: WebhookEvent::FirstSeen,
+                address: String::new(),
+                txid: String::new(),
+                vout: 0,
+                amount: 0.0,
+                confirmations: 0,
+                block_hash: None,
+                block_height: None,
+                created_at: 0,
+            },
+            callback_url: String::new(),
+            secret: String::new(),
+            attempts: 0,
+            next_attempt_at,
+        }
+    }
+
+    #[test]
+    fn watches_by_script() {
+        let store = store();
+        let watch = Watch {
+            id: "a".to_string(),
+            address: String::new(),
+            script_pubkey: "0014aa".to_string(),
+            callback_url: String::new(),
+            confirmations: 1,
+            secret: String::new(),
+            created_at: 0,
+        };
+        store.insert_watch(&watch).unwrap();
+
+        assert_eq!(store.watches_by_script("0014aa").unwrap().len(), 1);
+        assert!(store.watches_by_script("0014").unwrap().is_empty());
+
+        store.remove_watch("a").unwrap();
+        assert!(store.watches_by_script("0014aa").unwrap().is_empty());
+    }
+
+    fn deposit(txid: &str, block_height: Option<u64>) -> Deposit {
+        Deposit {
+            watch_id: "watch".to_string(),
+            txid: txid.to_string(),
+            vout: 0,
+            amount: 0.0,
+            block_hash: None,
+            block_height,
+            notified_confirmations: 0,
+        }
+    }
+
+    #[test]
+    fn mined_deposits() {
+        let store = store();
+        store.save_deposit(&deposit("mempool", None)).unwrap();
+        store.save_deposit(&deposit("early", Some(10))).unwrap();
+        store.save_deposit(&deposit("late", Some(20))).unwrap();
+
+        let txids = |height| {
+            store
+                .mined_deposits(height)
+                .unwrap()
+                .into_iter()
+                .map(|deposit| deposit.txid)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(txids(15), vec!["early"]);
+        assert_eq!(txids(20), vec!["early", "late"]);
+
+        // moved to another block and back to the mempool
+        store.save_deposit(&deposit("early", Some(30))).unwrap();
+        assert_eq!(txids(20), vec!["late"]);
+        store.save_deposit(&deposit("late", None)).unwrap();
+        assert!(txids(20).is_empty());
+
+        store.remove_deposit(&deposit("early", Some(30))).unwrap();
+        assert!(txids(u64::MAX - 1).is_empty());
+        assert_eq!(store.deposits().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn due_deliveries() {
+        let store = store();
+        store.enqueue(&delivery("early", 10)).unwrap();
+        store.enqueue(&delivery("late", 20)).unwrap();
+
+        let due = store.due_deliveries(10).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].payload.id, "early");
+
+        store.dequeue(&due[0]).unwrap();
+        assert!(store.due_deliveries(15).unwrap().is_empty());
+        assert_eq!(store.due_deliveries(20).unwrap().len(), 1);
+    }
+}
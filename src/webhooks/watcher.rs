@@ -0,0 +1,361 @@
+use actix_web::rt;
+use bitcoin::{hashes::hex::ToHex, Amount, Block, BlockHash, Transaction};
+use log::{error, warn};
+use tokio::sync::broadcast::error::RecvError;
+
+use super::{now, random_id, Delivery, Deposit, Watch, WebhookEvent, WebhookPayload, WebhookStore};
+use crate::events::{EventBus, NodeEvent, SequenceEvent};
+
+// blocks below the tip after which a fully confirmed deposit is forgotten,
+// deeper reorgs are not reported
+const PRUNE_DEPTH: u64 = 100;
+
+// turns node events into webhook deliveries for watched scripts
+pub fn spawn_watcher(store: WebhookStore, bus: &EventBus) {
+    let mut events = bus.subscribe();
+
+    rt::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Err(err) = handle_event(&store, &event) {
+                        error!("webhook watcher failed to handle event: {}", err);
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("webhook watcher skipped {} node events", skipped)
+                }
+                Err(RecvError::Closed) => return,
+            }
+        }
+    });
+}
+
+pub fn handle_event(store: &WebhookStore, event: &NodeEvent) -> sled::Result<()> {
+    match event {
+        NodeEvent::Tx { tx, .. } => tx_seen(store, tx, None),
+        NodeEvent::Block { block, .. } => block_connected(store, block),
+        NodeEvent::Sequence {
+            sequence: SequenceEvent::BlockDisconnected(hash),
+            ..
+        } => block_disconnected(store, hash),
+        _ => Ok(()),
+    }
+}
+
+fn tx_seen(
+    store: &WebhookStore,
+    tx: &Transaction,
+    block: Option<(&BlockHash, u64)>,
+) -> sled::Result<()> {
+    let txid = tx.txid().to_string();
+
+    for (vout, out) in tx.output.iter().enumerate() {
+        let vout = vout as u32;
+
+        for watch in store.watches_by_script(&out.script_pubkey.to_hex())? {
+            let mut deposit = match store.deposit(&watch.id, &txid, vout)? {
+                Some(deposit) => deposit,
+                None => {
+                    let deposit = Deposit {
+                        watch_id: watch.id.clone(),
+                        txid: txid.clone(),
+                        vout,
+                        amount: Amount::from_sat(out.value).to_btc(),
+                        block_hash: None,
+                        block_height: None,
+                        notified_confirmations: 0,
+                    };
+                    enqueue(store, &watch, &deposit, WebhookEvent::FirstSeen, 0)?;
+                    deposit
+                }
+            };
+
+            if let Some((hash, height)) = block {
+                deposit.block_hash = Some(hash.to_string());
+                deposit.block_height = Some(height);
+            }
+            store.save_deposit(&deposit)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn block_connected(store: &WebhookStore, block: &Block) -> sled::Result<()> {
+    let hash = block.block_hash();
+    let height = match block.bip34_block_height() {
+        Ok(height) => height,
+        Err(err) => {
+            warn!(
+                "webhook watcher skips block {} without height: {}",
+                hash, err
+            );
+            return Ok(());
+        }
+    };
+
+    if !store.mark_block(&hash.to_string(), height)? {
+        return Ok(());
+    }
+
+    for tx in &block.txdata {
+        tx_seen(store, tx, Some((&hash, height)))?;
+    }
+
+    for mut deposit in store.mined_deposits(height)? {
+        let watch = match store.watch(&deposit.watch_id)? {
+            Some(watch) => watch,
+            None => {
+                store.remove_deposit(&deposit)?;
+                continue;
+            }
+        };
+        let block_height = match deposit.block_height {
+            Some(block_height) => block_height,
+            None => continue,
+        };
+
+        let confirmations = ((height - block_height + 1) as u32).min(watch.confirmations);
+        if confirmations <= deposit.notified_confirmations {
+            if confirmations == watch.confirmations && block_height + PRUNE_DEPTH <= height {
+                store.remove_deposit(&deposit)?;
+            }
+            continue;
+        }
+
+        for milestone in deposit.notified_confirmations + 1..=confirmations {
+            enqueue(
+                store,
+                &watch,
+                &deposit,
+                WebhookEvent::Confirmation,
+                milestone,
+            )?;
+        }
+        deposit.notified_confirmations = confirmations;
+        store.save_deposit(&deposit)?;
+    }
+
+    Ok(())
+}
+
+fn block_disconnected(store: &WebhookStore, hash: &BlockHash) -> sled::Result<()> {
+    let hash = hash.to_string();
+    let height = match store.unmark_block(&hash)? {
+        Some(height) => height,
+        None => return Ok(()),
+    };
+
+    for mut deposit in store.mined_deposits(height)? {
+        if deposit.block_hash.as_ref() != Some(&hash) {
+            continue;
+        }
+
+        if let Some(watch) = store.watch(&deposit.watch_id)? {
+            enqueue(store, &watch, &deposit, WebhookEvent::ReorgOut, 0)?;
+        }
+
+        deposit.block_hash = None;
+        deposit.block_height = None;
+        deposit.notified_confirmations = 0;
+        store.save_deposit(&deposit)?;
+    }
+
+    Ok(())
+}
+
+fn enqueue(
+    store: &WebhookStore,
+    watch: &Watch,
+    deposit: &Deposit,
+    event: WebhookEvent,
+    confirmations: u32,
+) -> sled::Result<()> {
+    let created_at = now();
+
+    store.enqueue(&Delivery {
+        payload: WebhookPayload {
+            id: random_id(),
+            watch_id: watch.id.clone(),
+            event,
+            address: watch.address.clone(),
+            txid: deposit.txid.clone(),
+            vout: deposit.vout,
+            amount: deposit.amount,
+            confirmations,
+            block_hash: deposit.block_hash.clone(),
+            block_height: deposit.block_height,
+            created_at,
+        },
+        callback_url: watch.callback_url.clone(),
+        secret: watch.secret.clone(),
+        attempts: 0,
+        next_attempt_at: created_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{str::FromStr, sync::Arc};
+
+    use bitcoin::{
+        blockdata::{constants::genesis_block, script::Builder},
+        hashes::Hash,
+        Address, Network, OutPoint, PackedLockTime, Script, Sequence, TxIn, TxOut, Witness,
+    };
+
+    use super::*;
+
+    const ADDRESS: &str = "mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u";
+
+    fn store() -> WebhookStore {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let store = WebhookStore::open(&db).unwrap();
+        store
+            .insert_watch(&Watch {
+                id: "watch".to_string(),
+                address: ADDRESS.to_string(),
+                script_pubkey: Address::from_str(ADDRESS).unwrap().script_pubkey().to_hex(),
+                callback_url: "http://127.0.0.1:1/hook".to_string(),
+                confirmations: 2,
+                secret: "secret".to_string(),
+                created_at: 0,
+            })
+            .unwrap();
+        store
+    }
+
+    fn payment() -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![],
+            output: vec![TxOut {
+                value: 10_000,
+                script_pubkey: Address::from_str(ADDRESS).unwrap().script_pubkey(),
+            }],
+        }
+    }
+
+    fn block(height: i64, prev: BlockHash, txs: Vec<Transaction>) -> Block {
+        let mut block = genesis_block(Network::Testnet);
+        block.header.version = 2;
+        block.header.prev_blockhash = prev;
+        block.txdata = vec![Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Builder::new().push_int(height).into_script(),
+                sequence: Sequence::MAX,
+                witness: Witness::default(),
+            }],
+            output: vec![TxOut {
+                value: 0,
+                script_pubkey: Script::new(),
+            }],
+        }];
+        block.txdata.extend(txs);
+        block
+    }
+
+    fn events(store: &WebhookStore) -> Vec<(WebhookEvent, u32)> {
+        let mut deliveries = store.due_deliveries(u64::MAX - 1).unwrap();
+        deliveries.sort_by_key(|delivery| delivery.payload.created_at);
+        deliveries
+            .iter()
+            .map(|delivery| (delivery.payload.event, delivery.payload.confirmations))
+            .collect()
+    }
+
+    #[test]
+    fn deposit_lifecycle() {
+        let store = store();
+        let tx = payment();
+        let node = "node".to_string();
+
+        handle_event(
+            &store,
+            &NodeEvent::Tx {
+                node: node.clone(),
+                tx: Arc::new(tx.clone()),
+            },
+        )
+        .unwrap();
+        assert_eq!(events(&store), vec![(WebhookEvent::FirstSeen, 0)]);
+
+        let first = block(100, BlockHash::all_zeros(), vec![tx]);
+        let second = block(101, first.block_hash(), vec![]);
+        let third = block(102, second.block_hash(), vec![]);
+        for block in [&first, &first, &second, &third] {
+            handle_event(
+                &store,
+                &NodeEvent::Block {
+                    node: node.clone(),
+                    block: Arc::new(block.clone()),
+                },
+            )
+            .unwrap();
+        }
+
+        // duplicated block and confirmations past the watch limit are not reported
+        let mut reported = events(&store);
+        reported.sort_by_key(|(_, confirmations)| *confirmations);
+        assert_eq!(
+            reported,
+            vec![
+                (WebhookEvent::FirstSeen, 0),
+                (WebhookEvent::Confirmation, 1),
+                (WebhookEvent::Confirmation, 2),
+            ]
+        );
+
+        handle_event(
+            &store,
+            &NodeEvent::Sequence {
+                node,
+                sequence: SequenceEvent::BlockDisconnected(first.block_hash()),
+            },
+        )
+        .unwrap();
+
+        assert!(events(&store).contains(&(WebhookEvent::ReorgOut, 0)));
+        let deposit = &store.deposits().unwrap()[0];
+        assert_eq!(deposit.block_hash, None);
+        assert_eq!(deposit.notified_confirmations, 0);
+    }
+
+    #[test]
+    fn confirmed_deposits_are_pruned() {
+        let store = store();
+        let node = "node".to_string();
+
+        let mut prev = BlockHash::all_zeros();
+        for height in 100..100 + PRUNE_DEPTH as i64 + 1 {
+            let txs = if height == 100 {
+                vec![payment()]
+            } else {
+                vec![]
+            };
+            let block = block(height, prev, txs);
+            prev = block.block_hash();
+            handle_event(
+                &store,
+                &NodeEvent::Block {
+                    node: node.clone(),
+                    block: Arc::new(block),
+                },
+            )
+            .unwrap();
+
+            // followed until buried deep enough
+            let expected = if (height as u64) < 100 + PRUNE_DEPTH {
+                1
+            } else {
+                0
+            };
+            assert_eq!(store.deposits().unwrap().len(), expected);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use actix_web::{http, test, web, App};
+use multi_nodes::{config::Config, request};
+
+#[actix_web::test]
+async fn address_utxos_invalid_address() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/address/not-an-address/utxos")
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+}
+
+#[ignore = "comment this when you up your node"]
+#[actix_web::test]
+async fn address_balance() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/address/mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u/balance")
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), http::StatusCode::OK);
+}
@@ -0,0 +1,78 @@
+use actix_web::{http, test, web, App};
+use multi_nodes::{config::Config, request};
+
+#[actix_web::test]
+async fn block_invalid_hash() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/block/not-a-hash/header")
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+}
+
+#[ignore = "comment this when you up your node"]
+#[actix_web::test]
+async fn block_by_height() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/block-height/1?verbosity=transactions&page=1&limit=10")
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), http::StatusCode::OK);
+}
+
+#[ignore = "comment this when you up your node"]
+#[actix_web::test]
+async fn block_out_of_range() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/block-height/100000000")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+
+    // page past the end is empty, not an overflow
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/api/bitcoin/block-height/1?page={}&limit=1000",
+            usize::MAX
+        ))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+}
@@ -1,9 +1,18 @@
+use std::sync::Arc;
+
 use actix_web::{http, test, web, App};
 use multi_nodes::{
-    api::btc::handler::{CreateTxRequest, ToAddresses, Utxo},
+    api::{
+        btc::{
+            handler::{CreateTxRequest, ToAddresses, Utxo},
+            BitcoinNode,
+        },
+        chain::{self, ChainNodes},
+    },
     config::Config,
     request,
 };
+use serde_json::json;
 // use multi_nodes::
 
 #[ignore = "comment this when you up your node"]
@@ -15,7 +24,7 @@ async fn create_tx() {
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(request_client.clone()))
-            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
             .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
     )
     .await;
@@ -28,12 +37,16 @@ async fn create_tx() {
             vout: Some(1),
             amount: Some(0.001),
             pk_script: Some("76a914690cd6356789d30b99063632e0651a8d0c206c7f88ac".to_string()),
+            witness_script: None,
+            leaf_script: None,
+            control_block: None,
         }]),
         to: Some(vec![ToAddresses {
             to_address: Some("mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u".to_string()),
             amount: Some(0.0001),
         }]),
         change_address: Some("mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u".to_string()),
+        change_derivation: None,
     };
 
     let req = test::TestRequest::post()
@@ -45,3 +58,39 @@ async fn create_tx() {
 
     assert_eq!(resp.status(), http::StatusCode::OK);
 }
+
+#[actix_web::test]
+async fn create_tx_generic_body() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+    let nodes: ChainNodes = vec![Arc::new(BitcoinNode::new(
+        request_client.clone(),
+        cfg.bitcoin_rpc_config.unwrap(),
+    ))];
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .service(web::scope("/api").configure(|cfg| chain::init(cfg, &nodes))),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/create-tx")
+        .set_json(json!({
+            "utxos": [{
+                "tx_id": "989d301c546841d0ac5c8354c7d78079e3603b089682d1639b2ee1c1a8010c6a",
+                "vout": 1,
+                "amount": 0.001,
+                "pk_script": "76a914690cd6356789d30b99063632e0651a8d0c206c7f88ac"
+            }],
+            "to": [{ "address": "mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u", "amount": 0.0001 }],
+            "change_address": "mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u"
+        }))
+        .to_request();
+
+    // the generic body passes validation and goes on to the node
+    let resp = test::call_service(&app, req).await;
+    assert_ne!(resp.status(), http::StatusCode::BAD_REQUEST);
+    assert_ne!(resp.status(), http::StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,42 @@
+use actix_web::{http, test, web, App};
+use multi_nodes::{config::Config, request};
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn derive_addresses() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/derive")
+        .set_json(json!({
+            "key": "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs",
+            "indices": [0, 1]
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+
+    let addresses: Value = test::read_body_json(resp).await;
+    assert_eq!(addresses.as_array().unwrap().len(), 2);
+    assert_eq!(
+        addresses[1]["address"],
+        "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g"
+    );
+    assert_eq!(addresses[1]["path"], "m/84'/0'/0'/0/1");
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/derive")
+        .set_json(json!({ "key": "xpub-not-a-key" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+}
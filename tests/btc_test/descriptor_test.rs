@@ -0,0 +1,69 @@
+use actix_web::{http, test, web, App};
+use multi_nodes::{config::Config, request};
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn invalid_descriptor_requests() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/wallets/watch-only/descriptors")
+        .set_json(json!({ "descriptors": [] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/wallets/watch-only/descriptors/utxos?range_end=5")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+}
+
+#[ignore = "comment this when you up your node"]
+#[actix_web::test]
+async fn import_and_query_descriptor() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let desc = "wpkh(tpubD6NzVbkrYhZ4XgiXtGrdW5XDAPFCL9h7we1vwNCpn8tGbBcgfVYjXyhWo4E1xkh56hjod1RhGjxbaTLV3X4FyWuejifB9jusQ46QzG87VKp/0/*)";
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/wallets")
+        .set_json(json!({ "name": "watch-only", "disable_private_keys": true, "blank": true }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/wallets/watch-only/descriptors")
+        .set_json(json!({ "descriptors": [{ "desc": desc, "range": [0, 20] }] }))
+        .to_request();
+    let results: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(results[0]["success"], true);
+
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/api/bitcoin/wallets/watch-only/descriptors/balance?desc={}&range_end=20",
+            desc.replace('/', "%2F").replace('*', "%2A")
+        ))
+        .to_request();
+    let balance: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(balance["address_count"], 21);
+}
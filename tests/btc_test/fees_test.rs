@@ -0,0 +1,37 @@
+use actix_web::{http, test, web, App};
+use multi_nodes::{config::Config, request};
+use serde_json::Value;
+
+#[ignore = "comment this when you up your node"]
+#[actix_web::test]
+async fn fees() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/fees")
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+
+    // every target is estimated in both modes
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(
+        body.as_array().unwrap().len(),
+        cfg.bitcoin_rpc_config
+            .as_ref()
+            .unwrap()
+            .bitcoin_fee_targets
+            .len()
+            * 2
+    );
+}
@@ -0,0 +1,53 @@
+use actix_web::{http, test, web, App};
+use multi_nodes::{config::Config, indexer::IndexStore, request};
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn register_and_query_indexed_address() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let store = IndexStore::open(&db).unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .app_data(web::Data::new(store))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/index/address/mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u/utxos")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/index/addresses")
+        .set_json(json!({ "address": "not-an-address" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/index/addresses")
+        .set_json(json!({ "address": "mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::CREATED);
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/index/address/mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u/utxos")
+        .to_request();
+    let utxos: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(utxos.as_array().unwrap().len(), 0);
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/index")
+        .to_request();
+    let status: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(status["addresses"], 1);
+    assert!(status["height"].is_null());
+}
@@ -0,0 +1,62 @@
+use actix_web::{http, test, web, App};
+use multi_nodes::{config::Config, request};
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn generate_and_derive_keys() {
+    let request_client = request::RequestClient::new();
+    let mut btc_rpc_cfg = Config::init().bitcoin_rpc_config.unwrap();
+    btc_rpc_cfg.bitcoin_key_tools_enabled = true;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(btc_rpc_cfg))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/keys/mnemonic")
+        .set_json(json!({ "word_count": 24 }))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    let mnemonic = body["mnemonic"].as_str().unwrap();
+    assert_eq!(mnemonic.split(' ').count(), 24);
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/keys/derive")
+        .set_json(json!({ "mnemonic": mnemonic, "network": "regtest" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+
+    let key: Value = test::read_body_json(resp).await;
+    assert!(key["xprv"].as_str().unwrap().starts_with("tprv"));
+    assert!(key["addresses"]["p2tr"]
+        .as_str()
+        .unwrap()
+        .starts_with("bcrt1p"));
+}
+
+#[actix_web::test]
+async fn key_tools_disabled() {
+    let request_client = request::RequestClient::new();
+    let mut btc_rpc_cfg = Config::init().bitcoin_rpc_config.unwrap();
+    btc_rpc_cfg.bitcoin_key_tools_enabled = false;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(btc_rpc_cfg))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/keys/mnemonic")
+        .set_json(json!({}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+}
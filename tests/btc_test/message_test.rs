@@ -0,0 +1,77 @@
+use actix_web::{http, test, web, App};
+use multi_nodes::{config::Config, request};
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn sign_and_verify_message() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/message/sign")
+        .set_json(json!({
+            "address": "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l",
+            "message": "withdraw 0.1 BTC",
+            "private_key": "L3VFeEujGtevx9w18HD1fhRbCH67Az2dpCymeRE1SoPK6XQtaN2k"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+
+    let signed: Value = test::read_body_json(resp).await;
+    assert_eq!(signed["format"], "bip322");
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/message/verify")
+        .set_json(json!({
+            "address": "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l",
+            "message": "withdraw 0.1 BTC",
+            "signature": signed["signature"]
+        }))
+        .to_request();
+    let verified: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(verified["valid"], true);
+
+    // nested segwit with the same key
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/message/sign")
+        .set_json(json!({
+            "address": "37qyp7jQAzqb2rCBpMvVtLDuuzKAUCVnJb",
+            "message": "withdraw 0.1 BTC",
+            "private_key": "L3VFeEujGtevx9w18HD1fhRbCH67Az2dpCymeRE1SoPK6XQtaN2k"
+        }))
+        .to_request();
+    let signed: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(signed["format"], "bip322");
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/message/verify")
+        .set_json(json!({
+            "address": "37qyp7jQAzqb2rCBpMvVtLDuuzKAUCVnJb",
+            "message": "withdraw 0.1 BTC",
+            "signature": signed["signature"]
+        }))
+        .to_request();
+    let verified: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(verified["valid"], true);
+
+    // key of another address
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/message/sign")
+        .set_json(json!({
+            "address": "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+            "message": "withdraw 0.1 BTC",
+            "private_key": "L3VFeEujGtevx9w18HD1fhRbCH67Az2dpCymeRE1SoPK6XQtaN2k"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+}
@@ -1,2 +1,22 @@
+mod address_test;
+mod block_test;
 mod create_tx_test;
+mod derive_test;
+mod descriptor_test;
+mod fees_test;
+mod index_test;
+mod keys_test;
+mod mempool_test;
+mod message_test;
+mod multisig_test;
+mod reorg_test;
+mod rest_test;
+mod rpc_test;
+mod script_test;
+mod send_tx_test;
 mod status_test;
+mod taproot_test;
+mod test_tx_test;
+mod tx_status_test;
+mod wallet_test;
+mod watch_test;
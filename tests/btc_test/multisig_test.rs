@@ -0,0 +1,54 @@
+use actix_web::{http, test, web, App};
+use multi_nodes::{config::Config, request};
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn create_multisig() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let public_keys = [
+        "02ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f8",
+        "02fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f",
+        "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+    ];
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/multisig")
+        .set_json(json!({ "required": 2, "public_keys": public_keys, "network": "testnet" }))
+        .to_request();
+    let multisig: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(multisig["script_type"], "p2wsh");
+    assert!(multisig["address"].as_str().unwrap().starts_with("tb1q"));
+    assert_eq!(multisig["public_keys"][0], public_keys[2]);
+    assert!(multisig["redeem_script"].is_null());
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/multisig")
+        .set_json(json!({
+            "required": 2,
+            "public_keys": public_keys,
+            "script_type": "p2sh-p2wsh",
+            "network": "testnet"
+        }))
+        .to_request();
+    let nested: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(nested["address"].as_str().unwrap().starts_with('2'));
+    assert_eq!(nested["witness_script"], multisig["witness_script"]);
+    assert_eq!(nested["redeem_script"], multisig["script_pubkey"]);
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/multisig")
+        .set_json(json!({ "required": 4, "public_keys": public_keys }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+}
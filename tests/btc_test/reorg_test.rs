@@ -0,0 +1,24 @@
+use actix_web::{test, web, App};
+use multi_nodes::{config::Config, events::tip::TipTracker, request};
+use serde_json::Value;
+
+#[actix_web::test]
+async fn empty_reorg_history() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .app_data(web::Data::new(TipTracker::new()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/reorgs")
+        .to_request();
+    let reorgs: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(reorgs.as_array().unwrap().len(), 0);
+}
@@ -0,0 +1,98 @@
+use actix_web::{http, test, web, App};
+use multi_nodes::{
+    config::{BitcoinRpcConfig, Config},
+    request,
+};
+
+#[actix_web::test]
+async fn status_rest_node_down() {
+    let request_client = request::RequestClient::new();
+    let mut cfg = Config::init();
+    cfg.bitcoin_rpc_config.as_mut().unwrap().bitcoin_rest_urls =
+        vec![Some("http://127.0.0.1:1".to_string())];
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/status")
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), http::StatusCode::REQUEST_TIMEOUT);
+}
+
+#[actix_web::test]
+async fn rest_url_per_node() {
+    let cfg = BitcoinRpcConfig {
+        bitcoin_rpc_url_one: "http://node-a:8332".to_string(),
+        bitcoin_rpc_urls: vec![
+            "http://node-a:8332".to_string(),
+            "http://node-b:8332".to_string(),
+            "http://node-c:8332".to_string(),
+        ],
+        bitcoin_rest_urls: vec![None, Some("http://node-b:8080".to_string())],
+        ..Default::default()
+    };
+
+    assert_eq!(cfg.rest_url("http://node-a:8332"), None);
+    assert_eq!(
+        cfg.rest_url("http://node-b:8332"),
+        Some("http://node-b:8080")
+    );
+    assert_eq!(cfg.rest_url("http://node-c:8332"), None);
+    assert_eq!(cfg.rest_url("http://unknown:8332"), None);
+}
+
+#[actix_web::test]
+async fn tx_raw_invalid_format() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/tx/989d301c546841d0ac5c8354c7d78079e3603b089682d1639b2ee1c1a8010c6a/raw?format=json")
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+}
+
+#[ignore = "comment this when you up your node"]
+#[actix_web::test]
+async fn block_raw_rest() {
+    let request_client = request::RequestClient::new();
+    let mut cfg = Config::init();
+    let btc_rpc_cfg = cfg.bitcoin_rpc_config.as_mut().unwrap();
+    btc_rpc_cfg.bitcoin_rest_urls = vec![Some(btc_rpc_cfg.bitcoin_rpc_url_one.clone())];
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/block/000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943/raw?format=bin")
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), http::StatusCode::OK);
+}
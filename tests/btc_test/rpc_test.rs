@@ -0,0 +1,106 @@
+use actix_web::{http, test, web, App};
+use multi_nodes::{
+    config::{Config, RpcApiKey},
+    request,
+};
+use serde_json::{json, Value};
+
+fn rpc_config() -> multi_nodes::config::BitcoinRpcConfig {
+    let mut btc_rpc_cfg = Config::init().bitcoin_rpc_config.unwrap();
+    btc_rpc_cfg.bitcoin_rpc_allowlist = vec!["getblockcount".to_string(), "getblock".to_string()];
+    btc_rpc_cfg.bitcoin_rpc_denylist = vec!["stop".to_string()];
+    btc_rpc_cfg.bitcoin_rpc_api_keys = vec![RpcApiKey {
+        name: "ops".to_string(),
+        key: "secret".to_string(),
+        methods: vec!["getblockcount".to_string(), "stop".to_string()],
+    }];
+    btc_rpc_cfg
+}
+
+#[actix_web::test]
+async fn rpc_passthrough_permissions() {
+    let request_client = request::RequestClient::new();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(rpc_config()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let call = |key: &str, method: &str| {
+        test::TestRequest::post()
+            .uri("/api/bitcoin/rpc")
+            .insert_header(("X-API-Key", key))
+            .set_json(json!({ "method": method }))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, call("wrong", "getblockcount")).await;
+    assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
+
+    let resp = test::call_service(&app, call("secret", "stop")).await;
+    assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+
+    let resp = test::call_service(&app, call("secret", "getblock")).await;
+    assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+
+    let resp = test::call_service(&app, call("secret", "getbestblockhash")).await;
+    assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/rpc")
+        .insert_header(("X-API-Key", "secret"))
+        .set_json(json!({ "method": "getblockcount", "params": "1" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn rpc_passthrough_disabled() {
+    let request_client = request::RequestClient::new();
+    let mut btc_rpc_cfg = rpc_config();
+    btc_rpc_cfg.bitcoin_rpc_api_keys.clear();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(btc_rpc_cfg))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/rpc")
+        .insert_header(("X-API-Key", "secret"))
+        .set_json(json!({ "method": "getblockcount" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+}
+
+#[ignore = "comment this when you up your node"]
+#[actix_web::test]
+async fn rpc_passthrough() {
+    let request_client = request::RequestClient::new();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(rpc_config()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/rpc")
+        .insert_header(("X-API-Key", "secret"))
+        .set_json(json!({ "method": "getblockcount", "params": [] }))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+
+    assert!(body["result"].as_u64().is_some());
+    assert!(body["error"].is_null());
+}
@@ -0,0 +1,42 @@
+use actix_web::{http, test, web, App};
+use multi_nodes::{config::Config, request};
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn address_info_and_script_decode() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/address/mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u/info")
+        .to_request();
+    let info: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(info["network"], "testnet");
+    assert_eq!(info["address_type"], "p2pkh");
+    assert_eq!(
+        info["script_pubkey"],
+        "76a91443738e06bb02b07ad9c67e9480918d5df41fe35588ac"
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/script/decode")
+        .set_json(json!({ "hex": info["script_pubkey"], "network": "testnet" }))
+        .to_request();
+    let script: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(script["script_type"], "p2pkh");
+    assert_eq!(script["address"], "mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u");
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/address/not-an-address/info")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use actix_web::{http, test, web, App};
+use multi_nodes::{
+    api::{
+        btc::{handler::SendTxRequest, BitcoinNode},
+        chain::{self, ChainNodes},
+    },
+    config::Config,
+    request,
+};
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn send_tx_not_accepted() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let data = SendTxRequest {
+        signed_tx: Some("00".to_string()),
+        dry_run: None,
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/send-tx")
+        .set_json(&data)
+        .to_request();
+
+    // rejected by a running node, a transport failure when it is down
+    let resp = test::call_service(&app, req).await;
+    let status = resp.status();
+
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["success"], false);
+    match body["nodes"][0]["status"].as_str().unwrap() {
+        "rejected" => assert_eq!(status, http::StatusCode::BAD_REQUEST),
+        "unreachable" => assert_eq!(status, http::StatusCode::BAD_GATEWAY),
+        other => panic!("unexpected node status {}", other),
+    }
+    assert_eq!(
+        body["nodes"].as_array().unwrap().len(),
+        cfg.bitcoin_rpc_config
+            .as_ref()
+            .unwrap()
+            .bitcoin_rpc_urls
+            .len()
+    );
+}
+
+#[actix_web::test]
+async fn send_tx_dry_run_on_chain_route() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+    let nodes: ChainNodes = vec![Arc::new(BitcoinNode::new(
+        request_client.clone(),
+        cfg.bitcoin_rpc_config.unwrap(),
+    ))];
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .service(web::scope("/api").configure(|cfg| chain::init(cfg, &nodes))),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/send-tx")
+        .set_json(json!({ "signed_tx": "00", "dry_run": true }))
+        .to_request();
+
+    // checked with testmempoolaccept, never broadcast to the nodes
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(body["nodes"].is_null());
+}
@@ -10,7 +10,7 @@ async fn status() {
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(request_client.clone()))
-            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
             .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
     )
     .await;
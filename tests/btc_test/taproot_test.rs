@@ -0,0 +1,77 @@
+use actix_web::{http, test, web, App};
+use bitcoin::{
+    consensus::encode,
+    hashes::{
+        hex::{FromHex, ToHex},
+        Hash,
+    },
+    secp256k1::{Secp256k1, SecretKey},
+    Address, Network, OutPoint, PackedLockTime, PrivateKey, Script, Sequence, Transaction, TxIn,
+    TxOut, Txid, Witness,
+};
+use multi_nodes::{config::Config, request};
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn taproot_key_path_sign() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let secp = Secp256k1::new();
+    let private_key = PrivateKey::new(SecretKey::from_slice(&[7; 32]).unwrap(), Network::Testnet);
+    let (internal_key, _) = private_key.public_key(&secp).inner.x_only_public_key();
+    let address = Address::p2tr(&secp, internal_key, None, Network::Testnet);
+
+    let tx = Transaction {
+        version: 2,
+        lock_time: PackedLockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(Txid::all_zeros(), 0),
+            script_sig: Script::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::default(),
+        }],
+        output: vec![TxOut {
+            value: 90_000,
+            script_pubkey: address.script_pubkey(),
+        }],
+    };
+    let prevouts = json!([{ "amount": 0.001, "pk_script": address.script_pubkey().to_hex() }]);
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/taproot/sign")
+        .set_json(json!({
+            "raw_tx": encode::serialize_hex(&tx),
+            "prevouts": prevouts,
+            "inputs": [{ "index": 0, "private_key": private_key.to_wif() }]
+        }))
+        .to_request();
+    let signed: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(signed["complete"], true);
+
+    let signed_tx: Transaction =
+        encode::deserialize(&Vec::<u8>::from_hex(signed["hex"].as_str().unwrap()).unwrap())
+            .unwrap();
+    assert_eq!(signed_tx.input[0].witness.len(), 1);
+    assert_eq!(signed_tx.input[0].witness.to_vec()[0].len(), 64);
+
+    let other_key = PrivateKey::new(SecretKey::from_slice(&[8; 32]).unwrap(), Network::Testnet);
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/taproot/sign")
+        .set_json(json!({
+            "raw_tx": encode::serialize_hex(&tx),
+            "prevouts": prevouts,
+            "inputs": [{ "index": 0, "private_key": other_key.to_wif() }]
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+}
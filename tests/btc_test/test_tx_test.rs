@@ -0,0 +1,57 @@
+use actix_web::{http, test, web, App};
+use multi_nodes::{api::btc::handler::TestTxRequest, config::Config, request};
+
+#[actix_web::test]
+async fn test_tx_empty() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let data = TestTxRequest {
+        signed_txs: Some(vec![]),
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/test-tx")
+        .set_json(&data)
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+}
+
+#[ignore = "comment this when you up your node"]
+#[actix_web::test]
+async fn test_tx() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let data = TestTxRequest {
+        signed_txs: Some(vec!["0200000001c0c1b2d5d6d9a5b4c03e0e5f7d7c2e0b5f6a2f0d9e1c3b4a5f6e7d8c9b0a1f2e0100000000ffffffff0110270000000000001976a914690cd6356789d30b99063632e0651a8d0c206c7f88ac00000000".to_string()]),
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/test-tx")
+        .set_json(&data)
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), http::StatusCode::OK);
+}
@@ -0,0 +1,91 @@
+use actix_web::{http, test, web, App};
+use multi_nodes::{config::Config, request, webhooks::WebhookStore};
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn create_watch_rejects_bad_input() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let store = WebhookStore::open(&db).unwrap();
+
+    let mut btc_rpc_cfg = cfg.bitcoin_rpc_config.clone().unwrap();
+    btc_rpc_cfg.bitcoin_webhook_allow_private = false;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(btc_rpc_cfg))
+            .app_data(web::Data::new(store))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    for body in [
+        json!({ "address": "not-an-address", "callback_url": "https://example.com/hook" }),
+        json!({
+            "address": "mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u",
+            "callback_url": "http://127.0.0.1:8080/hook"
+        }),
+        json!({
+            "address": "mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u",
+            "callback_url": "http://169.254.169.254/latest/meta-data"
+        }),
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/api/bitcoin/watches")
+            .set_json(body)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+    }
+}
+
+#[actix_web::test]
+#[ignore = "comment this when you up your node"]
+async fn create_and_list_watches() {
+    let request_client = request::RequestClient::new();
+    let cfg = Config::init();
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let store = WebhookStore::open(&db).unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .app_data(web::Data::new(cfg.bitcoin_rpc_config.clone().unwrap()))
+            .app_data(web::Data::new(store))
+            .service(web::scope("/api/bitcoin").configure(multi_nodes::api::init_bitcoin_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/bitcoin/watches")
+        .set_json(json!({
+            "address": "mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u",
+            "callback_url": "https://example.com/hook",
+            "confirmations": 3
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::CREATED);
+
+    let created: Value = test::read_body_json(resp).await;
+    assert!(created["secret"].is_string());
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/watches")
+        .to_request();
+    let watches: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(watches.as_array().unwrap().len(), 1);
+    assert_eq!(watches[0]["id"], created["id"]);
+    assert!(watches[0]["secret"].is_null());
+
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/api/bitcoin/watches/{}/deliveries",
+            created["id"].as_str().unwrap()
+        ))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+}
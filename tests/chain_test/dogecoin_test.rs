@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use actix_web::{http, test, web, App};
+use multi_nodes::{
+    api::{
+        chain::{self, ChainNodes},
+        doge::DogecoinNode,
+    },
+    config::{BitcoinRpcConfig, Config},
+    request,
+};
+use serde_json::{json, Value};
+
+fn create_tx(pk_script: &str, to: &str, amount: f64) -> Value {
+    json!({
+        "utxos": [{
+            "tx_id": "989d301c546841d0ac5c8354c7d78079e3603b089682d1639b2ee1c1a8010c6a",
+            "vout": 0,
+            "amount": 10.0,
+            "pk_script": pk_script
+        }],
+        "to": [{ "address": to, "amount": amount }],
+        "change_address": "DFpN6QqFfUm3gKNaxN6tNcab1FArL9cZLE"
+    })
+}
+
+#[actix_web::test]
+async fn dogecoin_rejects_invalid_create_tx() {
+    let doge_rpc_cfg = BitcoinRpcConfig {
+        bitcoin_rpc_url_one: "http://127.0.0.1:44555".to_string(),
+        bitcoin_rpc_urls: vec!["http://127.0.0.1:44555".to_string()],
+        ..Default::default()
+    };
+    let nodes: ChainNodes = vec![Arc::new(DogecoinNode::new(
+        request::RequestClient::new(),
+        doge_rpc_cfg,
+        0.01,
+    ))];
+
+    let app = test::init_service(
+        App::new().service(web::scope("/api").configure(|cfg| chain::init(cfg, &nodes))),
+    )
+    .await;
+
+    let p2pkh = "76a914751e76e8199196d454941c45d1b3a323f1433bd688ac";
+    let p2wpkh = "0014751e76e8199196d454941c45d1b3a323f1433bd6";
+    let doge = "DFpN6QqFfUm3gKNaxN6tNcab1FArL9cZLE";
+
+    for body in [
+        // bitcoin addresses
+        create_tx(p2pkh, "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2", 1.0),
+        create_tx(p2pkh, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4", 1.0),
+        // below the hard dust limit
+        create_tx(p2pkh, doge, 0.0009),
+        // segwit input
+        create_tx(p2wpkh, doge, 1.0),
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/api/dogecoin/create-tx")
+            .set_json(body)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/api/dogecoin/tx/not-a-txid")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+}
+
+#[ignore = "comment this when you up your node"]
+#[actix_web::test]
+async fn dogecoin_status() {
+    let request_client = request::RequestClient::new();
+    // needs CHAINS=bitcoin,dogecoin and the DOGECOIN_RPC_* variables
+    let cfg = Config::init();
+    let nodes = chain::nodes(&cfg, &request_client);
+
+    let app = test::init_service(
+        App::new().service(web::scope("/api").configure(|cfg| chain::init(cfg, &nodes))),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/dogecoin/status")
+        .to_request();
+    let status: Value = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(status["chain"], "dogecoin");
+    assert!(status["mweb"].is_null());
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use actix_web::{http, test, web, App};
+use multi_nodes::{
+    api::{
+        chain::{self, ChainNodes},
+        ltc::LitecoinNode,
+    },
+    config::{BitcoinRpcConfig, Config},
+    request,
+};
+use serde_json::{json, Value};
+
+fn create_tx(to: &str) -> Value {
+    json!({
+        "utxos": [{
+            "tx_id": "989d301c546841d0ac5c8354c7d78079e3603b089682d1639b2ee1c1a8010c6a",
+            "vout": 0,
+            "amount": 0.01,
+            "pk_script": "0014751e76e8199196d454941c45d1b3a323f1433bd6"
+        }],
+        "to": [{ "address": to, "amount": 0.001 }],
+        "change_address": "ltc1qw508d6qejxtdg4y5r3zarvary0c5xw7kgmn4n9"
+    })
+}
+
+#[actix_web::test]
+async fn litecoin_rejects_foreign_addresses() {
+    let ltc_rpc_cfg = BitcoinRpcConfig {
+        bitcoin_rpc_url_one: "http://127.0.0.1:19332".to_string(),
+        bitcoin_rpc_urls: vec!["http://127.0.0.1:19332".to_string()],
+        ..Default::default()
+    };
+    let nodes: ChainNodes = vec![Arc::new(LitecoinNode::new(
+        request::RequestClient::new(),
+        ltc_rpc_cfg,
+    ))];
+
+    let app = test::init_service(
+        App::new().service(web::scope("/api").configure(|cfg| chain::init(cfg, &nodes))),
+    )
+    .await;
+
+    for address in [
+        "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+        "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2",
+        "ltcmweb1qqv9q2pmkf5nkrgqzvcgv5eh0uvgkkfhjwkv9vrmnn8m6ugzhp7nw5qjm8rd3c",
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/api/litecoin/create-tx")
+            .set_json(create_tx(address))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/api/litecoin/tx/not-a-txid")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+}
+
+#[ignore = "comment this when you up your node"]
+#[actix_web::test]
+async fn litecoin_status() {
+    let request_client = request::RequestClient::new();
+    // needs CHAINS=bitcoin,litecoin and the LITECOIN_RPC_* variables
+    let cfg = Config::init();
+    let nodes = chain::nodes(&cfg, &request_client);
+
+    let app = test::init_service(
+        App::new().service(web::scope("/api").configure(|cfg| chain::init(cfg, &nodes))),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/litecoin/status")
+        .to_request();
+    let status: Value = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(status["chain"], "litecoin");
+    assert!(status["blocks"].as_u64().is_some());
+}
@@ -0,0 +1,3 @@
+mod dogecoin_test;
+mod litecoin_test;
+mod node_test;
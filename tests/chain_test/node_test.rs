@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use actix_web::{http, test, web, App, HttpResponse};
+use async_trait::async_trait;
+use multi_nodes::api::chain::{
+    self,
+    handler::BuildTxRequest,
+    model::{
+        Broadcast, BroadcastNodeResult, BroadcastStatus, ChainFee, ChainStatus, SignTxResult,
+        TxStatus, UnsignedTx,
+    },
+    ChainNode, ChainNodes,
+};
+use serde_json::{json, Value};
+
+// a chain only implementing the trait, served by the generic routes
+struct TestNode;
+
+#[async_trait(?Send)]
+impl ChainNode for TestNode {
+    fn name(&self) -> &'static str {
+        "testcoin"
+    }
+
+    async fn status(&self) -> Result<ChainStatus, HttpResponse> {
+        Ok(ChainStatus {
+            chain: self.name().to_string(),
+            network: "regtest".to_string(),
+            blocks: 10,
+            headers: 10,
+            best_block_hash: "00".repeat(32),
+            verification_progress: 1.0,
+            initial_block_download: false,
+            mweb: None,
+        })
+    }
+
+    async fn estimate_fee(&self, target: u16) -> Result<ChainFee, HttpResponse> {
+        Ok(ChainFee {
+            target,
+            fee_rate: 0.0001,
+        })
+    }
+
+    async fn build_tx(&self, request: &BuildTxRequest) -> Result<UnsignedTx, HttpResponse> {
+        Ok(UnsignedTx {
+            hex: request.change_address.clone().unwrap(),
+            psbt: None,
+            fee_rate: 0.0001,
+        })
+    }
+
+    async fn sign_tx(
+        &self,
+        raw_tx: &str,
+        _private_keys: &[String],
+    ) -> Result<SignTxResult, HttpResponse> {
+        Ok(SignTxResult {
+            hex: raw_tx.to_string(),
+            complete: true,
+            errors: None,
+        })
+    }
+
+    async fn broadcast(&self, _signed_tx: &str) -> Result<Broadcast, HttpResponse> {
+        Ok(Broadcast {
+            success: false,
+            txid: None,
+            nodes: vec![BroadcastNodeResult {
+                node: "test".to_string(),
+                status: BroadcastStatus::Rejected,
+                reason: Some("bad-txns".to_string()),
+            }],
+        })
+    }
+
+    async fn tx_status(&self, _txid: &str) -> Result<TxStatus, HttpResponse> {
+        Err(HttpResponse::NotFound().finish())
+    }
+}
+
+#[actix_web::test]
+async fn generic_chain_routes() {
+    let nodes: ChainNodes = vec![Arc::new(TestNode)];
+
+    let app = test::init_service(
+        App::new().service(web::scope("/api").configure(|cfg| chain::init(cfg, &nodes))),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/api/chains").to_request();
+    let chains: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(chains[0]["chain"], "testcoin");
+    assert_eq!(chains[0]["status"]["blocks"], 10);
+
+    let req = test::TestRequest::get()
+        .uri("/api/testcoin/fees?target=3")
+        .to_request();
+    let fee: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(fee["target"], 3);
+
+    let req = test::TestRequest::post()
+        .uri("/api/testcoin/create-tx")
+        .set_json(json!({
+            "utxos": [{ "tx_id": "aa", "vout": 0, "amount": 1.0, "pk_script": "00" }],
+            "to": [{ "address": "to", "amount": 0.5 }],
+            "change_address": "change"
+        }))
+        .to_request();
+    let tx: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(tx["hex"], "change");
+
+    let req = test::TestRequest::post()
+        .uri("/api/testcoin/create-tx")
+        .set_json(json!({ "utxos": [], "to": [], "change_address": "change" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri("/api/testcoin/send-tx")
+        .set_json(json!({ "signed_tx": "00" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/testcoin/tx/{}", "00".repeat(32)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/status")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn configured_bitcoin_routes() {
+    let request_client = multi_nodes::request::RequestClient::new();
+    let cfg = multi_nodes::config::Config::init();
+    let nodes = chain::nodes(&cfg, &request_client);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(request_client.clone()))
+            .service(web::scope("/api").configure(|cfg| chain::init(cfg, &nodes))),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/bitcoin/address/mmfbzo2533SFa34ErmYNY4RdVtfw5XYK1u/info")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+
+    let req = test::TestRequest::get().uri("/api/chains").to_request();
+    let chains: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(chains[0]["chain"], "bitcoin");
+}
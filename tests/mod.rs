@@ -1,4 +1,6 @@
 #[cfg(test)]
 mod btc_test;
 #[cfg(test)]
+mod chain_test;
+#[cfg(test)]
 mod health;